@@ -25,6 +25,9 @@ pub enum ClientError {
 
     #[error("Server error: {0}")]
     ServerError(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 /// Error response from API
@@ -35,17 +38,21 @@ struct ErrorResponse {
 }
 
 /// Request DTO for creating a short URL
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateShortenRequest {
     pub original_url: String,
     #[serde(skip_serializing_if = "Option::is_none", rename = "short_code")]
     pub code: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "description")]
     pub describe: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_type: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 /// Request DTO for updating a short URL
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateShortenRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub original_url: Option<String>,
@@ -53,6 +60,8 @@ pub struct UpdateShortenRequest {
     pub describe: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_type: Option<i32>,
 }
 
 /// Response DTO for short URL
@@ -66,6 +75,18 @@ pub struct ShortenResponse {
     #[serde(rename = "description")]
     pub describe: Option<String>,
     pub status: i32,
+    #[serde(default)]
+    pub redirect_type: Option<i32>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub last_checked_at: Option<String>,
+    #[serde(default)]
+    pub last_status: Option<i32>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -96,6 +117,143 @@ pub struct ListParams {
     pub sort: Option<String>,
     pub order: Option<String>,
     pub original_url: Option<String>,
+    pub tag: Option<String>,
+    /// Only links whose last destination health check failed
+    pub broken: bool,
+}
+
+/// Query parameters for rendering a QR code
+#[derive(Debug, Clone, Default)]
+pub struct QrParams {
+    pub format: Option<String>,
+    pub size: Option<u32>,
+    pub margin: Option<u32>,
+    pub ec: Option<String>,
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+}
+
+impl QrParams {
+    /// Query string pairs for the parameters that are set
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query_params = vec![];
+        if let Some(format) = &self.format {
+            query_params.push(("format", format.clone()));
+        }
+        if let Some(size) = self.size {
+            query_params.push(("size", size.to_string()));
+        }
+        if let Some(margin) = self.margin {
+            query_params.push(("margin", margin.to_string()));
+        }
+        if let Some(ec) = &self.ec {
+            query_params.push(("ec", ec.clone()));
+        }
+        if let Some(fg) = &self.fg {
+            query_params.push(("fg", fg.clone()));
+        }
+        if let Some(bg) = &self.bg {
+            query_params.push(("bg", bg.clone()));
+        }
+        query_params
+    }
+}
+
+/// Query parameters for importing short URLs
+#[derive(Debug, Clone, Default)]
+pub struct ImportParams {
+    /// `csv` or `ndjson`
+    pub format: String,
+    /// `skip`, `overwrite` or `fail` (the server default)
+    pub on_conflict: Option<String>,
+    pub dry_run: bool,
+}
+
+impl ImportParams {
+    /// Query string pairs for the parameters that are set
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query_params = vec![("format", self.format.clone())];
+        if let Some(on_conflict) = &self.on_conflict {
+            query_params.push(("on_conflict", on_conflict.clone()));
+        }
+        if self.dry_run {
+            query_params.push(("dry_run", "true".to_string()));
+        }
+        query_params
+    }
+}
+
+/// Query parameters for exporting short URLs or access histories
+///
+/// Filters the server does not know for the exported records are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ExportParams {
+    /// `csv` (the server default), `ndjson` or `parquet_csv`
+    pub format: Option<String>,
+    pub short_code: Option<String>,
+    pub status: Option<i32>,
+    pub original_url: Option<String>,
+    pub tag: Option<String>,
+    pub broken: bool,
+    pub ip_address: Option<String>,
+    pub variant: Option<String>,
+}
+
+impl ExportParams {
+    /// Query string pairs for the parameters that are set
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query_params = vec![];
+        if let Some(format) = &self.format {
+            query_params.push(("format", format.clone()));
+        }
+        if let Some(short_code) = &self.short_code {
+            query_params.push(("short_code", short_code.clone()));
+        }
+        if let Some(status) = self.status {
+            query_params.push(("status", status.to_string()));
+        }
+        if let Some(original_url) = &self.original_url {
+            query_params.push(("original_url", original_url.clone()));
+        }
+        if let Some(tag) = &self.tag {
+            query_params.push(("tag", tag.clone()));
+        }
+        if self.broken {
+            query_params.push(("broken", "true".to_string()));
+        }
+        if let Some(ip_address) = &self.ip_address {
+            query_params.push(("ip_address", ip_address.clone()));
+        }
+        if let Some(variant) = &self.variant {
+            query_params.push(("variant", variant.clone()));
+        }
+        query_params
+    }
+}
+
+/// Report of one row of an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowReport {
+    pub line: usize,
+    pub short_code: Option<String>,
+    /// `created`, `updated`, `skipped`, `conflict` or `invalid`
+    pub result: String,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Report of an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub committed: bool,
+    pub total: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub conflicts: usize,
+    pub invalid: usize,
+    pub rows: Vec<ImportRowReport>,
 }
 
 /// API Client for interacting with the shortener server
@@ -135,6 +293,95 @@ impl ApiClient {
         self.handle_response(response).await
     }
 
+    /// Import short URLs from the content of a CSV or NDJSON file
+    ///
+    /// POST /api/shortens/import
+    ///
+    /// Imports the server aborted (invalid rows or conflicts) also return
+    /// their report, with `committed` unset.
+    pub async fn import_shortens(
+        &self,
+        body: String,
+        params: &ImportParams,
+    ) -> Result<ImportReport, ClientError> {
+        let url = format!("{}/api/shortens/import", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("X-API-KEY", &self.api_key)
+            .query(&params.query())
+            .body(body)
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::UNPROCESSABLE_ENTITY {
+            return response
+                .json::<ImportReport>()
+                .await
+                .map_err(|e| ClientError::ServerError(format!("Failed to parse response: {}", e)));
+        }
+
+        self.handle_response(response).await
+    }
+
+    /// Stream an export of short URLs into `out`
+    ///
+    /// GET /api/shortens/export
+    ///
+    /// Returns the number of bytes written.
+    pub async fn export_shortens(
+        &self,
+        params: &ExportParams,
+        out: &mut impl std::io::Write,
+    ) -> Result<u64, ClientError> {
+        self.export("shortens", params, out).await
+    }
+
+    /// Stream an export of access histories into `out`
+    ///
+    /// GET /api/histories/export
+    ///
+    /// Returns the number of bytes written.
+    pub async fn export_histories(
+        &self,
+        params: &ExportParams,
+        out: &mut impl std::io::Write,
+    ) -> Result<u64, ClientError> {
+        self.export("histories", params, out).await
+    }
+
+    /// Write the body of an export chunk by chunk as it arrives
+    async fn export(
+        &self,
+        resource: &str,
+        params: &ExportParams,
+        out: &mut impl std::io::Write,
+    ) -> Result<u64, ClientError> {
+        let url = format!("{}/api/{}/export", self.base_url, resource);
+
+        let mut response = self
+            .client
+            .get(&url)
+            .header("X-API-KEY", &self.api_key)
+            .query(&params.query())
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(self.error_from_response(response).await);
+        }
+
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await? {
+            out.write_all(&chunk)?;
+            written += chunk.len() as u64;
+        }
+        out.flush()?;
+
+        Ok(written)
+    }
+
     /// Get a short URL by short_code
     ///
     /// GET /api/shortens/{short_code}
@@ -182,6 +429,12 @@ impl ApiClient {
         if let Some(original_url) = params.original_url {
             query_params.push(("original_url", original_url));
         }
+        if let Some(tag) = params.tag {
+            query_params.push(("tag", tag));
+        }
+        if params.broken {
+            query_params.push(("broken", "true".to_string()));
+        }
 
         if !query_params.is_empty() {
             request = request.query(&query_params);
@@ -213,6 +466,27 @@ impl ApiClient {
         self.handle_response(response).await
     }
 
+    /// Download the QR code image of a short URL
+    ///
+    /// GET /api/shortens/{short_code}/qr
+    pub async fn get_qr(&self, code: &str, params: &QrParams) -> Result<Vec<u8>, ClientError> {
+        let url = format!("{}/api/shortens/{}/qr", self.base_url, code);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-API-KEY", &self.api_key)
+            .query(&params.query())
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(self.error_from_response(response).await);
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
     /// Delete a short URL
     ///
     /// DELETE /api/shortens/{short_code}
@@ -307,51 +581,58 @@ impl ApiClient {
     where
         T: for<'de> Deserialize<'de>,
     {
-        let status = response.status();
-
-        match status {
+        match response.status() {
             StatusCode::OK | StatusCode::CREATED => {
                 let data = response.json::<T>().await.map_err(|e| {
                     ClientError::ServerError(format!("Failed to parse response: {}", e))
                 })?;
                 Ok(data)
             }
-            StatusCode::UNAUTHORIZED => Err(ClientError::Unauthorized),
+            _ => Err(self.error_from_response(response).await),
+        }
+    }
+
+    /// Turn an unsuccessful response into a client error
+    async fn error_from_response(&self, response: reqwest::Response) -> ClientError {
+        let status = response.status();
+
+        match status {
+            StatusCode::UNAUTHORIZED => ClientError::Unauthorized,
             StatusCode::NOT_FOUND => {
                 let error_body = response.json::<ErrorResponse>().await.ok();
-                Err(ClientError::NotFound(
+                ClientError::NotFound(
                     error_body
                         .map(|e| e.errinfo)
                         .unwrap_or_else(|| "Resource not found".to_string()),
-                ))
+                )
             }
             StatusCode::CONFLICT => {
                 let error_body = response.json::<ErrorResponse>().await.ok();
-                Err(ClientError::Conflict(
+                ClientError::Conflict(
                     error_body
                         .map(|e| e.errinfo)
                         .unwrap_or_else(|| "Conflict".to_string()),
-                ))
+                )
             }
             status if status.is_client_error() => {
                 let error_body = response.json::<ErrorResponse>().await.ok();
-                Err(ClientError::BadRequest(
+                ClientError::BadRequest(
                     error_body
                         .map(|e| e.errinfo)
                         .unwrap_or_else(|| "Bad request".to_string()),
-                ))
+                )
             }
             status if status.is_server_error() => {
                 let error_body = response.json::<ErrorResponse>().await.ok();
-                Err(ClientError::ServerError(
+                ClientError::ServerError(
                     error_body
                         .map(|e| e.errinfo)
                         .unwrap_or_else(|| "Server error".to_string()),
-                ))
+                )
             }
             _ => {
                 let error_body = response.json::<ErrorResponse>().await.ok();
-                Err(ClientError::ApiError {
+                ClientError::ApiError {
                     code: error_body
                         .as_ref()
                         .map(|e| e.errcode.clone())
@@ -359,7 +640,7 @@ impl ApiClient {
                     message: error_body
                         .map(|e| e.errinfo)
                         .unwrap_or_else(|| format!("Unexpected status: {}", status)),
-                })
+                }
             }
         }
     }
@@ -388,11 +669,14 @@ mod tests {
             original_url: "https://example.com".to_string(),
             code: Some("test123".to_string()),
             describe: Some("Test URL".to_string()),
+            redirect_type: None,
+            tags: vec!["docs".to_string(), "ads".to_string()],
         };
 
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("original_url"));
         assert!(json.contains("test123"));
+        assert!(json.contains(r#""tags":["docs","ads"]"#));
     }
 
     #[test]
@@ -401,6 +685,8 @@ mod tests {
             original_url: "https://example.com".to_string(),
             code: None,
             describe: None,
+            redirect_type: None,
+            tags: Vec::new(),
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -408,6 +694,8 @@ mod tests {
         // Optional fields should not be serialized when None
         assert!(!json.contains("code"));
         assert!(!json.contains("describe"));
+        assert!(!json.contains("redirect_type"));
+        assert!(!json.contains("tags"));
     }
 
     #[test]
@@ -416,11 +704,71 @@ mod tests {
             original_url: Some("https://updated.com".to_string()),
             describe: Some("Updated".to_string()),
             status: Some(1),
+            redirect_type: Some(302),
         };
 
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("updated.com"));
         assert!(json.contains("Updated"));
+        assert!(json.contains(r#""redirect_type":302"#));
+    }
+
+    #[test]
+    fn test_qr_params_query() {
+        let params = QrParams {
+            format: Some("svg".to_string()),
+            size: Some(512),
+            ec: Some("h".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            params.query(),
+            vec![
+                ("format", "svg".to_string()),
+                ("size", "512".to_string()),
+                ("ec", "h".to_string()),
+            ]
+        );
+        assert!(QrParams::default().query().is_empty());
+    }
+
+    #[test]
+    fn test_import_params_query() {
+        let params = ImportParams {
+            format: "csv".to_string(),
+            on_conflict: Some("skip".to_string()),
+            dry_run: true,
+        };
+
+        assert_eq!(
+            params.query(),
+            vec![
+                ("format", "csv".to_string()),
+                ("on_conflict", "skip".to_string()),
+                ("dry_run", "true".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_params_query() {
+        let params = ExportParams {
+            format: Some("ndjson".to_string()),
+            status: Some(1),
+            broken: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            params.query(),
+            vec![
+                ("format", "ndjson".to_string()),
+                ("status", "1".to_string()),
+                ("broken", "true".to_string()),
+            ]
+        );
+        assert!(ExportParams::default().query().is_empty());
     }
 
     #[test]
@@ -431,5 +779,7 @@ mod tests {
         assert!(params.status.is_none());
         assert!(params.sort.is_none());
         assert!(params.order.is_none());
+        assert!(params.tag.is_none());
+        assert!(!params.broken);
     }
 }
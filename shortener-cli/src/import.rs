@@ -0,0 +1,177 @@
+use std::io::BufRead;
+
+/// Rows of an import file sent in one request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportBatch {
+    /// Request body, starting with the header row for CSV files
+    pub body: String,
+    /// Number of rows (blank lines excluded)
+    pub rows: usize,
+    /// Added to a line number of the body to get the line in the file
+    pub line_offset: usize,
+    /// Bytes of the file read so far
+    pub bytes_read: u64,
+}
+
+/// Reads an import file as batches of whole rows
+///
+/// CSV rows may span several lines inside quoted fields; a row only ends
+/// on a line break outside quotes. Every batch of a CSV file repeats the
+/// header row, so the server can read each one on its own.
+pub struct ImportBatches<R> {
+    reader: R,
+    csv: bool,
+    batch_size: usize,
+    header: Option<String>,
+    /// Lines of the file read so far
+    line: usize,
+    bytes_read: u64,
+}
+
+impl<R: BufRead> ImportBatches<R> {
+    /// Create a reader sending `batch_size` rows per batch (0 for the whole file)
+    pub fn new(reader: R, csv: bool, batch_size: usize) -> Self {
+        Self {
+            reader,
+            csv,
+            batch_size,
+            header: None,
+            line: 0,
+            bytes_read: 0,
+        }
+    }
+
+    /// Read the next batch, `None` at the end of the file
+    pub fn next_batch(&mut self) -> std::io::Result<Option<ImportBatch>> {
+        if self.csv && self.header.is_none() {
+            match self.read_row()? {
+                Some(header) => self.header = Some(header),
+                None => return Ok(None),
+            }
+        }
+
+        let mut body = self.header.clone().unwrap_or_default();
+        let line_offset = match self.header {
+            // Body line 2 is the line after the last one read
+            Some(_) => self.line - 1,
+            None => self.line,
+        };
+        let mut rows = 0;
+
+        while self.batch_size == 0 || rows < self.batch_size {
+            let Some(row) = self.read_row()? else {
+                break;
+            };
+            if !row.trim().is_empty() {
+                rows += 1;
+            }
+            body.push_str(&row);
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(ImportBatch {
+            body,
+            rows,
+            line_offset,
+            bytes_read: self.bytes_read,
+        }))
+    }
+
+    /// Read one row with its line break
+    fn read_row(&mut self) -> std::io::Result<Option<String>> {
+        let mut row = String::new();
+
+        loop {
+            let read = self.reader.read_line(&mut row)?;
+            if read == 0 {
+                break;
+            }
+            self.bytes_read += read as u64;
+            self.line += 1;
+
+            // Inside quotes while the number of quotes is odd
+            if !self.csv || row.matches('"').count().is_multiple_of(2) {
+                break;
+            }
+        }
+
+        if row.is_empty() {
+            return Ok(None);
+        }
+        if !row.ends_with('\n') {
+            row.push('\n');
+        }
+
+        Ok(Some(row))
+    }
+}
+
+/// Pick the import format from the file extension
+pub fn format_for_path(path: &str) -> Option<String> {
+    let extension = std::path::Path::new(path)
+        .extension()?
+        .to_str()?
+        .to_lowercase();
+
+    match extension.as_str() {
+        "csv" => Some("csv".to_string()),
+        "ndjson" | "jsonl" => Some("ndjson".to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_batches_repeat_header() {
+        let text = "code,url\na,https://a.example\n\nb,\"https://b.example\nsecond line\"\nc,https://c.example";
+        let mut batches = ImportBatches::new(text.as_bytes(), true, 2);
+
+        let first = batches.next_batch().unwrap().unwrap();
+        assert_eq!(
+            first.body,
+            "code,url\na,https://a.example\n\nb,\"https://b.example\nsecond line\"\n"
+        );
+        assert_eq!(first.rows, 2);
+        // Body line 2 ("a") is file line 2
+        assert_eq!(first.line_offset, 0);
+
+        let second = batches.next_batch().unwrap().unwrap();
+        assert_eq!(second.body, "code,url\nc,https://c.example\n");
+        assert_eq!(second.rows, 1);
+        // Body line 2 ("c") is file line 6
+        assert_eq!(2 + second.line_offset, 6);
+        assert_eq!(second.bytes_read, text.len() as u64);
+
+        assert!(batches.next_batch().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_ndjson_batches() {
+        let text = "{\"url\":\"https://a.example\"}\n{\"url\":\"https://b.example\"}\n{\"url\":\"https://c.example\"}\n";
+
+        let mut batches = ImportBatches::new(text.as_bytes(), false, 2);
+        assert_eq!(batches.next_batch().unwrap().unwrap().rows, 2);
+        let second = batches.next_batch().unwrap().unwrap();
+        assert_eq!(second.rows, 1);
+        assert_eq!(1 + second.line_offset, 3);
+        assert!(batches.next_batch().unwrap().is_none());
+
+        // Batch size 0 sends the whole file at once
+        let mut batches = ImportBatches::new(text.as_bytes(), false, 0);
+        assert_eq!(batches.next_batch().unwrap().unwrap().rows, 3);
+        assert!(batches.next_batch().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_format_for_path() {
+        assert_eq!(format_for_path("links.CSV"), Some("csv".to_string()));
+        assert_eq!(format_for_path("links.jsonl"), Some("ndjson".to_string()));
+        assert_eq!(format_for_path("links.txt"), None);
+    }
+}
@@ -1,9 +1,15 @@
 mod client;
 mod config;
+mod import;
 
 use clap::{Parser, ValueEnum};
-use client::{ApiClient, CreateShortenRequest, ListParams, UpdateShortenRequest};
+use client::{
+    ApiClient, CreateShortenRequest, ExportParams, ImportParams, ListParams, QrParams,
+    UpdateShortenRequest,
+};
 use config::CliConfig;
+use import::ImportBatches;
+use std::io::Write;
 
 #[derive(Debug, Clone, ValueEnum)]
 enum OutputFormat {
@@ -15,6 +21,14 @@ enum OutputFormat {
     List,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExportTarget {
+    /// Short URLs
+    Links,
+    /// Access histories
+    Histories,
+}
+
 #[derive(Parser)]
 #[command(name = "shortener-cli")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
@@ -60,6 +74,14 @@ enum Commands {
         /// Description for the short URL (optional)
         #[arg(short = 'd', long)]
         desc: Option<String>,
+
+        /// HTTP redirect status: 301, 302, 307 or 308 (optional, server default if omitted)
+        #[arg(short = 't', long, value_parser = parse_redirect_type)]
+        redirect_type: Option<i32>,
+
+        /// Tag to file the short URL under (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
     },
     /// Get details of a short URL
     Get {
@@ -106,6 +128,14 @@ enum Commands {
         #[arg(short = 'r', long)]
         original_url: Option<String>,
 
+        /// Filter by tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only show links whose destination failed its last health check
+        #[arg(long)]
+        broken: bool,
+
         /// Output format (table, compact, list)
         #[arg(short = 'f', long, value_enum)]
         format: Option<OutputFormat>,
@@ -126,12 +156,116 @@ enum Commands {
         /// New status (0=enabled, 1=disabled)
         #[arg(short = 's', long)]
         status: Option<i32>,
+
+        /// New HTTP redirect status: 301, 302, 307 or 308
+        #[arg(short = 't', long, value_parser = parse_redirect_type)]
+        redirect_type: Option<i32>,
     },
     /// Delete a short URL
     Delete {
         /// Short code to delete
         code: String,
     },
+    /// Import short URLs from a CSV or NDJSON file
+    Import {
+        /// File with code, url, description, status and created_at columns
+        file: String,
+
+        /// File format (default: from the file extension)
+        #[arg(short = 'f', long, value_parser = ["csv", "ndjson"])]
+        format: Option<String>,
+
+        /// What to do with codes that already exist (default: fail)
+        #[arg(short = 'c', long, value_parser = ["skip", "overwrite", "fail"])]
+        on_conflict: Option<String>,
+
+        /// Validate the file without importing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Rows per request (default: 0, the whole file in one transaction)
+        ///
+        /// Each batch is imported in its own transaction. A batch with invalid
+        /// rows or conflicts stops the import, but the batches before it stay
+        /// imported.
+        #[arg(short = 'b', long, default_value_t = 0)]
+        batch_size: usize,
+    },
+    /// Export short URLs or access histories as CSV or NDJSON
+    Export {
+        /// What to export
+        #[arg(value_enum, default_value = "links")]
+        target: ExportTarget,
+
+        /// Output file (default: standard output)
+        #[arg(short = 'o', long)]
+        output: Option<String>,
+
+        /// File format (default: from the output file extension, else csv)
+        #[arg(short = 'f', long, value_parser = ["csv", "ndjson", "parquet_csv"])]
+        format: Option<String>,
+
+        /// Filter by short code
+        #[arg(short = 'c', long)]
+        code: Option<String>,
+
+        /// Filter links by status (0=enabled, 1=disabled, 2=expired)
+        #[arg(short = 't', long)]
+        status: Option<i32>,
+
+        /// Filter links by original URL
+        #[arg(short = 'r', long)]
+        original_url: Option<String>,
+
+        /// Filter links by tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only export links whose destination failed its last health check
+        #[arg(long)]
+        broken: bool,
+
+        /// Filter histories by visitor IP address
+        #[arg(long)]
+        ip: Option<String>,
+
+        /// Filter histories by A/B variant
+        #[arg(long)]
+        variant: Option<String>,
+    },
+    /// Save the QR code of a short URL to a file
+    Qr {
+        /// Short code to render
+        code: String,
+
+        /// Output file (e.g., qr.png or qr.svg)
+        #[arg(short = 'o', long)]
+        output: String,
+
+        /// Image format (default: from the output file extension, else png)
+        #[arg(short = 'f', long, value_parser = ["png", "svg"])]
+        format: Option<String>,
+
+        /// Image size in pixels (default: 256)
+        #[arg(short = 's', long)]
+        size: Option<u32>,
+
+        /// Quiet zone around the code in modules (default: 4)
+        #[arg(short = 'm', long)]
+        margin: Option<u32>,
+
+        /// Error correction level (default: m)
+        #[arg(short = 'e', long, value_parser = ["l", "m", "q", "h"])]
+        ec: Option<String>,
+
+        /// Foreground colour as hex (default: 000000)
+        #[arg(long)]
+        fg: Option<String>,
+
+        /// Background colour as hex (default: ffffff)
+        #[arg(long)]
+        bg: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -152,7 +286,20 @@ async fn main() {
             original_url,
             code,
             desc,
-        }) => handle_create(cli.url, cli.key, original_url, code, desc).await,
+            redirect_type,
+            tags,
+        }) => {
+            handle_create(
+                cli.url,
+                cli.key,
+                original_url,
+                code,
+                desc,
+                redirect_type,
+                tags,
+            )
+            .await
+        }
         Some(Commands::Get { code }) => handle_get(cli.url, cli.key, code).await,
         Some(Commands::Find { original_url, all }) => {
             handle_find(cli.url, cli.key, original_url, all).await
@@ -165,6 +312,8 @@ async fn main() {
             order,
             status,
             original_url,
+            tag,
+            broken,
             format,
         }) => {
             let options = ListOptions {
@@ -175,6 +324,8 @@ async fn main() {
                 order,
                 status,
                 original_url,
+                tag,
+                broken,
                 format,
             };
             handle_list(cli.url, cli.key, options).await
@@ -184,8 +335,68 @@ async fn main() {
             ourl,
             desc,
             status,
-        }) => handle_update(cli.url, cli.key, code, ourl, desc, status).await,
+            redirect_type,
+        }) => handle_update(cli.url, cli.key, code, ourl, desc, status, redirect_type).await,
         Some(Commands::Delete { code }) => handle_delete(cli.url, cli.key, code).await,
+        Some(Commands::Import {
+            file,
+            format,
+            on_conflict,
+            dry_run,
+            batch_size,
+        }) => {
+            let options = ImportOptions {
+                format,
+                on_conflict,
+                dry_run,
+                batch_size,
+            };
+            handle_import(cli.url, cli.key, file, options).await
+        }
+        Some(Commands::Export {
+            target,
+            output,
+            format,
+            code,
+            status,
+            original_url,
+            tag,
+            broken,
+            ip,
+            variant,
+        }) => {
+            let params = ExportParams {
+                format: format.or_else(|| output.as_deref().and_then(import::format_for_path)),
+                short_code: code,
+                status,
+                original_url,
+                tag,
+                broken,
+                ip_address: ip,
+                variant,
+            };
+            handle_export(cli.url, cli.key, target, output, params).await
+        }
+        Some(Commands::Qr {
+            code,
+            output,
+            format,
+            size,
+            margin,
+            ec,
+            fg,
+            bg,
+        }) => {
+            let params = QrParams {
+                format: format.or_else(|| qr_format_for_path(&output)),
+                size,
+                margin,
+                ec,
+                fg,
+                bg,
+            };
+            handle_qr(cli.url, cli.key, code, output, params).await
+        }
         None => {
             println!("Shortener CLI - Rust implementation");
             println!("Use --help for more information");
@@ -232,6 +443,8 @@ async fn handle_create(
     original_url: String,
     code: Option<String>,
     desc: Option<String>,
+    redirect_type: Option<i32>,
+    tags: Vec<String>,
 ) -> anyhow::Result<()> {
     let config = CliConfig::load(url_arg, key_arg)?;
     let client = ApiClient::new(config.url, config.key);
@@ -240,6 +453,8 @@ async fn handle_create(
         original_url,
         code,
         describe: desc,
+        redirect_type,
+        tags,
     };
 
     let response = client.create_shorten(request).await?;
@@ -289,6 +504,8 @@ async fn handle_find(
                 sort: Some("created_at".to_string()),
                 order: Some("desc".to_string()),
                 original_url: Some(original_url.clone()),
+                tag: None,
+                broken: false,
             };
 
             let response = client.list_shortens(params).await?;
@@ -320,6 +537,8 @@ async fn handle_find(
             sort: Some("created_at".to_string()),
             order: Some("desc".to_string()),
             original_url: Some(original_url.clone()),
+            tag: None,
+            broken: false,
         };
 
         let response = client.list_shortens(params).await?;
@@ -355,6 +574,8 @@ struct ListOptions {
     order: Option<String>,
     status: Option<i32>,
     original_url: Option<String>,
+    tag: Option<String>,
+    broken: bool,
     format: Option<OutputFormat>,
 }
 
@@ -380,6 +601,8 @@ async fn handle_list(
                 sort: options.sort.clone(),
                 order: options.order.clone(),
                 original_url: options.original_url.clone(),
+                tag: options.tag.clone(),
+                broken: options.broken,
             };
 
             let response = client.list_shortens(params).await?;
@@ -403,6 +626,8 @@ async fn handle_list(
             sort: options.sort,
             order: options.order,
             original_url: options.original_url,
+            tag: options.tag,
+            broken: options.broken,
         };
 
         let response = client.list_shortens(params).await?;
@@ -425,6 +650,7 @@ async fn handle_update(
     ourl: Option<String>,
     desc: Option<String>,
     status: Option<i32>,
+    redirect_type: Option<i32>,
 ) -> anyhow::Result<()> {
     let config = CliConfig::load(url_arg, key_arg)?;
     let client = ApiClient::new(config.url, config.key);
@@ -433,6 +659,7 @@ async fn handle_update(
         original_url: ourl,
         describe: desc,
         status,
+        redirect_type,
     };
 
     let response = client.update_shorten(&code, request).await?;
@@ -459,6 +686,159 @@ async fn handle_delete(
     Ok(())
 }
 
+struct ImportOptions {
+    format: Option<String>,
+    on_conflict: Option<String>,
+    dry_run: bool,
+    batch_size: usize,
+}
+
+async fn handle_import(
+    url_arg: Option<String>,
+    key_arg: Option<String>,
+    file: String,
+    options: ImportOptions,
+) -> anyhow::Result<()> {
+    let config = CliConfig::load(url_arg, key_arg)?;
+    let client = ApiClient::new(config.url, config.key);
+
+    let format = options
+        .format
+        .or_else(|| import::format_for_path(&file))
+        .ok_or_else(|| anyhow::anyhow!("Cannot tell the format of '{}', use --format", file))?;
+    let params = ImportParams {
+        format: format.clone(),
+        on_conflict: options.on_conflict,
+        dry_run: options.dry_run,
+    };
+
+    let reader = std::fs::File::open(&file)?;
+    let file_size = reader.metadata()?.len().max(1);
+    let mut batches = ImportBatches::new(
+        std::io::BufReader::new(reader),
+        format == "csv",
+        options.batch_size,
+    );
+
+    let (mut rows, mut created, mut updated, mut skipped, mut failed) = (0, 0, 0, 0, 0);
+    while let Some(batch) = batches.next_batch()? {
+        let report = client.import_shortens(batch.body, &params).await?;
+        rows += batch.rows;
+        created += report.created;
+        updated += report.updated;
+        skipped += report.skipped;
+        failed += report.invalid + report.conflicts;
+
+        eprint!(
+            "\rProcessed {} rows ({}%)",
+            rows,
+            batch.bytes_read * 100 / file_size
+        );
+        std::io::stderr().flush()?;
+
+        let problems: Vec<_> = report
+            .rows
+            .iter()
+            .filter(|row| row.error.is_some())
+            .collect();
+        if !problems.is_empty() {
+            eprintln!();
+            for row in problems {
+                println!(
+                    "  line {}{}: {} - {}",
+                    row.line + batch.line_offset,
+                    row.short_code
+                        .as_ref()
+                        .map(|code| format!(" ({})", code))
+                        .unwrap_or_default(),
+                    row.result,
+                    row.error.as_deref().unwrap_or_default()
+                );
+            }
+        }
+
+        if !report.committed && !report.dry_run {
+            eprintln!();
+            anyhow::bail!(
+                "Import stopped: {} invalid rows, {} conflicts in this batch. Earlier batches were imported ({} created, {} updated)",
+                report.invalid,
+                report.conflicts,
+                created,
+                updated
+            );
+        }
+    }
+    eprintln!();
+
+    if options.dry_run {
+        println!(
+            "✓ Dry run finished: {} rows would be created, {} updated, {} skipped, {} failed",
+            created, updated, skipped, failed
+        );
+    } else {
+        println!(
+            "✓ Import finished: {} created, {} updated, {} skipped",
+            created, updated, skipped
+        );
+    }
+
+    Ok(())
+}
+
+async fn handle_export(
+    url_arg: Option<String>,
+    key_arg: Option<String>,
+    target: ExportTarget,
+    output: Option<String>,
+    params: ExportParams,
+) -> anyhow::Result<()> {
+    let config = CliConfig::load(url_arg, key_arg)?;
+    let client = ApiClient::new(config.url, config.key);
+
+    let mut out: Box<dyn Write> = match &output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    let written = match target {
+        ExportTarget::Links => client.export_shortens(&params, &mut out).await?,
+        ExportTarget::Histories => client.export_histories(&params, &mut out).await?,
+    };
+
+    if let Some(path) = output {
+        println!("✓ Exported {} bytes to: {}", written, path);
+    }
+
+    Ok(())
+}
+
+async fn handle_qr(
+    url_arg: Option<String>,
+    key_arg: Option<String>,
+    code: String,
+    output: String,
+    params: QrParams,
+) -> anyhow::Result<()> {
+    let config = CliConfig::load(url_arg, key_arg)?;
+    let client = ApiClient::new(config.url, config.key);
+
+    let image = client.get_qr(&code, &params).await?;
+    std::fs::write(&output, image)?;
+
+    println!("✓ QR code for '{}' saved to: {}", code, output);
+
+    Ok(())
+}
+
+/// Pick the QR image format from the output file extension
+fn qr_format_for_path(path: &str) -> Option<String> {
+    let extension = std::path::Path::new(path)
+        .extension()?
+        .to_str()?
+        .to_lowercase();
+    matches!(extension.as_str(), "png" | "svg").then_some(extension)
+}
+
 // ============================================================================
 // Output Formatting Functions (Task 15.3)
 // ============================================================================
@@ -481,6 +861,37 @@ fn print_shorten_details(shorten: &ShortenResponse) {
         shorten.status,
         status_name(shorten.status)
     );
+    println!(
+        "Redirect:     {}",
+        shorten
+            .redirect_type
+            .map(|code| code.to_string())
+            .unwrap_or_else(|| "(server default)".to_string())
+    );
+    if !shorten.tags.is_empty() {
+        println!("Tags:         {}", shorten.tags.join(", "));
+    }
+    if !shorten.metadata.is_empty() {
+        println!("Metadata:");
+        for (key, value) in &shorten.metadata {
+            match value {
+                serde_json::Value::String(text) => println!("  {}: {}", key, text),
+                other => println!("  {}: {}", key, other),
+            }
+        }
+    }
+    if let Some(checked_at) = &shorten.last_checked_at {
+        let result = match (&shorten.last_error, shorten.last_status) {
+            (Some(error), _) => format!("broken ({})", error),
+            (None, Some(status)) => format!("ok ({})", status),
+            (None, None) => "ok".to_string(),
+        };
+        println!(
+            "Health:       {} at {}",
+            result,
+            format_datetime(checked_at)
+        );
+    }
     println!("Created:      {}", format_datetime(&shorten.created_at));
     println!("Updated:      {}", format_datetime(&shorten.updated_at));
 }
@@ -587,6 +998,10 @@ fn print_shorten_list(shortens: &[ShortenResponse]) {
             println!("   Desc:      {}", desc);
         }
 
+        if !s.tags.is_empty() {
+            println!("   Tags:      {}", s.tags.join(", "));
+        }
+
         println!("   Created:   {}", format_datetime(&s.created_at));
     }
 }
@@ -658,6 +1073,17 @@ fn truncate_url_smart(url: &str, max_len: usize) -> String {
     format!("{}...{}", prefix, suffix)
 }
 
+/// Parse a `--redirect-type` value, accepting only redirect status codes
+fn parse_redirect_type(value: &str) -> Result<i32, String> {
+    match value.parse::<i32>() {
+        Ok(code @ (301 | 302 | 307 | 308)) => Ok(code),
+        _ => Err(format!(
+            "invalid redirect type '{}' (expected 301, 302, 307 or 308)",
+            value
+        )),
+    }
+}
+
 /// Format datetime string for display (convert to local timezone)
 fn format_datetime(dt: &str) -> String {
     // Input format: "2024-01-15T10:30:45Z" (RFC 3339 / ISO 8601)
@@ -695,6 +1121,44 @@ mod tests {
         assert_eq!(status_name(99), "Unknown");
     }
 
+    #[test]
+    fn test_parse_redirect_type() {
+        assert_eq!(parse_redirect_type("301"), Ok(301));
+        assert_eq!(parse_redirect_type("302"), Ok(302));
+        assert_eq!(parse_redirect_type("307"), Ok(307));
+        assert_eq!(parse_redirect_type("308"), Ok(308));
+        assert!(parse_redirect_type("303").is_err());
+        assert!(parse_redirect_type("abc").is_err());
+    }
+
+    #[test]
+    fn test_qr_format_for_path() {
+        assert_eq!(qr_format_for_path("qr.svg"), Some("svg".to_string()));
+        assert_eq!(qr_format_for_path("out/QR.PNG"), Some("png".to_string()));
+        assert_eq!(qr_format_for_path("qr.jpg"), None);
+        assert_eq!(qr_format_for_path("qr"), None);
+    }
+
+    #[test]
+    fn test_import_batch_size() {
+        // The whole file goes in one request unless batching is asked for
+        let cli = Cli::try_parse_from(["shortener-cli", "import", "links.csv"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Import { batch_size: 0, .. })
+        ));
+
+        let cli =
+            Cli::try_parse_from(["shortener-cli", "import", "links.csv", "-b", "500"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Import {
+                batch_size: 500,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_truncate_string() {
         assert_eq!(truncate_string("short", 10), "short");
@@ -729,6 +1193,15 @@ mod tests {
             original_url: "https://example.com".to_string(),
             describe: Some("Test URL".to_string()),
             status: 1,
+            redirect_type: None,
+            tags: Vec::new(),
+            last_checked_at: Some("2024-01-16T08:00:00Z".to_string()),
+            last_status: Some(404),
+            last_error: Some("HTTP 404 Not Found".to_string()),
+            metadata: serde_json::json!({"campaign": "spring", "ticket": 42})
+                .as_object()
+                .cloned()
+                .unwrap(),
             created_at: "2024-01-15T10:30:45Z".to_string(),
             updated_at: "2024-01-15T10:30:45Z".to_string(),
         };
@@ -753,6 +1226,12 @@ mod tests {
                 original_url: "https://example.com/1".to_string(),
                 describe: Some("Test 1".to_string()),
                 status: 1,
+                redirect_type: None,
+                tags: Vec::new(),
+                last_checked_at: None,
+                last_status: None,
+                last_error: None,
+                metadata: Default::default(),
                 created_at: "2024-01-15T10:30:45Z".to_string(),
                 updated_at: "2024-01-15T10:30:45Z".to_string(),
             },
@@ -763,6 +1242,12 @@ mod tests {
                 original_url: "https://example.com/2".to_string(),
                 describe: None,
                 status: 2,
+                redirect_type: None,
+                tags: Vec::new(),
+                last_checked_at: None,
+                last_status: None,
+                last_error: None,
+                metadata: Default::default(),
                 created_at: "2024-01-15T11:30:45Z".to_string(),
                 updated_at: "2024-01-15T11:30:45Z".to_string(),
             },
@@ -26,11 +26,17 @@ pub mod error_codes {
     pub const URL_NOT_FOUND: &str = "10001";
     pub const CODE_EXISTS: &str = "10002";
     pub const INVALID_URL: &str = "10003";
+    pub const URL_EXPIRED: &str = "10004";
+    pub const CLICK_LIMIT_REACHED: &str = "10005";
+    pub const URL_NOT_ACTIVE: &str = "10006";
+    pub const CODE_RESERVED: &str = "10007";
+    pub const URL_BLOCKED: &str = "10008";
 
     // HTTP 错误 (40xxx)
     pub const UNAUTHORIZED: &str = "40001";
     pub const FORBIDDEN: &str = "40003";
     pub const NOT_FOUND: &str = "40004";
+    pub const TOO_MANY_REQUESTS: &str = "40029";
 
     // 第三方服务错误 (50xxx)
     pub const DATABASE_ERROR: &str = "50001";
@@ -36,6 +36,7 @@ pub struct PagedResponse<T> {
 pub enum UrlStatus {
     Enabled = 0,
     Disabled = 1,
+    Expired = 2,
 }
 
 impl From<i32> for UrlStatus {
@@ -43,6 +44,7 @@ impl From<i32> for UrlStatus {
         match value {
             0 => UrlStatus::Enabled,
             1 => UrlStatus::Disabled,
+            2 => UrlStatus::Expired,
             _ => UrlStatus::Enabled,
         }
     }
@@ -1,26 +1,35 @@
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use rand::Rng;
+use shortener_server::codegen::{self, CodeGenerator, CodeStrategy};
+use shortener_server::config::ShortenerConfig;
 use std::hint::black_box;
 
-/// Benchmark for short code generation
-fn generate_code(length: usize, charset: &str) -> String {
-    let mut rng = rand::rng();
-    let chars: Vec<char> = charset.chars().collect();
-    let charset_len = chars.len();
+const CHARSET: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 
-    (0..length)
-        .map(|_| chars[rng.random_range(0..charset_len)])
-        .collect()
+const STRATEGIES: [CodeStrategy; 4] = [
+    CodeStrategy::Random,
+    CodeStrategy::Sequential,
+    CodeStrategy::Hashids,
+    CodeStrategy::Pronounceable,
+];
+
+/// Generator of a strategy with the given code length and charset
+fn generator(strategy: CodeStrategy, length: usize, charset: &str) -> Box<dyn CodeGenerator> {
+    codegen::from_config(&ShortenerConfig {
+        code_length: length,
+        code_charset: charset.to_string(),
+        code_strategy: strategy,
+        code_salt: Some("bench".to_string()),
+        ..Default::default()
+    })
 }
 
 fn benchmark_code_generation(c: &mut Criterion) {
-    let charset = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
-
     let mut group = c.benchmark_group("code_generation");
 
     for length in [4, 6, 8, 10, 12, 16].iter() {
-        group.bench_with_input(BenchmarkId::from_parameter(length), length, |b, &length| {
-            b.iter(|| generate_code(black_box(length), black_box(charset)));
+        let generator = generator(CodeStrategy::Random, *length, CHARSET);
+        group.bench_with_input(BenchmarkId::from_parameter(length), length, |b, _| {
+            b.iter(|| generator.generate(black_box(0), black_box(1)));
         });
     }
 
@@ -31,10 +40,7 @@ fn benchmark_code_generation_different_charsets(c: &mut Criterion) {
     let charsets = [
         ("numeric", "0123456789"),
         ("lowercase", "abcdefghijklmnopqrstuvwxyz"),
-        (
-            "alphanumeric",
-            "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ",
-        ),
+        ("alphanumeric", CHARSET),
         (
             "extended",
             "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ-_",
@@ -44,36 +50,78 @@ fn benchmark_code_generation_different_charsets(c: &mut Criterion) {
     let mut group = c.benchmark_group("code_generation_charsets");
 
     for (name, charset) in charsets.iter() {
-        group.bench_with_input(BenchmarkId::from_parameter(name), charset, |b, charset| {
-            b.iter(|| generate_code(black_box(6), black_box(charset)));
+        let generator = generator(CodeStrategy::Random, 6, charset);
+        group.bench_with_input(BenchmarkId::from_parameter(name), charset, |b, _| {
+            b.iter(|| generator.generate(black_box(0), black_box(1)));
         });
     }
 
     group.finish();
 }
 
+fn benchmark_code_strategies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("code_strategies");
+
+    for strategy in STRATEGIES {
+        let generator = generator(strategy, 6, CHARSET);
+        let mut sequence = 0u64;
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{:?}", strategy)),
+            |b| {
+                b.iter(|| {
+                    sequence += 1;
+                    generator.generate(black_box(sequence), black_box(1))
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Generate 10000 codes per strategy, counting the ones that collide
 fn benchmark_code_uniqueness_check(c: &mut Criterion) {
     use std::collections::HashSet;
 
-    c.bench_function("code_uniqueness_1000", |b| {
-        b.iter(|| {
-            let charset = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
-            let mut codes = HashSet::new();
+    let mut group = c.benchmark_group("code_uniqueness_10000");
 
-            for _ in 0..1000 {
-                let code = generate_code(6, charset);
-                codes.insert(code);
-            }
+    for strategy in STRATEGIES {
+        // Short codes make collisions of the random strategies show up
+        let generator = generator(strategy, 4, CHARSET);
+        println!(
+            "{:?}: {}, {:.4}% collision chance at 10000 codes",
+            strategy,
+            generator.collisions(),
+            generator.collisions().probability(10_000) * 100.0
+        );
 
-            black_box(codes)
-        });
-    });
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{:?}", strategy)),
+            |b| {
+                b.iter(|| {
+                    let mut codes = HashSet::new();
+                    let mut collisions = 0u32;
+
+                    for sequence in 1..=10_000 {
+                        if !codes.insert(generator.generate(sequence, 1)) {
+                            collisions += 1;
+                        }
+                    }
+
+                    black_box((codes, collisions))
+                });
+            },
+        );
+    }
+
+    group.finish();
 }
 
 criterion_group!(
     benches,
     benchmark_code_generation,
     benchmark_code_generation_different_charsets,
+    benchmark_code_strategies,
     benchmark_code_uniqueness_check
 );
 criterion_main!(benches);
@@ -21,6 +21,7 @@ async fn setup_test_db() -> sea_orm::DatabaseConnection {
             code_length: 6,
             code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
                 .to_string(),
+            ..Default::default()
         },
         admin: shortener_server::config::AdminConfig {
             username: "admin".to_string(),
@@ -72,9 +73,10 @@ fn benchmark_db_create(c: &mut Criterion) {
                 original_url: "https://example.com".to_string(),
                 description: Some("Benchmark URL".to_string()),
                 status: UrlStatus::Enabled as i32,
+                ..Default::default()
             };
 
-            rt.block_on(async { black_box(repo.create(create_dto).await.unwrap()) })
+            rt.block_on(async { black_box(repo.create(create_dto, "admin").await.unwrap()) })
         });
     });
 }
@@ -93,13 +95,14 @@ fn benchmark_db_find(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", i),
                 description: Some(format!("URL {}", i)),
                 status: UrlStatus::Enabled as i32,
+                ..Default::default()
             };
-            repo.create(create_dto).await.unwrap();
+            repo.create(create_dto, "admin").await.unwrap();
         }
     });
 
     c.bench_function("db_find_by_code", |b| {
-        b.iter(|| rt.block_on(async { black_box(repo.find_by_code("find50").await.unwrap()) }));
+        b.iter(|| rt.block_on(async { black_box(repo.find_by_code("", "find50").await.unwrap()) }));
     });
 
     c.bench_function("db_find_by_id", |b| {
@@ -125,8 +128,9 @@ fn benchmark_db_list(c: &mut Criterion) {
                 } else {
                     UrlStatus::Disabled as i32
                 },
+                ..Default::default()
             };
-            repo.create(create_dto).await.unwrap();
+            repo.create(create_dto, "admin").await.unwrap();
         }
     });
 
@@ -170,8 +174,9 @@ fn benchmark_db_list_filtered(c: &mut Criterion) {
                 } else {
                     UrlStatus::Disabled as i32
                 },
+                ..Default::default()
             };
-            repo.create(create_dto).await.unwrap();
+            repo.create(create_dto, "admin").await.unwrap();
         }
     });
 
@@ -215,8 +220,9 @@ fn benchmark_db_update(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", i),
                 description: Some(format!("URL {}", i)),
                 status: UrlStatus::Enabled as i32,
+                ..Default::default()
             };
-            repo.create(create_dto).await.unwrap();
+            repo.create(create_dto, "admin").await.unwrap();
         }
     });
 
@@ -226,8 +232,15 @@ fn benchmark_db_update(c: &mut Criterion) {
                 original_url: Some("https://updated.com".to_string()),
                 description: Some("Updated".to_string()),
                 status: Some(UrlStatus::Disabled as i32),
+                ..Default::default()
             };
-            rt.block_on(async { black_box(repo.update("update50", update_dto).await.unwrap()) })
+            rt.block_on(async {
+                black_box(
+                    repo.update("", "update50", update_dto, "admin")
+                        .await
+                        .unwrap(),
+                )
+            })
         });
     });
 }
@@ -248,15 +261,16 @@ fn benchmark_db_delete(c: &mut Criterion) {
                         original_url: "https://example.com".to_string(),
                         description: None,
                         status: UrlStatus::Enabled as i32,
+                        ..Default::default()
                     };
-                    repo.create(create_dto).await.unwrap();
+                    repo.create(create_dto, "admin").await.unwrap();
                     repo
                 })
             },
             |repo| {
                 // Benchmark: delete the URL
                 rt.block_on(async {
-                    repo.delete("delete_test").await.unwrap();
+                    repo.delete("", "delete_test", "admin").await.unwrap();
                     black_box(())
                 })
             },
@@ -289,8 +303,9 @@ fn benchmark_db_delete_batch(c: &mut Criterion) {
                                     original_url: format!("https://example{}.com", i),
                                     description: None,
                                     status: UrlStatus::Enabled as i32,
+                                    ..Default::default()
                                 };
-                                let url = repo.create(create_dto).await.unwrap();
+                                let url = repo.create(create_dto, "admin").await.unwrap();
                                 ids.push(url.id);
                             }
                             ids
@@ -299,7 +314,9 @@ fn benchmark_db_delete_batch(c: &mut Criterion) {
                     },
                     |(repo, ids)| {
                         // Benchmark: batch delete
-                        rt.block_on(async { black_box(repo.delete_batch(ids).await.unwrap()) })
+                        rt.block_on(async {
+                            black_box(repo.delete_batch(ids, "admin").await.unwrap())
+                        })
                     },
                     criterion::BatchSize::SmallInput,
                 );
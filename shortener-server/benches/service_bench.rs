@@ -2,6 +2,8 @@ use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use shortener_server::cache::NullCache;
 use shortener_server::config::{Config, DatabaseConfig, DatabaseType, SqliteConfig};
 use shortener_server::db::DbFactory;
+use shortener_server::repositories::revision_repository::RevisionRepositoryImpl;
+use shortener_server::repositories::tag_repository::TagRepositoryImpl;
 use shortener_server::repositories::url_repository::UrlRepositoryImpl;
 use shortener_server::services::{CreateShortenRequest, ShortenService, UpdateShortenRequest};
 use std::hint::black_box;
@@ -21,6 +23,7 @@ async fn setup_test_service() -> ShortenService {
             code_length: 6,
             code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
                 .to_string(),
+            ..Default::default()
         },
         admin: shortener_server::config::AdminConfig {
             username: "admin".to_string(),
@@ -54,11 +57,15 @@ async fn setup_test_service() -> ShortenService {
     let db = DbFactory::create_connection(&config).await.unwrap();
     DbFactory::run_migrations(&db).await.unwrap();
 
-    let url_repo = Arc::new(UrlRepositoryImpl::new(db));
+    let url_repo = Arc::new(UrlRepositoryImpl::new(db.clone()));
+    let tag_repo = Arc::new(TagRepositoryImpl::new(db.clone()));
+    let revision_repo = Arc::new(RevisionRepositoryImpl::new(db));
     let cache = Arc::new(NullCache::new());
 
     ShortenService::new(
         url_repo,
+        tag_repo,
+        revision_repo,
         cache,
         config.shortener.clone(),
         config.server.site_url.clone(),
@@ -79,8 +86,9 @@ fn benchmark_service_create(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", counter),
                 short_code: None,
                 description: Some("Benchmark URL".to_string()),
+                ..Default::default()
             };
-            rt.block_on(async { black_box(service.create_shorten(req).await.unwrap()) })
+            rt.block_on(async { black_box(service.create_shorten(req, "bench").await.unwrap()) })
         });
     });
 
@@ -93,8 +101,9 @@ fn benchmark_service_create(c: &mut Criterion) {
                 original_url: "https://example.com".to_string(),
                 short_code: Some(format!("custom{}", custom_counter)),
                 description: Some("Benchmark URL".to_string()),
+                ..Default::default()
             };
-            rt.block_on(async { black_box(service.create_shorten(req).await.unwrap()) })
+            rt.block_on(async { black_box(service.create_shorten(req, "bench").await.unwrap()) })
         });
     });
 }
@@ -111,8 +120,9 @@ fn benchmark_service_get(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", i),
                 short_code: Some(format!("get{}", i)),
                 description: None,
+                ..Default::default()
             };
-            service.create_shorten(req).await.unwrap();
+            service.create_shorten(req, "bench").await.unwrap();
         }
     });
 
@@ -133,8 +143,9 @@ fn benchmark_service_list(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", i),
                 short_code: Some(format!("list{}", i)),
                 description: Some(format!("URL {}", i)),
+                ..Default::default()
             };
-            service.create_shorten(req).await.unwrap();
+            service.create_shorten(req, "bench").await.unwrap();
         }
     });
 
@@ -172,8 +183,9 @@ fn benchmark_service_update(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", i),
                 short_code: Some(format!("update{}", i)),
                 description: Some(format!("URL {}", i)),
+                ..Default::default()
             };
-            service.create_shorten(req).await.unwrap();
+            service.create_shorten(req, "bench").await.unwrap();
         }
     });
 
@@ -183,8 +195,16 @@ fn benchmark_service_update(c: &mut Criterion) {
                 original_url: Some("https://updated.com".to_string()),
                 description: Some("Updated".to_string()),
                 status: None,
+                ..Default::default()
             };
-            rt.block_on(async { black_box(service.update_shorten("update50", req).await.unwrap()) })
+            rt.block_on(async {
+                black_box(
+                    service
+                        .update_shorten("update50", req, "bench")
+                        .await
+                        .unwrap(),
+                )
+            })
         });
     });
 }
@@ -201,8 +221,9 @@ fn benchmark_service_code_generation(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", i),
                 short_code: None,
                 description: None,
+                ..Default::default()
             };
-            service.create_shorten(req).await.unwrap();
+            service.create_shorten(req, "bench").await.unwrap();
         }
     });
 
@@ -215,8 +236,9 @@ fn benchmark_service_code_generation(c: &mut Criterion) {
                 original_url: format!("https://unique{}.com", counter),
                 short_code: None,
                 description: None,
+                ..Default::default()
             };
-            rt.block_on(async { black_box(service.create_shorten(req).await.unwrap()) })
+            rt.block_on(async { black_box(service.create_shorten(req, "bench").await.unwrap()) })
         });
     });
 }
@@ -234,8 +256,9 @@ fn benchmark_service_validation(c: &mut Criterion) {
                 original_url: format!("https://example{}.com", counter),
                 short_code: Some(format!("valid{}", counter)),
                 description: Some("Test description".to_string()),
+                ..Default::default()
             };
-            rt.block_on(async { black_box(service.create_shorten(req).await.unwrap()) })
+            rt.block_on(async { black_box(service.create_shorten(req, "bench").await.unwrap()) })
         });
     });
 }
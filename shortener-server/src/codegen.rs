@@ -0,0 +1,441 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::ShortenerConfig;
+
+/// Longest code the `urls.short_code` column holds
+pub const MAX_CODE_LENGTH: usize = 16;
+
+/// Name of the database sequence numbered strategies draw from
+pub const CODE_SEQUENCE: &str = "short_code";
+
+/// Random codes get one character longer after this many collisions in a row
+const GROW_AFTER: u32 = 5;
+
+const CONSONANTS: &[char] = &[
+    'b', 'd', 'f', 'g', 'h', 'j', 'k', 'l', 'm', 'n', 'p', 'r', 's', 't', 'v', 'z',
+];
+const VOWELS: &[char] = &['a', 'e', 'i', 'o', 'u'];
+
+/// How generated short codes are built
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeStrategy {
+    /// Random characters of `code_charset`
+    #[default]
+    Random,
+    /// The code sequence in base `code_charset`, padded to `code_length`
+    Sequential,
+    /// The code sequence scrambled with `code_salt`, so codes don't reveal the order
+    Hashids,
+    /// Alternating consonants and vowels, e.g. `bokamu`
+    Pronounceable,
+}
+
+/// How a strategy can run into codes that are already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collisions {
+    /// Codes are drawn from `space` possible codes; taken ones are retried
+    Random { space: u128 },
+    /// Codes derive from a unique sequence, only custom codes can be hit
+    Sequence,
+}
+
+impl Collisions {
+    /// Chance that the next code is already taken when `used` codes exist
+    pub fn probability(&self, used: u64) -> f64 {
+        match self {
+            Collisions::Random { space } => (used as f64 / *space as f64).min(1.0),
+            Collisions::Sequence => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Collisions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Collisions::Random { space } => write!(
+                f,
+                "random over {} codes, one more character after {} collisions",
+                space, GROW_AFTER
+            ),
+            Collisions::Sequence => write!(f, "collision-free, taken custom codes are skipped"),
+        }
+    }
+}
+
+/// Builds short codes for new links
+pub trait CodeGenerator: Send + Sync {
+    fn strategy(&self) -> CodeStrategy;
+
+    /// Whether `generate` needs the next value of the code sequence
+    fn uses_sequence(&self) -> bool {
+        false
+    }
+
+    /// Build a code from the next sequence value (0 when unused) for the
+    /// given attempt, starting at 1
+    fn generate(&self, sequence: u64, attempt: u32) -> String;
+
+    fn collisions(&self) -> Collisions;
+}
+
+/// Generator of the configured strategy
+pub fn from_config(config: &ShortenerConfig) -> Box<dyn CodeGenerator> {
+    let chars: Vec<char> = config.code_charset.chars().collect();
+    let length = config.code_length.clamp(1, MAX_CODE_LENGTH);
+
+    match config.code_strategy {
+        CodeStrategy::Random => Box::new(RandomGenerator::new(chars, length)),
+        CodeStrategy::Sequential => Box::new(SequentialGenerator::new(chars, length)),
+        CodeStrategy::Hashids => Box::new(HashidsGenerator::new(
+            chars,
+            length,
+            config.code_salt.as_deref().unwrap_or_default(),
+        )),
+        CodeStrategy::Pronounceable => Box::new(PronounceableGenerator::new(length)),
+    }
+}
+
+/// Length of a random code, growing once collisions pile up
+fn grown_length(length: usize, attempt: u32) -> usize {
+    (length + (attempt.saturating_sub(1) / GROW_AFTER) as usize).min(MAX_CODE_LENGTH)
+}
+
+fn space(base: usize, length: usize) -> u128 {
+    (base as u128)
+        .checked_pow(length as u32)
+        .unwrap_or(u128::MAX)
+}
+
+/// Random characters of the charset
+pub struct RandomGenerator {
+    chars: Vec<char>,
+    length: usize,
+}
+
+impl RandomGenerator {
+    pub fn new(chars: Vec<char>, length: usize) -> Self {
+        Self { chars, length }
+    }
+}
+
+impl CodeGenerator for RandomGenerator {
+    fn strategy(&self) -> CodeStrategy {
+        CodeStrategy::Random
+    }
+
+    fn generate(&self, _sequence: u64, attempt: u32) -> String {
+        let mut rng = rand::rng();
+
+        (0..grown_length(self.length, attempt))
+            .map(|_| self.chars[rng.random_range(0..self.chars.len())])
+            .collect()
+    }
+
+    fn collisions(&self) -> Collisions {
+        Collisions::Random {
+            space: space(self.chars.len(), self.length),
+        }
+    }
+}
+
+/// The code sequence written in base `chars.len()`
+pub struct SequentialGenerator {
+    chars: Vec<char>,
+    length: usize,
+}
+
+impl SequentialGenerator {
+    pub fn new(chars: Vec<char>, length: usize) -> Self {
+        Self { chars, length }
+    }
+}
+
+impl CodeGenerator for SequentialGenerator {
+    fn strategy(&self) -> CodeStrategy {
+        CodeStrategy::Sequential
+    }
+
+    fn uses_sequence(&self) -> bool {
+        true
+    }
+
+    fn generate(&self, sequence: u64, _attempt: u32) -> String {
+        encode(sequence as u128, &self.chars, self.length)
+    }
+
+    fn collisions(&self) -> Collisions {
+        Collisions::Sequence
+    }
+}
+
+/// The code sequence mapped onto a salted permutation of all codes
+///
+/// Within the codes of one length, `n -> n * multiplier + offset (mod space)`
+/// is a bijection when the multiplier is coprime to the space, so codes stay
+/// unique while consecutive links look unrelated. The charset is shuffled by
+/// the salt as well, like hashids does.
+pub struct HashidsGenerator {
+    chars: Vec<char>,
+    length: usize,
+    seed: u64,
+}
+
+impl HashidsGenerator {
+    pub fn new(mut chars: Vec<char>, length: usize, salt: &str) -> Self {
+        let seed = fnv1a(salt.as_bytes());
+
+        // Fisher-Yates driven by a SplitMix64 stream of the salt
+        let mut state = seed;
+        for i in (1..chars.len()).rev() {
+            let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+            chars.swap(i, j);
+        }
+
+        Self {
+            chars,
+            length,
+            seed,
+        }
+    }
+}
+
+impl CodeGenerator for HashidsGenerator {
+    fn strategy(&self) -> CodeStrategy {
+        CodeStrategy::Hashids
+    }
+
+    fn uses_sequence(&self) -> bool {
+        true
+    }
+
+    fn generate(&self, sequence: u64, _attempt: u32) -> String {
+        let base = self.chars.len();
+        let n = sequence as u128;
+
+        // Shortest length whose codes still cover the sequence value
+        let mut length = self.length;
+        while base > 1 && space(base, length) <= n && length < MAX_CODE_LENGTH {
+            length += 1;
+        }
+        let space = space(base, length);
+        if base < 2 || space > 1 << 126 {
+            return encode(n, &self.chars, length);
+        }
+
+        let mut state = self.seed ^ length as u64;
+        let offset = (splitmix64(&mut state) as u128) % space;
+        let mut multiplier = (splitmix64(&mut state) as u128 | 1) % space;
+        while gcd(multiplier, space) != 1 {
+            multiplier += 1;
+        }
+
+        let scrambled = (mulmod(n % space, multiplier, space) + offset) % space;
+        encode(scrambled, &self.chars, length)
+    }
+
+    fn collisions(&self) -> Collisions {
+        Collisions::Sequence
+    }
+}
+
+/// Alternating consonants and vowels, ignoring the charset
+pub struct PronounceableGenerator {
+    length: usize,
+}
+
+impl PronounceableGenerator {
+    pub fn new(length: usize) -> Self {
+        Self { length }
+    }
+}
+
+impl CodeGenerator for PronounceableGenerator {
+    fn strategy(&self) -> CodeStrategy {
+        CodeStrategy::Pronounceable
+    }
+
+    fn generate(&self, _sequence: u64, attempt: u32) -> String {
+        let mut rng = rand::rng();
+
+        (0..grown_length(self.length, attempt))
+            .map(|i| {
+                let letters = if i % 2 == 0 { CONSONANTS } else { VOWELS };
+                letters[rng.random_range(0..letters.len())]
+            })
+            .collect()
+    }
+
+    fn collisions(&self) -> Collisions {
+        let consonants = self.length.div_ceil(2);
+        Collisions::Random {
+            space: space(CONSONANTS.len(), consonants)
+                .saturating_mul(space(VOWELS.len(), self.length - consonants)),
+        }
+    }
+}
+
+/// Write `n` in base `chars.len()`, left-padded to `length`
+fn encode(mut n: u128, chars: &[char], length: usize) -> String {
+    let base = chars.len() as u128;
+    if base < 2 {
+        return chars.iter().cycle().take(length).collect();
+    }
+
+    let mut code = Vec::with_capacity(length);
+    while n > 0 {
+        code.push(chars[(n % base) as usize]);
+        n /= base;
+    }
+    while code.len() < length {
+        code.push(chars[0]);
+    }
+
+    code.iter().rev().collect()
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// `a * b mod m` without overflowing u128
+fn mulmod(mut a: u128, mut b: u128, m: u128) -> u128 {
+    let mut result = 0u128;
+    a %= m;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = (result + a) % m;
+        }
+        a = (a << 1) % m;
+        b >>= 1;
+    }
+    result
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const CHARSET: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    fn config(strategy: CodeStrategy) -> ShortenerConfig {
+        ShortenerConfig {
+            code_strategy: strategy,
+            code_salt: Some("salt".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_random_codes_grow_after_collisions() {
+        let generator = from_config(&config(CodeStrategy::Random));
+
+        assert_eq!(generator.strategy(), CodeStrategy::Random);
+        assert!(!generator.uses_sequence());
+        assert_eq!(generator.generate(0, 1).len(), 6);
+        assert_eq!(generator.generate(0, GROW_AFTER).len(), 6);
+        assert_eq!(generator.generate(0, GROW_AFTER + 1).len(), 7);
+        assert!(
+            generator
+                .generate(0, 1)
+                .chars()
+                .all(|c| CHARSET.contains(c))
+        );
+        assert_eq!(
+            generator.collisions(),
+            Collisions::Random {
+                space: 62u128.pow(6)
+            }
+        );
+    }
+
+    #[test]
+    fn test_sequential_codes() {
+        let generator = from_config(&config(CodeStrategy::Sequential));
+
+        assert!(generator.uses_sequence());
+        assert_eq!(generator.generate(0, 1), "000000");
+        assert_eq!(generator.generate(61, 1), "00000Z");
+        assert_eq!(generator.generate(62, 1), "000010");
+        // The sequence outgrowing the code length makes codes longer
+        assert_eq!(generator.generate(62u64.pow(6), 1), "1000000");
+        assert_eq!(generator.collisions(), Collisions::Sequence);
+    }
+
+    #[test]
+    fn test_hashids_codes_are_unique_and_scrambled() {
+        let generator = from_config(&config(CodeStrategy::Hashids));
+
+        let codes: Vec<String> = (1..=10_000).map(|n| generator.generate(n, 1)).collect();
+        assert!(codes.iter().all(|code| code.len() == 6));
+        assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len());
+        assert_ne!(
+            codes[1],
+            from_config(&config(CodeStrategy::Sequential)).generate(2, 1)
+        );
+
+        // Same salt, same codes; another salt, other codes
+        assert_eq!(generator.generate(42, 1), generator.generate(42, 7));
+        let other = HashidsGenerator::new(CHARSET.chars().collect(), 6, "pepper");
+        assert_ne!(generator.generate(42, 1), other.generate(42, 1));
+
+        assert_eq!(generator.generate(62u64.pow(6) + 5, 1).len(), 7);
+    }
+
+    #[test]
+    fn test_hashids_small_space_is_a_permutation() {
+        let generator = HashidsGenerator::new("ab".chars().collect(), 4, "salt");
+
+        let codes: HashSet<String> = (0..16).map(|n| generator.generate(n, 1)).collect();
+        assert_eq!(codes.len(), 16);
+    }
+
+    #[test]
+    fn test_pronounceable_codes() {
+        let generator = from_config(&config(CodeStrategy::Pronounceable));
+
+        let code = generator.generate(0, 1);
+        assert_eq!(code.len(), 6);
+        for (i, c) in code.chars().enumerate() {
+            let letters = if i % 2 == 0 { CONSONANTS } else { VOWELS };
+            assert!(letters.contains(&c), "{} in {}", c, code);
+        }
+        assert_eq!(
+            generator.collisions(),
+            Collisions::Random {
+                space: 16u128.pow(3) * 5u128.pow(3)
+            }
+        );
+    }
+
+    #[test]
+    fn test_collision_probability() {
+        let random = Collisions::Random { space: 1000 };
+        assert_eq!(random.probability(0), 0.0);
+        assert_eq!(random.probability(250), 0.25);
+        assert_eq!(random.probability(5000), 1.0);
+        assert_eq!(Collisions::Sequence.probability(u64::MAX), 0.0);
+
+        assert!(random.to_string().contains("1000 codes"));
+    }
+}
@@ -1,4 +1,7 @@
+use crate::codegen::CodeStrategy;
 use crate::logging::LoggingConfig;
+use crate::models::url::RedirectType;
+use crate::policy::RuleList;
 use config::{Config as ConfigBuilder, ConfigError, Environment, File};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -31,6 +34,170 @@ pub struct ServerConfig {
 pub struct ShortenerConfig {
     pub code_length: usize,
     pub code_charset: String,
+    /// Where to send visitors of an expired or used-up link (410 Gone when unset)
+    #[serde(default)]
+    pub expired_redirect_url: Option<String>,
+    /// Interval in seconds between expiry sweeps (0 disables the sweeper)
+    #[serde(default = "default_expiry_sweep_interval")]
+    pub expiry_sweep_interval: u64,
+    /// Secret used to sign unlock cookies of password-protected links
+    /// (defaults to `server.api_key`)
+    #[serde(default)]
+    pub unlock_secret: Option<String>,
+    /// How long in seconds an unlocked link stays accessible without the password
+    #[serde(default = "default_unlock_ttl")]
+    pub unlock_ttl: u64,
+    /// Wrong passwords allowed per link before unlocking is locked out (0 for no limit)
+    #[serde(default = "default_unlock_max_attempts")]
+    pub unlock_max_attempts: u32,
+    /// How long in seconds unlocking stays locked out after too many wrong passwords
+    #[serde(default = "default_unlock_lockout")]
+    pub unlock_lockout: u64,
+    /// Default HTTP redirect status (301, 302, 307 or 308)
+    #[serde(default = "default_redirect_type")]
+    pub redirect_type: i32,
+    /// HTML file shown before a link's `active_from` (built-in page when unset)
+    #[serde(default)]
+    pub not_active_page: Option<String>,
+    /// Append the incoming query string to the destination (links may override it)
+    #[serde(default)]
+    pub forward_query: bool,
+    /// Redirect `/{code}/extra/path` to the destination plus `/extra/path`
+    /// (links may override it)
+    #[serde(default)]
+    pub forward_path: bool,
+    /// Additional short domains (e.g. `https://go.example.com`); each has its
+    /// own short code namespace, `server.site_url` is the default domain
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Codes that cannot be used, on top of the built-in route prefixes
+    #[serde(default)]
+    pub reserved_codes: Vec<String>,
+    /// File of blocked words, one per line; codes containing one are rejected
+    #[serde(default)]
+    pub blocklist_file: Option<String>,
+    /// How generated codes are built
+    #[serde(default)]
+    pub code_strategy: CodeStrategy,
+    /// Salt scrambling `hashids` codes (defaults to an empty salt)
+    #[serde(default)]
+    pub code_salt: Option<String>,
+    /// Return the existing link of a destination on create (requests may override it)
+    #[serde(default)]
+    pub reuse_existing: bool,
+    /// Drop `utm_*` and other tracking parameters from destination URLs
+    #[serde(default)]
+    pub strip_tracking_params: bool,
+    /// File of blocked destination domains and `regex:` URL patterns, one per line
+    #[serde(default)]
+    pub destination_blocklist_file: Option<String>,
+    /// File of destinations exempt from the blocklist, same format
+    #[serde(default)]
+    pub destination_allowlist_file: Option<String>,
+    /// Interval in seconds between checks for changed policy files (0 disables reloading)
+    #[serde(default = "default_policy_reload_interval")]
+    pub policy_reload_interval: u64,
+    /// Interval in seconds between destination health checks (0 disables the checker)
+    #[serde(default)]
+    pub health_check_interval: u64,
+    /// Timeout in seconds of a single health check request
+    #[serde(default = "default_health_check_timeout")]
+    pub health_check_timeout: u64,
+    /// Health check requests in flight at the same time
+    #[serde(default = "default_health_check_concurrency")]
+    pub health_check_concurrency: usize,
+    /// Health check requests started per second (0 for no limit)
+    #[serde(default = "default_health_check_rate_limit")]
+    pub health_check_rate_limit: u32,
+    /// Disable links after this many failed checks in a row (0 never disables)
+    #[serde(default)]
+    pub health_check_disable_after: u32,
+    /// Let health checks reach loopback, private and link-local addresses
+    #[serde(default)]
+    pub health_check_allow_private: bool,
+    /// Days deleted links stay in the trash before they are purged (0 keeps them)
+    ///
+    /// Purging is irreversible: the link, its access history and tags are
+    /// deleted for good, only its revisions are kept.
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u64,
+}
+
+fn default_expiry_sweep_interval() -> u64 {
+    60
+}
+
+fn default_policy_reload_interval() -> u64 {
+    30
+}
+
+fn default_health_check_timeout() -> u64 {
+    10
+}
+
+fn default_health_check_concurrency() -> usize {
+    4
+}
+
+fn default_health_check_rate_limit() -> u32 {
+    10
+}
+
+fn default_trash_retention_days() -> u64 {
+    30
+}
+
+fn default_unlock_ttl() -> u64 {
+    3600
+}
+
+fn default_unlock_max_attempts() -> u32 {
+    5
+}
+
+fn default_unlock_lockout() -> u64 {
+    300
+}
+
+fn default_redirect_type() -> i32 {
+    RedirectType::PermanentRedirect as i32
+}
+
+impl Default for ShortenerConfig {
+    fn default() -> Self {
+        Self {
+            code_length: 6,
+            code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
+                .to_string(),
+            expired_redirect_url: None,
+            expiry_sweep_interval: default_expiry_sweep_interval(),
+            unlock_secret: None,
+            unlock_ttl: default_unlock_ttl(),
+            unlock_max_attempts: default_unlock_max_attempts(),
+            unlock_lockout: default_unlock_lockout(),
+            redirect_type: default_redirect_type(),
+            not_active_page: None,
+            forward_query: false,
+            forward_path: false,
+            domains: Vec::new(),
+            reserved_codes: Vec::new(),
+            blocklist_file: None,
+            code_strategy: CodeStrategy::default(),
+            code_salt: None,
+            reuse_existing: false,
+            strip_tracking_params: false,
+            destination_blocklist_file: None,
+            destination_allowlist_file: None,
+            policy_reload_interval: default_policy_reload_interval(),
+            health_check_interval: 0,
+            health_check_timeout: default_health_check_timeout(),
+            health_check_concurrency: default_health_check_concurrency(),
+            health_check_rate_limit: default_health_check_rate_limit(),
+            health_check_disable_after: 0,
+            health_check_allow_private: false,
+            trash_retention_days: default_trash_retention_days(),
+        }
+    }
 }
 
 /// Admin configuration
@@ -212,6 +379,58 @@ impl Config {
             self.shortener.code_charset =
                 "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string();
         }
+        if self
+            .shortener
+            .expired_redirect_url
+            .as_deref()
+            .is_some_and(str::is_empty)
+        {
+            self.shortener.expired_redirect_url = None;
+        }
+        if self
+            .shortener
+            .unlock_secret
+            .as_deref()
+            .is_none_or(str::is_empty)
+        {
+            self.shortener.unlock_secret = Some(self.server.api_key.clone());
+        }
+        if self.shortener.unlock_ttl == 0 {
+            self.shortener.unlock_ttl = default_unlock_ttl();
+        }
+        if self.shortener.unlock_lockout == 0 {
+            self.shortener.unlock_lockout = default_unlock_lockout();
+        }
+        if self.shortener.health_check_timeout == 0 {
+            self.shortener.health_check_timeout = default_health_check_timeout();
+        }
+        if self.shortener.health_check_concurrency == 0 {
+            self.shortener.health_check_concurrency = default_health_check_concurrency();
+        }
+        if self
+            .shortener
+            .not_active_page
+            .as_deref()
+            .is_some_and(str::is_empty)
+        {
+            self.shortener.not_active_page = None;
+        }
+        if self
+            .shortener
+            .blocklist_file
+            .as_deref()
+            .is_some_and(str::is_empty)
+        {
+            self.shortener.blocklist_file = None;
+        }
+        for file in [
+            &mut self.shortener.destination_blocklist_file,
+            &mut self.shortener.destination_allowlist_file,
+        ] {
+            if file.as_deref().is_some_and(str::is_empty) {
+                *file = None;
+            }
+        }
 
         // Cache defaults
         if self.cache.expire == 0 {
@@ -264,6 +483,68 @@ impl Config {
                 "shortener.code_charset cannot be empty".to_string(),
             ));
         }
+        // `/{code}.qr` serves QR codes, so a dot must never appear in a code
+        if self.shortener.code_charset.contains('.') {
+            return Err(ConfigError::Message(
+                "shortener.code_charset cannot contain '.'".to_string(),
+            ));
+        }
+        if RedirectType::from_code(self.shortener.redirect_type).is_none() {
+            return Err(ConfigError::Message(
+                "shortener.redirect_type must be one of 301, 302, 307, 308".to_string(),
+            ));
+        }
+        if let Some(url) = &self.shortener.expired_redirect_url
+            && !url.starts_with("http://")
+            && !url.starts_with("https://")
+        {
+            return Err(ConfigError::Message(
+                "shortener.expired_redirect_url must be an http(s) URL".to_string(),
+            ));
+        }
+        if let Some(url) = self
+            .shortener
+            .domains
+            .iter()
+            .find(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            return Err(ConfigError::Message(format!(
+                "shortener.domains must contain http(s) URLs: {}",
+                url
+            )));
+        }
+        if let Some(page) = &self.shortener.not_active_page
+            && !Path::new(page).is_file()
+        {
+            return Err(ConfigError::Message(format!(
+                "shortener.not_active_page file not found: {}",
+                page
+            )));
+        }
+        if let Some(file) = &self.shortener.blocklist_file
+            && !Path::new(file).is_file()
+        {
+            return Err(ConfigError::Message(format!(
+                "shortener.blocklist_file not found: {}",
+                file
+            )));
+        }
+        for (name, file) in [
+            (
+                "destination_blocklist_file",
+                &self.shortener.destination_blocklist_file,
+            ),
+            (
+                "destination_allowlist_file",
+                &self.shortener.destination_allowlist_file,
+            ),
+        ] {
+            if let Some(file) = file
+                && let Err(e) = RuleList::load(Path::new(file))
+            {
+                return Err(ConfigError::Message(format!("shortener.{}: {}", name, e)));
+            }
+        }
 
         // Validate database configuration
         match self.database.db_type {
@@ -405,6 +686,49 @@ impl Config {
     }
 }
 
+#[cfg(test)]
+impl Config {
+    /// Configuration for unit tests: in-memory SQLite, no cache and no GeoIP
+    pub(crate) fn for_tests() -> Self {
+        Self {
+            server: ServerConfig {
+                address: ":8080".to_string(),
+                trusted_platform: None,
+                site_url: "http://localhost:8080".to_string(),
+                api_key: "test-key".to_string(),
+            },
+            shortener: ShortenerConfig::default(),
+            admin: AdminConfig {
+                username: "admin".to_string(),
+                password: "admin123".to_string(),
+            },
+            database: DatabaseConfig {
+                db_type: DatabaseType::Sqlite,
+                log_level: 0,
+                sqlite: Some(SqliteConfig {
+                    path: ":memory:".to_string(),
+                }),
+                postgres: None,
+                mysql: None,
+            },
+            cache: CacheConfig {
+                enabled: false,
+                cache_type: CacheType::Redis,
+                expire: 3600,
+                prefix: "shorten:".to_string(),
+                redis: None,
+                valkey: None,
+            },
+            geoip: GeoIpConfig {
+                enabled: false,
+                geoip_type: GeoIpType::Ip2region,
+                ip2region: None,
+            },
+            logging: LoggingConfig::default(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -459,6 +783,17 @@ type = "ip2region"
         assert_eq!(config.server.address, ":8080");
         assert_eq!(config.server.api_key, "test-api-key");
         assert_eq!(config.shortener.code_length, 6);
+        assert!(config.shortener.expired_redirect_url.is_none());
+        assert_eq!(config.shortener.expiry_sweep_interval, 60);
+        assert_eq!(
+            config.shortener.unlock_secret.as_deref(),
+            Some("test-api-key")
+        );
+        assert_eq!(config.shortener.unlock_ttl, 3600);
+        assert_eq!(config.shortener.unlock_max_attempts, 5);
+        assert_eq!(config.shortener.unlock_lockout, 300);
+        assert_eq!(config.shortener.redirect_type, 308);
+        assert!(config.shortener.not_active_page.is_none());
         assert_eq!(config.admin.username, "admin");
         assert_eq!(config.database.db_type, DatabaseType::Sqlite);
         assert!(!config.cache.enabled);
@@ -1213,4 +1548,331 @@ type = "ip2region"
                 .contains("cache.redis configuration is required")
         );
     }
+
+    #[test]
+    fn test_invalid_expired_redirect_url() {
+        let config_content = r#"
+[server]
+address = ":8080"
+site_url = "http://localhost:8080"
+api_key = "test-key"
+
+[shortener]
+code_length = 6
+code_charset = "abc"
+expired_redirect_url = "example.com/expired"
+
+[admin]
+username = "admin"
+password = "pass"
+
+[database]
+type = "sqlite"
+log_level = 1
+
+[database.sqlite]
+path = "test.db"
+
+[cache]
+enabled = false
+type = "redis"
+expire = 3600
+prefix = "shorten:"
+
+[geoip]
+enabled = false
+type = "ip2region"
+"#;
+
+        let file = create_test_config_file(config_content);
+        let result = Config::from_file(file.path());
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("shortener.expired_redirect_url")
+        );
+    }
+
+    #[test]
+    fn test_invalid_domains() {
+        let config_content = r#"
+[server]
+address = ":8080"
+site_url = "http://localhost:8080"
+api_key = "test-key"
+
+[shortener]
+code_length = 6
+code_charset = "abc"
+domains = ["https://go.example.com", "camp.example.com"]
+
+[admin]
+username = "admin"
+password = "pass"
+
+[database]
+type = "sqlite"
+log_level = 1
+
+[database.sqlite]
+path = "test.db"
+
+[cache]
+enabled = false
+type = "redis"
+expire = 3600
+prefix = "shorten:"
+
+[geoip]
+enabled = false
+type = "ip2region"
+"#;
+
+        let file = create_test_config_file(config_content);
+        let result = Config::from_file(file.path());
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("shortener.domains")
+        );
+    }
+
+    #[test]
+    fn test_invalid_redirect_type() {
+        let config_content = r#"
+[server]
+address = ":8080"
+site_url = "http://localhost:8080"
+api_key = "test-key"
+
+[shortener]
+code_length = 6
+code_charset = "abc"
+redirect_type = 303
+
+[admin]
+username = "admin"
+password = "pass"
+
+[database]
+type = "sqlite"
+log_level = 1
+
+[database.sqlite]
+path = "test.db"
+
+[cache]
+enabled = false
+type = "redis"
+expire = 3600
+prefix = "shorten:"
+
+[geoip]
+enabled = false
+type = "ip2region"
+"#;
+
+        let file = create_test_config_file(config_content);
+        let result = Config::from_file(file.path());
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("shortener.redirect_type")
+        );
+    }
+
+    #[test]
+    fn test_code_charset_with_dot() {
+        let config_content = r#"
+[server]
+address = ":8080"
+site_url = "http://localhost:8080"
+api_key = "test-key"
+
+[shortener]
+code_length = 6
+code_charset = "abc."
+
+[admin]
+username = "admin"
+password = "pass"
+
+[database]
+type = "sqlite"
+log_level = 1
+
+[database.sqlite]
+path = "test.db"
+
+[cache]
+enabled = false
+type = "redis"
+expire = 3600
+prefix = "shorten:"
+
+[geoip]
+enabled = false
+type = "ip2region"
+"#;
+
+        let file = create_test_config_file(config_content);
+        let result = Config::from_file(file.path());
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("shortener.code_charset")
+        );
+    }
+
+    #[test]
+    fn test_missing_not_active_page() {
+        let config_content = r#"
+[server]
+address = ":8080"
+site_url = "http://localhost:8080"
+api_key = "test-key"
+
+[shortener]
+code_length = 6
+code_charset = "abc"
+not_active_page = "/nonexistent/not-active.html"
+
+[admin]
+username = "admin"
+password = "pass"
+
+[database]
+type = "sqlite"
+log_level = 1
+
+[database.sqlite]
+path = "test.db"
+
+[cache]
+enabled = false
+type = "redis"
+expire = 3600
+prefix = "shorten:"
+
+[geoip]
+enabled = false
+type = "ip2region"
+"#;
+
+        let file = create_test_config_file(config_content);
+        let result = Config::from_file(file.path());
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("shortener.not_active_page")
+        );
+    }
+
+    #[test]
+    fn test_missing_blocklist_file() {
+        let config_content = r#"
+[server]
+address = ":8080"
+site_url = "http://localhost:8080"
+api_key = "test-key"
+
+[shortener]
+code_length = 6
+code_charset = "abc"
+blocklist_file = "/nonexistent/blocklist.txt"
+
+[admin]
+username = "admin"
+password = "pass"
+
+[database]
+type = "sqlite"
+log_level = 1
+
+[database.sqlite]
+path = "test.db"
+
+[cache]
+enabled = false
+type = "redis"
+expire = 3600
+prefix = "shorten:"
+
+[geoip]
+enabled = false
+type = "ip2region"
+"#;
+
+        let file = create_test_config_file(config_content);
+        let result = Config::from_file(file.path());
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("shortener.blocklist_file")
+        );
+    }
+
+    #[test]
+    fn test_invalid_destination_blocklist_file() {
+        let blocklist = create_test_config_file("evil.example\nregex:(unclosed\n");
+        let config_content = format!(
+            r#"
+[server]
+address = ":8080"
+site_url = "http://localhost:8080"
+api_key = "test-key"
+
+[shortener]
+code_length = 6
+code_charset = "abc"
+destination_blocklist_file = "{}"
+
+[admin]
+username = "admin"
+password = "pass"
+
+[database]
+type = "sqlite"
+log_level = 1
+
+[database.sqlite]
+path = "test.db"
+
+[cache]
+enabled = false
+type = "redis"
+expire = 3600
+prefix = "shorten:"
+
+[geoip]
+enabled = false
+type = "ip2region"
+"#,
+            blocklist.path().display()
+        );
+
+        let file = create_test_config_file(&config_content);
+        let error = Config::from_file(file.path()).unwrap_err().to_string();
+
+        assert!(error.contains("shortener.destination_blocklist_file"));
+        assert!(error.contains("line 2: invalid regex"));
+    }
 }
@@ -95,49 +95,12 @@ impl DbFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, DatabaseConfig, DatabaseType, SqliteConfig};
+    use crate::config::{Config, SqliteConfig};
 
     fn create_test_config() -> Config {
-        Config {
-            server: crate::config::ServerConfig {
-                address: ":8080".to_string(),
-                trusted_platform: None,
-                site_url: "http://localhost:8080".to_string(),
-                api_key: "test-key".to_string(),
-            },
-            shortener: crate::config::ShortenerConfig {
-                code_length: 6,
-                code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-                    .to_string(),
-            },
-            admin: crate::config::AdminConfig {
-                username: "admin".to_string(),
-                password: "admin123".to_string(),
-            },
-            database: DatabaseConfig {
-                db_type: DatabaseType::Sqlite,
-                log_level: 1,
-                sqlite: Some(SqliteConfig {
-                    path: ":memory:".to_string(),
-                }),
-                postgres: None,
-                mysql: None,
-            },
-            cache: crate::config::CacheConfig {
-                enabled: false,
-                cache_type: crate::config::CacheType::Redis,
-                expire: 3600,
-                prefix: "shorten:".to_string(),
-                redis: None,
-                valkey: None,
-            },
-            geoip: crate::config::GeoIpConfig {
-                enabled: false,
-                geoip_type: crate::config::GeoIpType::Ip2region,
-                ip2region: None,
-            },
-            logging: crate::logging::LoggingConfig::default(),
-        }
+        let mut config = Config::for_tests();
+        config.database.log_level = 1;
+        config
     }
 
     #[tokio::test]
@@ -0,0 +1,216 @@
+use std::fmt;
+
+/// Identifies a short URL: its code within the namespace of a short domain
+///
+/// An empty `domain` stands for the default domain (`server.site_url`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LinkKey {
+    pub domain: String,
+    pub code: String,
+}
+
+impl LinkKey {
+    pub fn new(domain: impl Into<String>, code: impl Into<String>) -> Self {
+        Self {
+            domain: domain.into(),
+            code: code.into(),
+        }
+    }
+}
+
+/// `code` on the default domain, `domain/code` otherwise
+impl fmt::Display for LinkKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.domain.is_empty() {
+            write!(f, "{}", self.code)
+        } else {
+            write!(f, "{}/{}", self.domain, self.code)
+        }
+    }
+}
+
+/// A bare code lives on the default domain
+impl From<&str> for LinkKey {
+    fn from(code: &str) -> Self {
+        Self::new("", code)
+    }
+}
+
+impl From<&String> for LinkKey {
+    fn from(code: &String) -> Self {
+        Self::new("", code.as_str())
+    }
+}
+
+/// Short domains served by this instance
+///
+/// Links on the default domain are stored with an empty domain, so changing
+/// `site_url` moves them along; every additional domain is stored by host.
+#[derive(Debug, Clone)]
+pub struct Domains {
+    site_url: String,
+    default_host: String,
+    extra: Vec<(String, String)>,
+}
+
+impl Domains {
+    /// Build from the default site URL and the additional domain URLs
+    pub fn new(site_url: &str, domains: &[String]) -> Self {
+        let site_url = site_url.trim_end_matches('/').to_string();
+        let default_host = host_of(&site_url);
+
+        let mut extra: Vec<(String, String)> = Vec::new();
+        for url in domains {
+            let url = url.trim().trim_end_matches('/');
+            let host = host_of(url);
+            if host.is_empty() || host == default_host || extra.iter().any(|(h, _)| *h == host) {
+                continue;
+            }
+            extra.push((host, url.to_string()));
+        }
+
+        Self {
+            site_url,
+            default_host,
+            extra,
+        }
+    }
+
+    /// Stored domain of a domain name given by an API client
+    ///
+    /// Accepts the empty string, the default host or any configured host.
+    pub fn resolve(&self, domain: &str) -> Result<String, String> {
+        let host = host_of(domain);
+        if host.is_empty() || host == self.default_host {
+            return Ok(String::new());
+        }
+        if self.extra.iter().any(|(h, _)| *h == host) {
+            return Ok(host);
+        }
+
+        Err(format!("Unknown domain: {}", domain))
+    }
+
+    /// Stored domain of the host a request was sent to
+    ///
+    /// Unknown hosts (IP addresses, internal names) map to the default domain.
+    pub fn for_host(&self, host: &str) -> String {
+        let host = host_of(host);
+        let hostname = strip_port(&host);
+
+        self.extra
+            .iter()
+            .find(|(h, _)| *h == host || strip_port(h) == hostname)
+            .map(|(h, _)| h.clone())
+            .unwrap_or_default()
+    }
+
+    /// Base URL short URLs of a stored domain are built from
+    pub fn site_url(&self, domain: &str) -> &str {
+        self.extra
+            .iter()
+            .find(|(h, _)| h == domain)
+            .map_or(&self.site_url, |(_, url)| url)
+    }
+
+    /// Host names of every domain, the default one first
+    pub fn hosts(&self) -> Vec<&str> {
+        std::iter::once(self.default_host.as_str())
+            .chain(self.extra.iter().map(|(h, _)| h.as_str()))
+            .collect()
+    }
+}
+
+/// Lowercase host (with port) of a URL or bare host name
+pub fn host_of(url: &str) -> String {
+    let rest = url
+        .trim()
+        .split_once("://")
+        .map_or(url.trim(), |(_, rest)| rest);
+    rest.split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+fn strip_port(host: &str) -> &str {
+    host.rsplit_once(':')
+        .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+        .map_or(host, |(name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn domains() -> Domains {
+        Domains::new(
+            "https://s.example.com/",
+            &[
+                "https://go.brand.com".to_string(),
+                "https://camp.example.org".to_string(),
+                "https://s.example.com".to_string(),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://Go.Brand.com/path?x=1"), "go.brand.com");
+        assert_eq!(host_of("http://localhost:8080"), "localhost:8080");
+        assert_eq!(host_of("camp.example.org"), "camp.example.org");
+        assert_eq!(host_of(""), "");
+    }
+
+    #[test]
+    fn test_resolve_domain() {
+        let domains = domains();
+
+        assert_eq!(domains.resolve("").unwrap(), "");
+        assert_eq!(domains.resolve("s.example.com").unwrap(), "");
+        assert_eq!(domains.resolve("GO.brand.com").unwrap(), "go.brand.com");
+        assert_eq!(
+            domains.resolve("https://camp.example.org/").unwrap(),
+            "camp.example.org"
+        );
+        assert!(domains.resolve("evil.example.net").is_err());
+
+        assert_eq!(
+            domains.hosts(),
+            vec!["s.example.com", "go.brand.com", "camp.example.org"]
+        );
+    }
+
+    #[test]
+    fn test_domain_for_host() {
+        let domains = domains();
+
+        assert_eq!(domains.for_host("go.brand.com"), "go.brand.com");
+        assert_eq!(domains.for_host("GO.BRAND.COM:443"), "go.brand.com");
+        assert_eq!(domains.for_host("s.example.com"), "");
+        assert_eq!(domains.for_host("127.0.0.1:8080"), "");
+    }
+
+    #[test]
+    fn test_link_key_display() {
+        assert_eq!(LinkKey::from("abc").to_string(), "abc");
+        assert_eq!(
+            LinkKey::new("go.brand.com", "abc").to_string(),
+            "go.brand.com/abc"
+        );
+    }
+
+    #[test]
+    fn test_site_url_per_domain() {
+        let domains = domains();
+
+        assert_eq!(domains.site_url(""), "https://s.example.com");
+        assert_eq!(domains.site_url("go.brand.com"), "https://go.brand.com");
+        assert_eq!(
+            domains.site_url("camp.example.org"),
+            "https://camp.example.org"
+        );
+        // Domains removed from the configuration fall back to the default
+        assert_eq!(domains.site_url("old.example.net"), "https://s.example.com");
+    }
+}
@@ -6,6 +6,8 @@ use axum::{
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::destination::UrlError;
+
 // Re-export error codes from common
 pub use shortener_common::error_codes;
 
@@ -27,6 +29,24 @@ pub enum AppError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    #[error("Gone: {0}")]
+    Gone(String),
+
+    #[error("Click limit reached: {0}")]
+    ClickLimitReached(String),
+
+    #[error("Not yet active: {0}")]
+    NotYetActive(String),
+
+    #[error("Code reserved: {0}")]
+    CodeReserved(String),
+
+    #[error("{0}")]
+    InvalidUrl(UrlError),
+
+    #[error("Destination blocked: {0}")]
+    DestinationBlocked(String),
+
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
@@ -45,6 +65,9 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 }
@@ -54,6 +77,9 @@ pub enum AppError {
 pub struct ErrorResponse {
     pub errcode: String,
     pub errinfo: String,
+    /// Machine readable context, e.g. which part of a URL is invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 impl ErrorResponse {
@@ -61,18 +87,48 @@ impl ErrorResponse {
         Self {
             errcode: errcode.into(),
             errinfo: errinfo.into(),
+            details: None,
         }
     }
+
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
 }
 
-/// 实现 IntoResponse trait 用于 HTTP 错误响应
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, errcode, errinfo) = match &self {
+impl AppError {
+    /// HTTP 状态码和错误响应体，批量接口中单项的错误也使用它
+    pub fn response_parts(&self) -> (StatusCode, ErrorResponse) {
+        let (status, errcode, errinfo) = match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, error_codes::NOT_FOUND, msg.clone()),
             AppError::Conflict(msg) => {
                 (StatusCode::CONFLICT, error_codes::CODE_EXISTS, msg.clone())
             }
+            AppError::Gone(msg) => (StatusCode::GONE, error_codes::URL_EXPIRED, msg.clone()),
+            AppError::ClickLimitReached(msg) => (
+                StatusCode::GONE,
+                error_codes::CLICK_LIMIT_REACHED,
+                msg.clone(),
+            ),
+            AppError::NotYetActive(msg) => (
+                StatusCode::FORBIDDEN,
+                error_codes::URL_NOT_ACTIVE,
+                msg.clone(),
+            ),
+            AppError::CodeReserved(msg) => (
+                StatusCode::BAD_REQUEST,
+                error_codes::CODE_RESERVED,
+                msg.clone(),
+            ),
+            AppError::InvalidUrl(err) => (
+                StatusCode::BAD_REQUEST,
+                error_codes::INVALID_URL,
+                err.to_string(),
+            ),
+            AppError::DestinationBlocked(msg) => {
+                (StatusCode::FORBIDDEN, error_codes::URL_BLOCKED, msg.clone())
+            }
             AppError::Unauthorized(msg) => (
                 StatusCode::UNAUTHORIZED,
                 error_codes::UNAUTHORIZED,
@@ -120,6 +176,11 @@ impl IntoResponse for AppError {
                 error_codes::CONFIG_ERROR,
                 msg.clone(),
             ),
+            AppError::TooManyRequests(msg) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                error_codes::TOO_MANY_REQUESTS,
+                msg.clone(),
+            ),
             AppError::ServiceUnavailable(msg) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 error_codes::SYSTEM_ERROR,
@@ -135,8 +196,19 @@ impl IntoResponse for AppError {
             }
         };
 
-        let body = Json(ErrorResponse::new(errcode, errinfo));
-        (status, body).into_response()
+        let mut body = ErrorResponse::new(errcode, errinfo);
+        if let AppError::InvalidUrl(err) = self {
+            body = body.with_details(err);
+        }
+        (status, body)
+    }
+}
+
+/// 实现 IntoResponse trait 用于 HTTP 错误响应
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, body) = self.response_parts();
+        (status, Json(body)).into_response()
     }
 }
 
@@ -196,9 +268,39 @@ pub enum ServiceError {
     #[error("Already exists: {0}")]
     AlreadyExists(String),
 
+    #[error("Expired: {0}")]
+    Expired(String),
+
+    #[error("Click limit reached: {0}")]
+    ClickLimitReached(String),
+
+    #[error("Not yet active: {0}")]
+    NotYetActive(String),
+
+    #[error("Password required: {0}")]
+    PasswordRequired(String),
+
+    #[error("Too many attempts: {0}")]
+    TooManyAttempts(String),
+
+    #[error("Code reserved: {0}")]
+    CodeReserved(String),
+
+    #[error("{0}")]
+    InvalidUrl(#[from] UrlError),
+
+    #[error("Destination blocked: {0}")]
+    DestinationBlocked(String),
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Cache error: {0}")]
     Cache(String),
 
@@ -211,7 +313,17 @@ impl From<ServiceError> for AppError {
         match err {
             ServiceError::NotFound(msg) => AppError::NotFound(msg),
             ServiceError::AlreadyExists(msg) => AppError::Conflict(msg),
+            ServiceError::Expired(msg) => AppError::Gone(msg),
+            ServiceError::ClickLimitReached(msg) => AppError::ClickLimitReached(msg),
+            ServiceError::NotYetActive(msg) => AppError::NotYetActive(msg),
+            ServiceError::PasswordRequired(msg) => AppError::Unauthorized(msg),
+            ServiceError::TooManyAttempts(msg) => AppError::TooManyRequests(msg),
+            ServiceError::CodeReserved(msg) => AppError::CodeReserved(msg),
+            ServiceError::InvalidUrl(err) => AppError::InvalidUrl(err),
+            ServiceError::DestinationBlocked(msg) => AppError::DestinationBlocked(msg),
             ServiceError::InvalidInput(msg) => AppError::BadRequest(msg),
+            ServiceError::Unauthorized(msg) => AppError::Unauthorized(msg),
+            ServiceError::Forbidden(msg) => AppError::Forbidden(msg),
             ServiceError::Cache(msg) => AppError::Cache(msg),
             ServiceError::Repository(msg) | ServiceError::Internal(msg) => AppError::Internal(msg),
         }
@@ -331,6 +443,48 @@ mod tests {
         assert!(matches!(app_error, AppError::Conflict(_)));
     }
 
+    #[test]
+    fn test_service_error_expired_conversion() {
+        let service_error = ServiceError::Expired("Link expired".to_string());
+        let app_error: AppError = service_error.into();
+        assert!(matches!(app_error, AppError::Gone(_)));
+    }
+
+    #[test]
+    fn test_service_error_click_limit_conversion() {
+        let service_error = ServiceError::ClickLimitReached("Quota used".to_string());
+        let app_error: AppError = service_error.into();
+        assert!(matches!(app_error, AppError::ClickLimitReached(_)));
+    }
+
+    #[test]
+    fn test_service_error_not_yet_active_conversion() {
+        let service_error = ServiceError::NotYetActive("Not live yet".to_string());
+        let app_error: AppError = service_error.into();
+        assert!(matches!(app_error, AppError::NotYetActive(_)));
+    }
+
+    #[test]
+    fn test_service_error_forbidden_conversion() {
+        let service_error = ServiceError::Forbidden("Administrator role required".to_string());
+        let app_error: AppError = service_error.into();
+        assert!(matches!(app_error, AppError::Forbidden(_)));
+    }
+
+    #[test]
+    fn test_service_error_code_reserved_conversion() {
+        let service_error = ServiceError::CodeReserved("Reserved".to_string());
+        let app_error: AppError = service_error.into();
+        assert!(matches!(app_error, AppError::CodeReserved(_)));
+    }
+
+    #[test]
+    fn test_service_error_destination_blocked_conversion() {
+        let service_error = ServiceError::DestinationBlocked("Phishing".to_string());
+        let app_error: AppError = service_error.into();
+        assert!(matches!(app_error, AppError::DestinationBlocked(_)));
+    }
+
     #[test]
     fn test_service_error_invalid_input_conversion() {
         let service_error = ServiceError::InvalidInput("Invalid data".to_string());
@@ -359,6 +513,11 @@ mod tests {
         assert_eq!(error_codes::URL_NOT_FOUND, "10001");
         assert_eq!(error_codes::CODE_EXISTS, "10002");
         assert_eq!(error_codes::INVALID_URL, "10003");
+        assert_eq!(error_codes::URL_EXPIRED, "10004");
+        assert_eq!(error_codes::CLICK_LIMIT_REACHED, "10005");
+        assert_eq!(error_codes::URL_NOT_ACTIVE, "10006");
+        assert_eq!(error_codes::CODE_RESERVED, "10007");
+        assert_eq!(error_codes::URL_BLOCKED, "10008");
         assert_eq!(error_codes::UNAUTHORIZED, "40001");
         assert_eq!(error_codes::FORBIDDEN, "40003");
         assert_eq!(error_codes::NOT_FOUND, "40004");
@@ -381,6 +540,50 @@ mod tests {
         assert_eq!(response.status(), StatusCode::CONFLICT);
     }
 
+    #[tokio::test]
+    async fn test_app_error_into_response_gone() {
+        let error = AppError::Gone("Expired link".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::GONE);
+    }
+
+    #[tokio::test]
+    async fn test_app_error_into_response_not_yet_active() {
+        let error = AppError::NotYetActive("Scheduled link".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_app_error_into_response_code_reserved() {
+        let error = AppError::CodeReserved("Reserved code".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_app_error_into_response_destination_blocked() {
+        let error = AppError::DestinationBlocked("Blocked destination".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_app_error_into_response_invalid_url() {
+        let error: AppError =
+            ServiceError::from(crate::destination::normalize("ftp://example.com").unwrap_err())
+                .into();
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["errcode"], error_codes::INVALID_URL);
+        assert_eq!(json["details"]["part"], "scheme");
+    }
+
     #[tokio::test]
     async fn test_app_error_into_response_unauthorized() {
         let error = AppError::Unauthorized("No token".to_string());
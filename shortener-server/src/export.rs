@@ -0,0 +1,448 @@
+use axum::http::{HeaderName, header};
+use chrono::{DateTime, SecondsFormat};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::models::url::UrlStatus;
+use crate::services::{HistoryResponse, ShortenResponse};
+
+/// Number of records read from the database at a time
+pub const EXPORT_BATCH_SIZE: u64 = 500;
+
+/// Format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Comma separated values with a header row, status written by name
+    #[default]
+    Csv,
+    /// One JSON object per line, the same objects as the list endpoints
+    Ndjson,
+    /// CSV that columnar tools (DuckDB, Arrow, Spark) load without type
+    /// guessing: one line per record, numeric status, `true`/`false`
+    /// booleans, UTC timestamps with microseconds and empty nulls
+    ParquetCsv,
+}
+
+impl ExportFormat {
+    /// MIME type of the exported file
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv | ExportFormat::ParquetCsv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// File name extension of the exported file
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv | ExportFormat::ParquetCsv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Response headers of an export, offering it as a file named after `name`
+pub fn headers(format: ExportFormat, name: &str) -> [(HeaderName, String); 2] {
+    [
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", name, format.extension()),
+        ),
+    ]
+}
+
+/// Options of an export, taken from the query string next to the list filters
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ExportOptions {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// A record that can be exported
+pub trait ExportRecord: Serialize {
+    /// CSV header row
+    const COLUMNS: &'static [&'static str];
+
+    /// ID the next batch starts after
+    fn id(&self) -> i64;
+
+    /// Field values in column order, `None` for null
+    fn fields(&self, typed: bool) -> Vec<Option<String>>;
+}
+
+impl ExportRecord for ShortenResponse {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "domain",
+        "short_code",
+        "short_url",
+        "original_url",
+        "description",
+        "status",
+        "expires_at",
+        "active_from",
+        "active_until",
+        "max_clicks",
+        "click_count",
+        "password_protected",
+        "redirect_type",
+        "targeting_rules",
+        "variants",
+        "sticky_variants",
+        "forward_query",
+        "forward_path",
+        "last_checked_at",
+        "last_status",
+        "last_error",
+        "tags",
+        "metadata",
+        "created_at",
+        "updated_at",
+    ];
+
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn fields(&self, typed: bool) -> Vec<Option<String>> {
+        let time = |value: &Option<String>| value.as_deref().map(|value| timestamp(value, typed));
+        let status = if typed {
+            self.status.to_string()
+        } else {
+            status_name(self.status).to_string()
+        };
+
+        vec![
+            Some(self.id.to_string()),
+            Some(self.domain.clone()),
+            Some(self.short_code.clone()),
+            Some(self.short_url.clone()),
+            Some(self.original_url.clone()),
+            self.description.clone(),
+            Some(status),
+            time(&self.expires_at),
+            time(&self.active_from),
+            time(&self.active_until),
+            self.max_clicks.map(|clicks| clicks.to_string()),
+            Some(self.click_count.to_string()),
+            Some(self.password_protected.to_string()),
+            self.redirect_type.map(|code| code.to_string()),
+            json_list(&self.targeting_rules),
+            json_list(&self.variants),
+            Some(self.sticky_variants.to_string()),
+            self.forward_query.map(|forward| forward.to_string()),
+            self.forward_path.map(|forward| forward.to_string()),
+            time(&self.last_checked_at),
+            self.last_status.map(|status| status.to_string()),
+            self.last_error.clone(),
+            (!self.tags.is_empty()).then(|| self.tags.join(",")),
+            (!self.metadata.is_empty())
+                .then(|| serde_json::to_string(&self.metadata).ok())
+                .flatten(),
+            Some(timestamp(&self.created_at, typed)),
+            Some(timestamp(&self.updated_at, typed)),
+        ]
+    }
+}
+
+impl ExportRecord for HistoryResponse {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "url_id",
+        "short_code",
+        "ip_address",
+        "user_agent",
+        "referer",
+        "country",
+        "region",
+        "province",
+        "city",
+        "isp",
+        "device_type",
+        "os",
+        "browser",
+        "targeting_rule",
+        "variant",
+        "accessed_at",
+        "created_at",
+    ];
+
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn fields(&self, typed: bool) -> Vec<Option<String>> {
+        vec![
+            Some(self.id.to_string()),
+            Some(self.url_id.to_string()),
+            Some(self.short_code.clone()),
+            Some(self.ip_address.clone()),
+            Some(self.user_agent.clone()),
+            self.referer.clone(),
+            self.country.clone(),
+            self.region.clone(),
+            self.province.clone(),
+            self.city.clone(),
+            self.isp.clone(),
+            self.device_type.clone(),
+            self.os.clone(),
+            self.browser.clone(),
+            self.targeting_rule.map(|rule| rule.to_string()),
+            self.variant.clone(),
+            Some(timestamp(&self.accessed_at, typed)),
+            Some(timestamp(&self.created_at, typed)),
+        ]
+    }
+}
+
+/// Encode records, with the header row first for CSV formats
+pub fn encode<T: ExportRecord>(format: ExportFormat, records: &[T], header: bool) -> String {
+    let mut out = String::new();
+
+    if header && format != ExportFormat::Ndjson {
+        let columns: Vec<Option<String>> = T::COLUMNS
+            .iter()
+            .map(|column| Some(column.to_string()))
+            .collect();
+        push_csv_row(&mut out, &columns, false);
+    }
+
+    for record in records {
+        match format {
+            ExportFormat::Csv => push_csv_row(&mut out, &record.fields(false), false),
+            ExportFormat::ParquetCsv => push_csv_row(&mut out, &record.fields(true), true),
+            ExportFormat::Ndjson => {
+                // Serializing response DTOs cannot fail
+                out.push_str(&serde_json::to_string(record).unwrap_or_default());
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+/// Stream an export batch by batch
+///
+/// `first` is the batch starting the export, fetched up front so errors such
+/// as invalid filters are reported before the response starts. `fetch` reads
+/// the batch following an ID; a batch shorter than [`EXPORT_BATCH_SIZE`] ends
+/// the export. An error ends the stream, cutting the response short.
+pub fn stream<T, E, F, Fut>(
+    format: ExportFormat,
+    first: Vec<T>,
+    fetch: F,
+) -> impl Stream<Item = Result<String, E>>
+where
+    T: ExportRecord,
+    F: FnMut(i64) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, E>>,
+{
+    struct Export<T, F> {
+        fetch: F,
+        pending: Option<Vec<T>>,
+        after_id: Option<i64>,
+        header: bool,
+    }
+
+    let export = Export {
+        fetch,
+        pending: Some(first),
+        after_id: None,
+        header: true,
+    };
+
+    futures_util::stream::unfold(Some(export), move |export| async move {
+        let mut export = export?;
+
+        let batch = match export.pending.take() {
+            Some(batch) => batch,
+            None => match (export.fetch)(export.after_id?).await {
+                Ok(batch) => batch,
+                Err(e) => return Some((Err(e), None)),
+            },
+        };
+
+        export.after_id = if batch.len() as u64 >= EXPORT_BATCH_SIZE {
+            batch.last().map(ExportRecord::id)
+        } else {
+            None
+        };
+
+        let chunk = encode(format, &batch, export.header);
+        export.header = false;
+        if chunk.is_empty() {
+            return None;
+        }
+
+        Some((Ok(chunk), Some(export)))
+    })
+}
+
+/// Name of a link status
+fn status_name(status: i32) -> &'static str {
+    match UrlStatus::from(status) {
+        UrlStatus::Enabled => "enabled",
+        UrlStatus::Disabled => "disabled",
+        UrlStatus::Expired => "expired",
+    }
+}
+
+/// RFC 3339 time, normalized to UTC with microseconds when typed
+fn timestamp(value: &str, typed: bool) -> String {
+    match DateTime::parse_from_rfc3339(value) {
+        Ok(time) if typed => time.to_utc().to_rfc3339_opts(SecondsFormat::Micros, true),
+        _ => value.to_string(),
+    }
+}
+
+/// JSON array of a list, `None` when it is empty
+fn json_list<T: Serialize>(items: &[T]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+
+    serde_json::to_string(items).ok()
+}
+
+/// Append one CSV row
+///
+/// Fields holding a separator, quote or line break are quoted. On one-line
+/// rows line breaks are replaced by spaces instead.
+fn push_csv_row(out: &mut String, fields: &[Option<String>], one_line: bool) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+
+        let Some(field) = field else {
+            continue;
+        };
+        let field = if one_line {
+            field.replace("\r\n", " ").replace(['\r', '\n'], " ")
+        } else {
+            field.clone()
+        };
+
+        if field.contains([',', '"', '\r', '\n']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&field);
+        }
+    }
+
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn history(id: i64) -> HistoryResponse {
+        HistoryResponse {
+            id,
+            url_id: 1,
+            short_code: "abc".to_string(),
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: "Mozilla/5.0 (X11, Linux)".to_string(),
+            referer: None,
+            country: Some("中国".to_string()),
+            region: None,
+            province: None,
+            city: None,
+            isp: None,
+            device_type: None,
+            os: None,
+            browser: None,
+            targeting_rule: None,
+            variant: Some("line\nbreak".to_string()),
+            accessed_at: "2026-10-17T08:00:00+08:00".to_string(),
+            created_at: "2026-10-17T00:00:00.5+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_encode_csv() {
+        let csv = encode(ExportFormat::Csv, &[history(1)], true);
+        let mut lines = csv.lines();
+
+        assert_eq!(lines.next().unwrap(), HistoryResponse::COLUMNS.join(","));
+        assert_eq!(
+            csv.split_once('\n').unwrap().1,
+            "1,1,abc,127.0.0.1,\"Mozilla/5.0 (X11, Linux)\",,中国,,,,,,,,,\"line\nbreak\",2026-10-17T08:00:00+08:00,2026-10-17T00:00:00.5+00:00\n"
+        );
+
+        // Batches after the first one leave the header out
+        assert!(encode(ExportFormat::Csv, &[history(2)], false).starts_with("2,1,abc"));
+    }
+
+    #[test]
+    fn test_encode_parquet_csv() {
+        let csv = encode(ExportFormat::ParquetCsv, &[history(1)], false);
+
+        assert_eq!(
+            csv,
+            "1,1,abc,127.0.0.1,\"Mozilla/5.0 (X11, Linux)\",,中国,,,,,,,,,line break,2026-10-17T00:00:00.000000Z,2026-10-17T00:00:00.500000Z\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_ndjson() {
+        let ndjson = encode(ExportFormat::Ndjson, &[history(1), history(2)], true);
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["id"], 2);
+        assert_eq!(lines[0]["variant"], "line\nbreak");
+    }
+
+    #[tokio::test]
+    async fn test_stream_batches() {
+        let total = EXPORT_BATCH_SIZE as i64 + 3;
+        let batch = |after_id: i64| -> Vec<HistoryResponse> {
+            (after_id + 1..=total)
+                .take(EXPORT_BATCH_SIZE as usize)
+                .map(history)
+                .collect()
+        };
+
+        let chunks: Vec<String> = stream(ExportFormat::Ndjson, batch(0), |after_id| async move {
+            Ok::<_, String>(batch(after_id))
+        })
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.concat().lines().count(), total as usize);
+
+        // An empty export still has its header row
+        let chunks: Vec<Result<String, String>> = stream(
+            ExportFormat::Csv,
+            Vec::<HistoryResponse>::new(),
+            |_| async { Err("not called".to_string()) },
+        )
+        .collect()
+        .await;
+        assert_eq!(chunks, vec![Ok(HistoryResponse::COLUMNS.join(",") + "\n")]);
+
+        // Errors end the stream
+        let chunks: Vec<Result<String, String>> =
+            stream(ExportFormat::Ndjson, batch(0), |_| async {
+                Err("database gone".to_string())
+            })
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1], Err("database gone".to_string()));
+    }
+}
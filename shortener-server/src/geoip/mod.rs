@@ -56,6 +56,15 @@ impl GeoIpInfo {
         Self::default()
     }
 
+    /// 过滤空字段和 ip2region 表示未知的 "0"
+    pub fn known(value: &str) -> Option<String> {
+        if value.is_empty() || value == "0" {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+
     /// 检查是否为空
     pub fn is_empty(&self) -> bool {
         self.country.is_empty()
@@ -234,6 +243,13 @@ mod tests {
         assert!(info.is_empty());
     }
 
+    #[test]
+    fn test_geoip_info_known() {
+        assert_eq!(GeoIpInfo::known("中国"), Some("中国".to_string()));
+        assert_eq!(GeoIpInfo::known("0"), None);
+        assert_eq!(GeoIpInfo::known(""), None);
+    }
+
     #[test]
     fn test_geoip_error_display() {
         let err = GeoIpError::DatabaseNotFound("/path/to/db".to_string());
@@ -1,7 +1,13 @@
-use crate::config::AdminConfig;
 use crate::errors::AppError;
-use axum::{Extension, Json, extract::State, http::StatusCode};
+use crate::models::user::UserRole;
+use crate::services::{UserResponse, UserService};
+use axum::{
+    Extension, Json,
+    extract::{FromRequestParts, State},
+    http::{StatusCode, request::Parts},
+};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
 use tracing::info;
 
@@ -27,7 +33,31 @@ pub struct LoginResponse {
 /// Current user response
 #[derive(Debug, Serialize)]
 pub struct CurrentUserResponse {
+    /// Display name, or the username when unset
     pub name: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+    pub role: UserRole,
+    pub last_login_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<UserResponse> for CurrentUserResponse {
+    fn from(user: UserResponse) -> Self {
+        Self {
+            name: user
+                .display_name
+                .clone()
+                .unwrap_or_else(|| user.username.clone()),
+            username: user.username,
+            display_name: user.display_name,
+            email: user.email,
+            role: user.role,
+            last_login_at: user.last_login_at,
+            created_at: user.created_at,
+        }
+    }
 }
 
 /// User information extracted from token
@@ -36,28 +66,44 @@ pub struct User {
     pub username: String,
 }
 
+/// Marker the auth middleware adds to requests authenticated with the API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiKeyCaller;
+
+/// Actor recorded for changes made with the API key
+pub const API_KEY_ACTOR: &str = "api-key";
+
+/// Who makes a request: the logged-in user, or [`API_KEY_ACTOR`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let name = parts
+            .extensions
+            .get::<User>()
+            .map_or(API_KEY_ACTOR, |user| user.username.as_str());
+
+        Ok(Actor(name.to_string()))
+    }
+}
+
 /// Login handler
 ///
 /// POST /api/account/login
 pub async fn login(
-    State(config): State<Arc<AdminConfig>>,
+    State(service): State<Arc<UserService>>,
     Json(req): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>, AppError> {
     info!("Login attempt for user: {}", req.username);
 
-    // Verify username and password
-    if req.username != config.username {
-        return Err(AppError::Unauthorized("Invalid credentials".to_string()));
-    }
-
-    // For now, use simple string comparison for password
-    // TODO: In production, store hashed passwords in config and verify against hash
-    if req.password != config.password {
-        return Err(AppError::Unauthorized("Invalid credentials".to_string()));
-    }
+    // Verify username and password against the argon2 hash
+    let user = service.authenticate(&req.username, &req.password).await?;
 
     // Generate JWT token
-    let token = generate_token(&req.username)?;
+    let token = generate_token(&user.username)?;
 
     info!("User logged in successfully: {}", req.username);
 
@@ -83,81 +129,30 @@ pub async fn logout() -> Result<StatusCode, AppError> {
 ///
 /// GET /api/users/current
 pub async fn current_user(
+    State(service): State<Arc<UserService>>,
     Extension(user): Extension<User>,
 ) -> Result<Json<CurrentUserResponse>, AppError> {
     info!("Getting current user: {}", user.username);
 
-    Ok(Json(CurrentUserResponse {
-        name: user.username,
-    }))
-}
-
-/// Hash a password using argon2
-/// TODO: Use this for production password hashing
-#[allow(dead_code)]
-fn hash_password(password: &str) -> Result<String, AppError> {
-    use argon2::{
-        Argon2,
-        password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
-    };
-
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-
-    let password_hash = argon2
-        .hash_password(password.as_bytes(), &salt)
-        .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?
-        .to_string();
-
-    Ok(password_hash)
-}
-
-/// Verify a password against a hash using argon2
-/// TODO: Use this for production password verification
-#[allow(dead_code)]
-fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
-    use argon2::{
-        Argon2,
-        password_hash::{PasswordHash, PasswordVerifier},
-    };
-
-    let parsed_hash = PasswordHash::new(hash)
-        .map_err(|e| AppError::Internal(format!("Failed to parse password hash: {}", e)))?;
-
-    let argon2 = Argon2::default();
+    let user = service.get_user(&user.username).await?;
 
-    Ok(argon2
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok())
+    Ok(Json(user.into()))
 }
 
-/// Generate a simple token for a user (only lowercase letters and numbers)
+/// Generate a random token for a user (only lowercase letters and numbers)
+///
+/// The characters come from the thread-local CSPRNG, seeded by the OS.
 pub fn generate_token(username: &str) -> Result<String, AppError> {
+    use rand::Rng;
     use std::time::{SystemTime, UNIX_EPOCH};
 
     // Character set: lowercase letters and numbers only
-    const CHARSET: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+    const CHARSET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
     const TOKEN_LENGTH: usize = 32;
 
-    // Generate random token using timestamp and username as seed for simplicity
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-
-    // Create a simple pseudo-random generator
-    let mut seed = timestamp as u64;
-    seed ^= username.len() as u64;
-    for byte in username.bytes() {
-        seed = seed.wrapping_mul(31).wrapping_add(byte as u64);
-    }
-
+    let mut rng = rand::rng();
     let token: String = (0..TOKEN_LENGTH)
-        .map(|i| {
-            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
-            let idx = (seed as usize + i) % CHARSET.len();
-            CHARSET.chars().nth(idx).unwrap()
-        })
+        .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
         .collect();
 
     // Store token with user info and expiration (24 hours)
@@ -201,6 +196,14 @@ pub fn verify_token(token: &str) -> Result<String, AppError> {
     Err(AppError::Unauthorized("Invalid token".to_string()))
 }
 
+/// Invalidate every token of a user, e.g. after the account is disabled
+pub fn revoke_tokens(username: &str) {
+    get_token_store()
+        .lock()
+        .unwrap()
+        .retain(|_, (owner, _)| owner != username);
+}
+
 /// Get the global token store
 fn get_token_store() -> &'static std::sync::Mutex<std::collections::HashMap<String, (String, u64)>>
 {
@@ -215,15 +218,6 @@ fn get_token_store() -> &'static std::sync::Mutex<std::collections::HashMap<Stri
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_hash_and_verify_password() {
-        let password = "test_password_123";
-        let hash = hash_password(password).unwrap();
-
-        assert!(verify_password(password, &hash).unwrap());
-        assert!(!verify_password("wrong_password", &hash).unwrap());
-    }
-
     #[test]
     fn test_generate_and_verify_token() {
         let username = "testuser";
@@ -231,6 +225,16 @@ mod tests {
 
         let verified_username = verify_token(&token).unwrap();
         assert_eq!(verified_username, username);
+
+        // Tokens do not repeat, even for the same user at the same time
+        let other = generate_token(username).unwrap();
+        assert_ne!(other, token);
+        assert_eq!(other.len(), 32);
+        assert!(
+            other
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_ascii_lowercase())
+        );
     }
 
     #[test]
@@ -239,12 +243,24 @@ mod tests {
         assert!(result.is_err());
     }
 
+    async fn setup_test_service() -> Arc<UserService> {
+        use crate::config::Config;
+        use crate::db::DbFactory;
+        use crate::repositories::UserRepositoryImpl;
+
+        let config = Config::for_tests();
+
+        let db = DbFactory::create_connection(&config).await.unwrap();
+        DbFactory::run_migrations(&db).await.unwrap();
+
+        let service = UserService::new(Arc::new(UserRepositoryImpl::new(db)));
+        service.ensure_admin(&config.admin).await.unwrap();
+        Arc::new(service)
+    }
+
     #[tokio::test]
     async fn test_login_handler() {
-        let config = Arc::new(AdminConfig {
-            username: "admin".to_string(),
-            password: "admin123".to_string(),
-        });
+        let service = setup_test_service().await;
 
         let req = LoginRequest {
             username: "admin".to_string(),
@@ -252,20 +268,18 @@ mod tests {
             auto_login: false,
         };
 
-        let result = login(State(config), Json(req)).await;
+        let result = login(State(service), Json(req)).await;
         assert!(result.is_ok());
 
         let response = result.unwrap().0;
         assert!(!response.token.is_empty());
         assert!(response.error_code.is_none());
+        assert_eq!(verify_token(&response.token).unwrap(), "admin");
     }
 
     #[tokio::test]
     async fn test_login_invalid_username() {
-        let config = Arc::new(AdminConfig {
-            username: "admin".to_string(),
-            password: "admin123".to_string(),
-        });
+        let service = setup_test_service().await;
 
         let req = LoginRequest {
             username: "wrong".to_string(),
@@ -273,11 +287,25 @@ mod tests {
             auto_login: false,
         };
 
-        let result = login(State(config), Json(req)).await;
+        let result = login(State(service), Json(req)).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::Unauthorized(_)));
     }
 
+    #[tokio::test]
+    async fn test_login_invalid_password() {
+        let service = setup_test_service().await;
+
+        let req = LoginRequest {
+            username: "admin".to_string(),
+            password: "wrong-password".to_string(),
+            auto_login: false,
+        };
+
+        let result = login(State(service), Json(req)).await;
+        assert!(matches!(result.unwrap_err(), AppError::Unauthorized(_)));
+    }
+
     #[tokio::test]
     async fn test_logout_handler() {
         let result = logout().await;
@@ -287,14 +315,30 @@ mod tests {
 
     #[tokio::test]
     async fn test_current_user_handler() {
+        let service = setup_test_service().await;
         let user = User {
-            username: "testuser".to_string(),
+            username: "admin".to_string(),
         };
 
-        let result = current_user(Extension(user)).await;
+        let result = current_user(State(service), Extension(user)).await;
         assert!(result.is_ok());
 
         let response = result.unwrap().0;
-        assert_eq!(response.name, "testuser");
+        assert_eq!(response.name, "admin");
+        assert_eq!(response.username, "admin");
+        assert_eq!(response.role, UserRole::Admin);
+    }
+
+    #[test]
+    fn test_revoke_tokens() {
+        let kept = generate_token("revoke-keep").unwrap();
+        let first = generate_token("revoke-me").unwrap();
+        let second = generate_token("revoke-me").unwrap();
+
+        revoke_tokens("revoke-me");
+
+        assert!(verify_token(&first).is_err());
+        assert!(verify_token(&second).is_err());
+        assert_eq!(verify_token(&kept).unwrap(), "revoke-keep");
     }
 }
@@ -1,22 +1,50 @@
 use crate::errors::AppError;
+use crate::export::{self, ExportOptions};
 use crate::repositories::history_repository::HistoryListParams;
-use crate::services::{HistoryResponse, HistoryService, PagedResponse};
+use crate::services::{HistoryListResponse, HistoryService};
 use axum::{
     Json,
+    body::Body,
     extract::{Query, State},
     http::StatusCode,
+    response::IntoResponse,
 };
 use serde::Deserialize;
 use std::sync::Arc;
 use tracing::info;
 
+/// Export access histories as CSV or NDJSON
+///
+/// GET /api/histories/export
+///
+/// Takes the filters of the list endpoint and streams every matching record.
+pub async fn export_histories(
+    State(service): State<Arc<HistoryService>>,
+    Query(params): Query<HistoryListParams>,
+    Query(options): Query<ExportOptions>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Exporting histories: format={:?}", options.format);
+
+    let first = service.export_histories(&params, 0).await?;
+    let chunks = export::stream(options.format, first, move |after_id| {
+        let service = service.clone();
+        let params = params.clone();
+        async move { service.export_histories(&params, after_id).await }
+    });
+
+    Ok((
+        export::headers(options.format, "histories"),
+        Body::from_stream(chunks),
+    ))
+}
+
 /// List access histories with pagination
 ///
 /// GET /api/histories
 pub async fn list_histories(
     State(service): State<Arc<HistoryService>>,
     Query(params): Query<HistoryListParams>,
-) -> Result<Json<PagedResponse<HistoryResponse>>, AppError> {
+) -> Result<Json<HistoryListResponse>, AppError> {
     info!(
         "Listing histories: page={}, per_page={}",
         params.page, params.page_size
@@ -54,7 +82,7 @@ pub async fn delete_histories(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, DatabaseConfig, DatabaseType, SqliteConfig};
+    use crate::config::Config;
     use crate::db::DbFactory;
     use crate::geoip::NullGeoIp;
     use crate::repositories::history_repository::HistoryRepositoryImpl;
@@ -65,46 +93,7 @@ mod tests {
     use tower::ServiceExt;
 
     async fn setup_test_app() -> (Router, Arc<dyn UrlRepository>) {
-        let config = Config {
-            server: crate::config::ServerConfig {
-                address: ":8080".to_string(),
-                trusted_platform: None,
-                site_url: "http://localhost:8080".to_string(),
-                api_key: "test-key".to_string(),
-            },
-            shortener: crate::config::ShortenerConfig {
-                code_length: 6,
-                code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-                    .to_string(),
-            },
-            admin: crate::config::AdminConfig {
-                username: "admin".to_string(),
-                password: "admin123".to_string(),
-            },
-            database: DatabaseConfig {
-                db_type: DatabaseType::Sqlite,
-                log_level: 0,
-                sqlite: Some(SqliteConfig {
-                    path: ":memory:".to_string(),
-                }),
-                postgres: None,
-                mysql: None,
-            },
-            cache: crate::config::CacheConfig {
-                enabled: false,
-                cache_type: crate::config::CacheType::Redis,
-                expire: 3600,
-                prefix: "shorten:".to_string(),
-                redis: None,
-                valkey: None,
-            },
-            geoip: crate::config::GeoIpConfig {
-                enabled: false,
-                geoip_type: crate::config::GeoIpType::Ip2region,
-                ip2region: None,
-            },
-            logging: crate::logging::LoggingConfig::default(),
-        };
+        let config = Config::for_tests();
 
         let db = DbFactory::create_connection(&config).await.unwrap();
         DbFactory::run_migrations(&db).await.unwrap();
@@ -1,7 +1,15 @@
 pub mod account;
 pub mod history;
+pub mod policy;
+pub mod redirect;
 pub mod shorten;
+pub mod tag;
+pub mod user;
 
 pub use account::*;
 pub use history::*;
+pub use policy::*;
+pub use redirect::*;
 pub use shorten::*;
+pub use tag::*;
+pub use user::*;
@@ -0,0 +1,32 @@
+use crate::errors::AppError;
+use crate::policy::PolicyStats;
+use crate::services::{BlockedLinkResponse, ShortenService};
+use axum::{Json, extract::State};
+use std::sync::Arc;
+use tracing::info;
+
+/// List short URLs whose destination is blocked by the current policy
+///
+/// GET /api/policy/matches
+pub async fn list_policy_matches(
+    State(service): State<Arc<ShortenService>>,
+) -> Result<Json<Vec<BlockedLinkResponse>>, AppError> {
+    info!("Listing short URLs matching the destination blocklist");
+
+    let links = service.list_blocked_links().await?;
+
+    Ok(Json(links))
+}
+
+/// Reload the destination blocklist and allowlist files
+///
+/// POST /api/policy/reload
+pub async fn reload_policy(
+    State(service): State<Arc<ShortenService>>,
+) -> Result<Json<PolicyStats>, AppError> {
+    info!("Reloading destination policy");
+
+    let stats = service.reload_policy()?;
+
+    Ok(Json(stats))
+}
@@ -0,0 +1,677 @@
+use crate::domains::LinkKey;
+use crate::errors::{AppError, ServiceError};
+use crate::qr::QrOptions;
+use crate::router::AppState;
+use crate::services::{AccessDetails, ShortenResponse};
+use crate::targeting::{TargetingContext, select_rule};
+use crate::variants::choose_variant;
+use axum::{
+    Form,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, Uri, header},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::{info, warn};
+
+/// Form body posted to unlock a password-protected link
+#[derive(Debug, Deserialize)]
+pub struct UnlockForm {
+    pub password: String,
+}
+
+/// Redirect to original URL by short code
+///
+/// GET /{short_code}
+/// GET /{short_code}.qr (QR code of the short URL)
+///
+/// The short domain of the code is taken from the `Host` header.
+pub async fn redirect_to_url(
+    State(state): State<AppState>,
+    Path(short_code): Path<String>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    // Codes cannot contain a dot (checked on config and creation), so the suffix cannot clash
+    if let Some(code) = short_code.strip_suffix(".qr") {
+        let key = visited_key(&state, code, &uri, &headers);
+        return public_qr(&state, key, &uri).await;
+    }
+
+    let key = visited_key(&state, &short_code, &uri, &headers);
+    redirect(&state, &key, None, &uri, &headers).await
+}
+
+/// Redirect to original URL, appending the path after the short code
+///
+/// GET /{short_code}/{*path}
+pub async fn redirect_with_path(
+    State(state): State<AppState>,
+    Path((short_code, path)): Path<(String, String)>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    // Take the suffix from the raw URI so its percent-encoding is kept
+    let suffix = uri
+        .path()
+        .strip_prefix(&format!("/{}/", short_code))
+        .map(str::to_string)
+        .unwrap_or(path);
+
+    let key = visited_key(&state, &short_code, &uri, &headers);
+    redirect(&state, &key, Some(&suffix), &uri, &headers).await
+}
+
+/// Key of a visited short code on the short domain named by the `Host` header
+fn visited_key(state: &AppState, short_code: &str, uri: &Uri, headers: &HeaderMap) -> LinkKey {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .or_else(|| uri.host())
+        .unwrap_or_default();
+
+    LinkKey::new(state.shorten_service.domain_for_host(host), short_code)
+}
+
+/// Resolve a visit and redirect it, forwarding the path suffix and query when enabled
+async fn redirect(
+    state: &AppState,
+    key: &LinkKey,
+    suffix: Option<&str>,
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> Result<Response, AppError> {
+    let short_code = key.code.as_str();
+    info!("Redirecting short code: {}", key);
+
+    let unlock_token = read_cookie(headers, &unlock_cookie_name(short_code));
+
+    // Get the short URL info (rejects disabled, expired and locked links)
+    let shorten_response = match state
+        .shorten_service
+        .resolve_redirect(key.clone(), unlock_token.as_deref())
+        .await
+    {
+        Ok(response) => response,
+        Err(ServiceError::PasswordRequired(_)) => {
+            return Ok(password_form(short_code, None, StatusCode::OK));
+        }
+        Err(e) => return unavailable_response(state, key, e).await,
+    };
+
+    let forward_path = shorten_response
+        .forward_path
+        .unwrap_or(state.config.shortener.forward_path);
+    if suffix.is_some() && !forward_path {
+        return Err(AppError::NotFound(format!(
+            "URL with code '{}' does not forward paths",
+            short_code
+        )));
+    }
+    let forward_query = shorten_response
+        .forward_query
+        .unwrap_or(state.config.shortener.forward_query);
+
+    // Redirect to the matching targeted destination or the original URL
+    let (destination, details) = choose_destination(state, &shorten_response, headers).await;
+    let destination = forward_to(
+        &destination,
+        suffix,
+        if forward_query { uri.query() } else { None },
+    );
+    let variant = details.variant.clone();
+
+    record_visit(state, &shorten_response, short_code, headers, details);
+
+    let mut response = redirect_response(state, &shorten_response, &destination)?;
+    apply_variant_headers(&shorten_response, variant.as_deref(), &mut response);
+
+    Ok(response)
+}
+
+/// Render the public QR code of a short code, options taken from the query string
+async fn public_qr(state: &AppState, key: LinkKey, uri: &Uri) -> Result<Response, AppError> {
+    info!("Rendering public QR code for short URL: {}", key);
+
+    let Query(options) =
+        Query::<QrOptions>::try_from_uri(uri).map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let image = state.shorten_service.render_qr(key, &options).await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, options.format.content_type())],
+        image,
+    )
+        .into_response())
+}
+
+/// Unlock a password-protected short URL and redirect to it
+///
+/// POST /{short_code}
+pub async fn unlock_url(
+    State(state): State<AppState>,
+    Path(short_code): Path<String>,
+    uri: Uri,
+    headers: HeaderMap,
+    Form(form): Form<UnlockForm>,
+) -> Result<Response, AppError> {
+    let key = visited_key(&state, &short_code, &uri, &headers);
+    info!("Unlock attempt for short code: {}", key);
+
+    let token = match state
+        .shorten_service
+        .unlock(key.clone(), &form.password)
+        .await
+    {
+        Ok(token) => token,
+        Err(ServiceError::PasswordRequired(_)) => {
+            return Ok(password_form(
+                &short_code,
+                Some("Incorrect password"),
+                StatusCode::UNAUTHORIZED,
+            ));
+        }
+        Err(ServiceError::TooManyAttempts(_)) => {
+            return Ok(password_form(
+                &short_code,
+                Some("Too many attempts, please try again later"),
+                StatusCode::TOO_MANY_REQUESTS,
+            ));
+        }
+        Err(e) => return unavailable_response(&state, &key, e).await,
+    };
+
+    let shorten_response = match state
+        .shorten_service
+        .resolve_redirect(key.clone(), Some(&token))
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return unavailable_response(&state, &key, e).await,
+    };
+
+    let (destination, details) = choose_destination(&state, &shorten_response, &headers).await;
+    let variant = details.variant.clone();
+
+    record_visit(&state, &shorten_response, &short_code, &headers, details);
+
+    // 303 makes the browser follow up with a GET instead of replaying the POST
+    let mut response = Redirect::to(&destination).into_response();
+    let cookie = unlock_cookie(&state, &shorten_response, &token);
+    if let Ok(value) = HeaderValue::from_str(&cookie) {
+        response.headers_mut().insert(header::SET_COOKIE, value);
+    }
+    apply_variant_headers(&shorten_response, variant.as_deref(), &mut response);
+
+    Ok(response)
+}
+
+/// Pick the destination of a visit: the first matching targeting rule, else an A/B
+/// variant, else `original_url`
+///
+/// GeoIP is only consulted when a rule has a location condition; the result is
+/// handed on to the history record so the IP is not looked up twice.
+async fn choose_destination(
+    state: &AppState,
+    url: &ShortenResponse,
+    headers: &HeaderMap,
+) -> (String, AccessDetails) {
+    let mut details = AccessDetails::default();
+    if url.targeting_rules.is_empty() {
+        let destination = variant_destination(url, headers, &mut details);
+        return (destination, details);
+    }
+
+    let mut context = TargetingContext::default();
+    if let Some(ua) = headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+    {
+        let info = state.history_service.parse_user_agent(ua);
+        context.device_type = info.device_type;
+        context.os = info.os;
+    }
+
+    if url.targeting_rules.iter().any(|rule| rule.needs_location()) {
+        let geo = state.history_service.locate(client_ip(headers)).await;
+        context.set_location(&geo);
+        details.geo = Some(geo);
+    }
+
+    match select_rule(&url.targeting_rules, &context) {
+        Some((index, rule)) => {
+            details.targeting_rule = Some(index as i32);
+            (rule.url.clone(), details)
+        }
+        None => {
+            let destination = variant_destination(url, headers, &mut details);
+            (destination, details)
+        }
+    }
+}
+
+/// Assign the visitor to an A/B variant, or fall back to `original_url` without variants
+fn variant_destination(
+    url: &ShortenResponse,
+    headers: &HeaderMap,
+    details: &mut AccessDetails,
+) -> String {
+    let sticky = if url.sticky_variants {
+        read_cookie(headers, &variant_cookie_name(&url.short_code))
+    } else {
+        None
+    };
+
+    match choose_variant(&url.variants, sticky.as_deref()) {
+        Some(variant) => {
+            details.variant = Some(variant.name.clone());
+            variant.url.clone()
+        }
+        None => url.original_url.clone(),
+    }
+}
+
+/// Keep A/B redirects out of browser caches and pin sticky visitors to their variant
+fn apply_variant_headers(url: &ShortenResponse, variant: Option<&str>, response: &mut Response) {
+    if url.variants.is_empty() {
+        return;
+    }
+
+    // A cached 301/308 would send the browser to the same variant forever
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+    if url.sticky_variants
+        && let Some(variant) = variant
+        && let Ok(value) = HeaderValue::from_str(&link_cookie(
+            url,
+            &variant_cookie_name(&url.short_code),
+            variant,
+            VARIANT_COOKIE_MAX_AGE,
+        ))
+    {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+}
+
+/// Append a path suffix and/or an incoming query string to a destination URL
+///
+/// The suffix is joined onto the destination's path. Incoming query parameters
+/// replace destination parameters of the same name; the others are kept.
+fn forward_to(destination: &str, suffix: Option<&str>, query: Option<&str>) -> String {
+    if suffix.is_none() && query.is_none() {
+        return destination.to_string();
+    }
+
+    let (rest, fragment) = match destination.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (destination, None),
+    };
+    let (base, existing) = match rest.split_once('?') {
+        Some((base, existing)) => (base, existing),
+        None => (rest, ""),
+    };
+
+    let mut url = base.to_string();
+    if let Some(suffix) = suffix.map(|s| s.trim_start_matches('/'))
+        && !suffix.is_empty()
+    {
+        url = format!("{}/{}", url.trim_end_matches('/'), suffix);
+    }
+
+    let param_name = |pair: &str| pair.split('=').next().unwrap_or_default().to_string();
+    let incoming: Vec<&str> = query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .collect();
+    let mut pairs: Vec<&str> = existing
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            !incoming
+                .iter()
+                .any(|other| param_name(other) == param_name(pair))
+        })
+        .collect();
+    pairs.extend(incoming);
+
+    if !pairs.is_empty() {
+        url.push('?');
+        url.push_str(&pairs.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+
+    url
+}
+
+/// Build the redirect using the link's redirect type or the server default
+fn redirect_response(
+    state: &AppState,
+    url: &ShortenResponse,
+    destination: &str,
+) -> Result<Response, AppError> {
+    let code = url
+        .redirect_type
+        .unwrap_or(state.config.shortener.redirect_type);
+    let status = StatusCode::from_u16(code as u16).unwrap_or(StatusCode::PERMANENT_REDIRECT);
+
+    let location = HeaderValue::try_from(destination)
+        .map_err(|e| AppError::Internal(format!("Invalid redirect location: {}", e)))?;
+
+    Ok((status, [(header::LOCATION, location)]).into_response())
+}
+
+/// Turn an unavailable link into the configured fallback redirect, page or error
+async fn unavailable_response(
+    state: &AppState,
+    key: &LinkKey,
+    err: ServiceError,
+) -> Result<Response, AppError> {
+    match err {
+        ServiceError::Expired(_) | ServiceError::ClickLimitReached(_) => {
+            // Send visitors to the configured fallback, or answer 410 Gone
+            match &state.config.shortener.expired_redirect_url {
+                Some(fallback) => {
+                    info!("Short code {} is no longer available: {}", key, err);
+                    Ok(Redirect::temporary(fallback).into_response())
+                }
+                None => Err(err.into()),
+            }
+        }
+        ServiceError::NotYetActive(_) => Ok(not_active_page(state, key).await),
+        _ => Err(err.into()),
+    }
+}
+
+/// Extract the client IP address from headers
+///
+/// Try to get real IP from common proxy headers
+/// Priority: CF-Connecting-IP (Cloudflare) > X-Forwarded-For > X-Real-IP
+fn client_ip(headers: &HeaderMap) -> &str {
+    headers
+        .get("cf-connecting-ip")
+        .and_then(|h| h.to_str().ok())
+        .or_else(|| {
+            headers
+                .get("x-forwarded-for")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|s| s.split(',').next().map(|ip| ip.trim()))
+        })
+        .or_else(|| headers.get("x-real-ip").and_then(|h| h.to_str().ok()))
+        .unwrap_or("unknown")
+}
+
+/// Record access history asynchronously (don't block the redirect)
+fn record_visit(
+    state: &AppState,
+    url: &ShortenResponse,
+    short_code: &str,
+    headers: &HeaderMap,
+    details: AccessDetails,
+) {
+    // Extract client information for history logging
+    let user_agent = headers.get("user-agent").and_then(|h| h.to_str().ok());
+
+    let referer = headers.get("referer").and_then(|h| h.to_str().ok());
+
+    let ip_address = client_ip(headers);
+
+    let history_service = state.history_service.clone();
+    let url_id = url.id;
+    let code = short_code.to_string();
+    let ip = ip_address.to_string();
+    let ua = user_agent.map(|s| s.to_string());
+    let ref_url = referer.map(|s| s.to_string());
+
+    tokio::spawn(async move {
+        if let Err(e) = history_service
+            .record_access_with(
+                url_id,
+                &code,
+                &ip,
+                ua.as_deref(),
+                ref_url.as_deref(),
+                details,
+            )
+            .await
+        {
+            tracing::error!("Failed to record access history: {:?}", e);
+        }
+    });
+
+    info!(
+        "Redirecting: short_code={}, ip={}, user_agent={:?}, referer={:?}",
+        short_code, ip_address, user_agent, referer
+    );
+}
+
+/// Name of the cookie holding the unlock token of a short code
+fn unlock_cookie_name(short_code: &str) -> String {
+    format!("shortener_unlock_{}", short_code)
+}
+
+/// Build the Set-Cookie value for an unlocked short code
+fn unlock_cookie(state: &AppState, url: &ShortenResponse, token: &str) -> String {
+    link_cookie(
+        url,
+        &unlock_cookie_name(&url.short_code),
+        token,
+        state.config.shortener.unlock_ttl,
+    )
+}
+
+/// Name of the cookie holding the sticky A/B variant of a short code
+fn variant_cookie_name(short_code: &str) -> String {
+    format!("shortener_variant_{}", short_code)
+}
+
+/// How long a visitor stays on their sticky variant (30 days)
+const VARIANT_COOKIE_MAX_AGE: u64 = 30 * 24 * 60 * 60;
+
+/// Build a Set-Cookie value scoped to the path of a short code
+///
+/// Secure when the link's own domain is served over https.
+fn link_cookie(url: &ShortenResponse, name: &str, value: &str, max_age: u64) -> String {
+    let mut cookie = format!(
+        "{}={}; Path=/{}; Max-Age={}; HttpOnly; SameSite=Lax",
+        name, value, url.short_code, max_age
+    );
+    if url.short_url.starts_with("https://") {
+        cookie.push_str("; Secure");
+    }
+    cookie
+}
+
+/// Read a cookie value from the request headers
+fn read_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Render the password form of a protected short code
+fn password_form(short_code: &str, error: Option<&str>, status: StatusCode) -> Response {
+    let code = html_escape(short_code);
+    let error = error
+        .map(|msg| format!("<p class=\"error\">{}</p>", html_escape(msg)))
+        .unwrap_or_default();
+
+    let body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>Password required</title>
+<style>
+body {{ font-family: sans-serif; display: flex; justify-content: center; margin-top: 15vh; }}
+form {{ display: flex; flex-direction: column; gap: 0.75rem; width: 18rem; }}
+.error {{ color: #c00; margin: 0; }}
+</style>
+</head>
+<body>
+<form method="post" action="/{code}">
+<h1>Password required</h1>
+{error}
+<input type="password" name="password" placeholder="Password" autofocus required>
+<button type="submit">Continue</button>
+</form>
+</body>
+</html>
+"#
+    );
+
+    let mut response = (status, Html(body)).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+/// Render the "not yet available" page of a scheduled short code
+///
+/// Uses `shortener.not_active_page` when configured; `{{code}}` and
+/// `{{active_from}}` in the page are replaced with the link's values.
+async fn not_active_page(state: &AppState, key: &LinkKey) -> Response {
+    let active_from = state
+        .shorten_service
+        .get_shorten(key.clone())
+        .await
+        .ok()
+        .and_then(|url| url.active_from)
+        .unwrap_or_default();
+
+    let template = match &state.config.shortener.not_active_page {
+        Some(path) => page_template(path).await,
+        None => Arc::from(DEFAULT_NOT_ACTIVE_PAGE),
+    };
+
+    let body = template
+        .replace("{{code}}", &html_escape(&key.code))
+        .replace("{{active_from}}", &html_escape(&active_from));
+
+    let mut response = (StatusCode::FORBIDDEN, Html(body)).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+/// Content of a configured page, read from disk on first use
+///
+/// Falls back to the built-in page (and tries again next time) when the
+/// file cannot be read.
+async fn page_template(path: &str) -> Arc<str> {
+    static PAGES: OnceLock<Mutex<HashMap<String, Arc<str>>>> = OnceLock::new();
+    let pages = PAGES.get_or_init(Default::default);
+
+    if let Some(page) = pages.lock().unwrap().get(path) {
+        return page.clone();
+    }
+
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => {
+            let page: Arc<str> = Arc::from(content);
+            pages.lock().unwrap().insert(path.to_string(), page.clone());
+            page
+        }
+        Err(e) => {
+            warn!("Failed to read not_active_page {}: {}", path, e);
+            Arc::from(DEFAULT_NOT_ACTIVE_PAGE)
+        }
+    }
+}
+
+/// Built-in page shown before a link's activation window starts
+const DEFAULT_NOT_ACTIVE_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>Not yet available</title>
+<style>
+body { font-family: sans-serif; display: flex; justify-content: center; margin-top: 15vh; text-align: center; }
+</style>
+</head>
+<body>
+<main>
+<h1>Not yet available</h1>
+<p>This link goes live at <time datetime="{{active_from}}">{{active_from}}</time>.</p>
+</main>
+</body>
+</html>
+"#;
+
+/// Escape text for safe inclusion in HTML
+fn html_escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("a=1; shortener_unlock_abc=token; b=2"),
+        );
+
+        assert_eq!(
+            read_cookie(&headers, "shortener_unlock_abc"),
+            Some("token".to_string())
+        );
+        assert_eq!(read_cookie(&headers, "missing"), None);
+    }
+
+    #[test]
+    fn test_forward_to() {
+        let dest = "https://example.com/landing?ref=short#top";
+
+        assert_eq!(forward_to(dest, None, None), dest);
+        assert_eq!(
+            forward_to(dest, None, Some("utm_source=mail&ref=news")),
+            "https://example.com/landing?utm_source=mail&ref=news#top"
+        );
+        assert_eq!(
+            forward_to(dest, Some("docs/a%20b"), None),
+            "https://example.com/landing/docs/a%20b?ref=short#top"
+        );
+        assert_eq!(
+            forward_to("https://example.com/", Some("extra/path"), Some("x=1")),
+            "https://example.com/extra/path?x=1"
+        );
+        assert_eq!(
+            forward_to("https://example.com", Some(""), Some("")),
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(
+            html_escape("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
@@ -1,30 +1,98 @@
-use crate::errors::AppError;
+use crate::domains::LinkKey;
+use crate::errors::{AppError, ErrorResponse, ServiceError};
+use crate::export::{self, ExportOptions};
+use crate::handlers::account::Actor;
+use crate::import::{ImportFormat, ImportOptions, ImportReport};
+use crate::qr::QrOptions;
 use crate::repositories::url_repository::ListParams;
 use crate::services::{
-    CreateShortenRequest, PagedResponse, ShortenResponse, ShortenService, UpdateShortenRequest,
+    BatchTarget, CreateShortenRequest, MAX_BATCH_ITEMS, PagedResponse, RevisionResponse,
+    ShortenResponse, ShortenService, UpdateShortenRequest,
 };
+use crate::targeting::TargetingRule;
 use axum::{
     Json,
+    body::Body,
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    response::Redirect,
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::info;
 
+/// Query parameter selecting the short domain of a link
+///
+/// Links on the default domain need no parameter.
+#[derive(Debug, Default, Deserialize)]
+pub struct DomainQuery {
+    pub domain: Option<String>,
+}
+
+impl DomainQuery {
+    /// Key of a short code on the selected domain
+    pub fn key(self, short_code: String) -> LinkKey {
+        LinkKey::new(self.domain.unwrap_or_default(), short_code)
+    }
+}
+
 /// Create a new short URL
 ///
 /// POST /api/shortens
+///
+/// Answers 200 instead of 201 when an existing link was reused.
 pub async fn create_shorten(
     State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
     Json(req): Json<CreateShortenRequest>,
 ) -> Result<(StatusCode, Json<ShortenResponse>), AppError> {
     info!("Creating short URL for: {}", req.original_url);
 
-    let response = service.create_shorten(req).await?;
+    let (response, reused) = service.create_or_reuse_shorten(req, &actor).await?;
+    let status = if reused {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+
+    Ok((status, Json(response)))
+}
+
+/// Largest import file accepted, in bytes
+pub const MAX_IMPORT_SIZE: usize = 64 * 1024 * 1024;
+
+/// Import short URLs from a CSV or NDJSON file
+///
+/// POST /api/shortens/import
+///
+/// The format comes from `?format=` or the `Content-Type` header. Answers
+/// 422 with the report when the import was aborted.
+pub async fn import_shortens(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Query(mut options): Query<ImportOptions>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<(StatusCode, Json<ImportReport>), AppError> {
+    if options.format.is_none() {
+        options.format = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(ImportFormat::from_content_type);
+    }
+    info!(
+        "Importing short URLs: format={:?}, on_conflict={:?}, dry_run={}",
+        options.format, options.on_conflict, options.dry_run
+    );
+
+    let report = service.import_shortens(&body, options, &actor).await?;
+    let status = if report.committed || report.dry_run {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
 
-    Ok((StatusCode::CREATED, Json(response)))
+    Ok((status, Json(report)))
 }
 
 /// Get a short URL by short_code
@@ -33,14 +101,59 @@ pub async fn create_shorten(
 pub async fn get_shorten(
     State(service): State<Arc<ShortenService>>,
     Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
 ) -> Result<Json<ShortenResponse>, AppError> {
     info!("Getting short URL: {}", short_code);
 
-    let response = service.get_shorten(&short_code).await?;
+    let response = service.get_shorten(domain.key(short_code)).await?;
 
     Ok(Json(response))
 }
 
+/// Render the short URL as a QR code image
+///
+/// GET /api/shortens/{short_code}/qr
+pub async fn get_shorten_qr(
+    State(service): State<Arc<ShortenService>>,
+    Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
+    Query(options): Query<QrOptions>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Rendering QR code for short URL: {}", short_code);
+
+    let image = service.render_qr(domain.key(short_code), &options).await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, options.format.content_type())],
+        image,
+    ))
+}
+
+/// Export short URLs as CSV or NDJSON
+///
+/// GET /api/shortens/export
+///
+/// Takes the filters of the list endpoint and streams every matching link.
+pub async fn export_shortens(
+    State(service): State<Arc<ShortenService>>,
+    Query(params): Query<ListParams>,
+    Query(options): Query<ExportOptions>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Exporting short URLs: format={:?}", options.format);
+
+    let first = service.export_shortens(&params, 0).await?;
+    let chunks = export::stream(options.format, first, move |after_id| {
+        let service = service.clone();
+        let params = params.clone();
+        async move { service.export_shortens(&params, after_id).await }
+    });
+
+    Ok((
+        export::headers(options.format, "shortens"),
+        Body::from_stream(chunks),
+    ))
+}
+
 /// List short URLs with pagination
 ///
 /// GET /api/shortens
@@ -63,30 +176,106 @@ pub async fn list_shortens(
 /// PUT /api/shortens/{short_code}
 pub async fn update_shorten(
     State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
     Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
     Json(req): Json<UpdateShortenRequest>,
 ) -> Result<Json<ShortenResponse>, AppError> {
     info!("Updating short URL: {}", short_code);
 
-    let response = service.update_shorten(&short_code, req).await?;
+    let response = service
+        .update_shorten(domain.key(short_code), req, &actor)
+        .await?;
 
     Ok(Json(response))
 }
 
-/// Delete a short URL
+/// Move a short URL to the trash
 ///
 /// DELETE /api/shortens/{short_code}
 pub async fn delete_shorten(
     State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
     Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
 ) -> Result<StatusCode, AppError> {
     info!("Deleting short URL: {}", short_code);
 
-    service.delete_shorten(&short_code).await?;
+    service
+        .delete_shorten(domain.key(short_code), &actor)
+        .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// List the revisions of a short URL, newest first
+///
+/// GET /api/shortens/{short_code}/revisions
+pub async fn list_revisions(
+    State(service): State<Arc<ShortenService>>,
+    Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
+) -> Result<Json<Vec<RevisionResponse>>, AppError> {
+    info!("Listing revisions: {}", short_code);
+
+    let revisions = service.list_revisions(domain.key(short_code)).await?;
+
+    Ok(Json(revisions))
+}
+
+/// Restore a short URL to the state recorded by a revision
+///
+/// POST /api/shortens/{short_code}/revisions/{id}/restore
+pub async fn restore_revision(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Path((short_code, id)): Path<(String, i64)>,
+    Query(domain): Query<DomainQuery>,
+) -> Result<Json<ShortenResponse>, AppError> {
+    info!("Restoring revision {}: {}", id, short_code);
+
+    let response = service
+        .restore_revision(domain.key(short_code), id, &actor)
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// List the short URLs in the trash with pagination
+///
+/// GET /api/trash
+pub async fn list_trash(
+    State(service): State<Arc<ShortenService>>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<PagedResponse<ShortenResponse>>, AppError> {
+    info!(
+        "Listing trash: page={}, per_page={}",
+        params.page, params.page_size
+    );
+
+    let response = service.list_trash(params).await?;
+
+    Ok(Json(response))
+}
+
+/// Take a short URL out of the trash
+///
+/// POST /api/trash/{short_code}/restore
+pub async fn restore_deleted(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
+) -> Result<Json<ShortenResponse>, AppError> {
+    info!("Restoring short URL from trash: {}", short_code);
+
+    let response = service
+        .restore_deleted(domain.key(short_code), &actor)
+        .await?;
+
+    Ok(Json(response))
+}
+
 /// Request body for batch delete
 #[derive(Debug, Deserialize)]
 pub struct BatchDeleteShortensRequest {
@@ -98,6 +287,7 @@ pub struct BatchDeleteShortensRequest {
 /// POST /api/shortens/batch-delete
 pub async fn delete_batch(
     State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
     Json(req): Json<BatchDeleteShortensRequest>,
 ) -> Result<StatusCode, AppError> {
     if req.ids.is_empty() {
@@ -106,81 +296,261 @@ pub async fn delete_batch(
 
     info!("Batch deleting {} short URLs", req.ids.len());
 
-    service.delete_batch(req.ids).await?;
+    service.delete_batch(req.ids, &actor).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Redirect to original URL by short code
+/// Request body for batch create
+#[derive(Debug, Deserialize)]
+pub struct BatchCreateShortensRequest {
+    pub items: Vec<CreateShortenRequest>,
+}
+
+/// Request body for batch update
 ///
-/// GET /{short_code}
-pub async fn redirect_to_url(
-    State(state): State<crate::router::AppState>,
-    Path(short_code): Path<String>,
-    headers: HeaderMap,
-) -> Result<Redirect, AppError> {
-    info!("Redirecting short code: {}", short_code);
+/// The links are chosen by `ids` or by `filter`; the changes are applied to
+/// each of them.
+#[derive(Debug, Deserialize)]
+pub struct BatchUpdateShortensRequest {
+    pub ids: Option<Vec<i64>>,
+    /// Filters of the list endpoint, e.g. `{"tag": "article-42"}`
+    pub filter: Option<ListParams>,
+    pub status: Option<i32>,
+    pub description: Option<String>,
+    /// Replaces the tags; an empty list removes them
+    pub tags: Option<Vec<String>>,
+}
+
+/// Result of one item of a batch request
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    /// Position of the item in the request, or of the link among the matched ones
+    pub index: usize,
+    /// ID of the link, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<ShortenResponse>,
+    /// Error the item would have got as a single request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
 
-    // Get the short URL info
-    let shorten_response = state.shorten_service.get_shorten(&short_code).await?;
+/// Response of a batch request
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub items: Vec<BatchItemResult>,
+}
 
-    // Check if the URL is disabled
-    if shorten_response.status != 0 {
-        return Err(AppError::NotFound("Short URL is disabled".to_string()));
+impl BatchResponse {
+    /// Build a response from the link ID (when known) and result of every item
+    fn new(
+        results: impl IntoIterator<Item = (Option<i64>, Result<ShortenResponse, ServiceError>)>,
+    ) -> Self {
+        let items: Vec<BatchItemResult> = results
+            .into_iter()
+            .enumerate()
+            .map(|(index, (id, result))| match result {
+                Ok(data) => BatchItemResult {
+                    index,
+                    id: Some(data.id),
+                    success: true,
+                    data: Some(data),
+                    error: None,
+                },
+                Err(e) => BatchItemResult {
+                    index,
+                    id,
+                    success: false,
+                    data: None,
+                    error: Some(AppError::from(e).response_parts().1),
+                },
+            })
+            .collect();
+        let succeeded = items.iter().filter(|item| item.success).count();
+
+        Self {
+            total: items.len(),
+            succeeded,
+            failed: items.len() - succeeded,
+            items,
+        }
     }
+}
 
-    // Extract client information for history logging
-    let user_agent = headers.get("user-agent").and_then(|h| h.to_str().ok());
-
-    let referer = headers.get("referer").and_then(|h| h.to_str().ok());
-
-    // Extract IP address from headers
-    // Try to get real IP from common proxy headers
-    // Priority: CF-Connecting-IP (Cloudflare) > X-Forwarded-For > X-Real-IP
-    let ip_address = headers
-        .get("cf-connecting-ip")
-        .and_then(|h| h.to_str().ok())
-        .or_else(|| {
-            headers
-                .get("x-forwarded-for")
-                .and_then(|h| h.to_str().ok())
-                .and_then(|s| s.split(',').next().map(|ip| ip.trim()))
-        })
-        .or_else(|| headers.get("x-real-ip").and_then(|h| h.to_str().ok()))
-        .unwrap_or("unknown");
-
-    // Record access history asynchronously (don't block the redirect)
-    let history_service = state.history_service.clone();
-    let url_id = shorten_response.id;
-    let code = short_code.clone();
-    let ip = ip_address.to_string();
-    let ua = user_agent.map(|s| s.to_string());
-    let ref_url = referer.map(|s| s.to_string());
-
-    tokio::spawn(async move {
-        if let Err(e) = history_service
-            .record_access(url_id, &code, &ip, ua.as_deref(), ref_url.as_deref())
-            .await
-        {
-            tracing::error!("Failed to record access history: {:?}", e);
+/// Create multiple short URLs
+///
+/// POST /api/shortens/batch
+///
+/// Items are created one by one; failed items are reported in the response
+/// without affecting the others.
+pub async fn create_batch(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Json(req): Json<BatchCreateShortensRequest>,
+) -> Result<Json<BatchResponse>, AppError> {
+    if req.items.is_empty() {
+        return Err(AppError::BadRequest("items cannot be empty".to_string()));
+    }
+    if req.items.len() > MAX_BATCH_ITEMS {
+        return Err(AppError::BadRequest(format!(
+            "at most {} items are allowed",
+            MAX_BATCH_ITEMS
+        )));
+    }
+
+    info!("Batch creating {} short URLs", req.items.len());
+
+    let results = service.create_batch(req.items, &actor).await;
+
+    Ok(Json(BatchResponse::new(
+        results.into_iter().map(|result| (None, result)),
+    )))
+}
+
+/// Update multiple short URLs
+///
+/// POST /api/shortens/batch-update
+///
+/// Sets the status, description or tags of the links chosen by ID or by
+/// filter. Failed links are reported in the response without affecting the
+/// others.
+pub async fn update_batch(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Json(req): Json<BatchUpdateShortensRequest>,
+) -> Result<Json<BatchResponse>, AppError> {
+    let target = match (req.ids, req.filter) {
+        (Some(_), Some(_)) => {
+            return Err(AppError::BadRequest(
+                "ids and filter cannot be combined".to_string(),
+            ));
         }
-    });
+        (Some(ids), None) if ids.is_empty() => {
+            return Err(AppError::BadRequest("ids cannot be empty".to_string()));
+        }
+        (Some(ids), None) if ids.len() > MAX_BATCH_ITEMS => {
+            return Err(AppError::BadRequest(format!(
+                "at most {} ids are allowed",
+                MAX_BATCH_ITEMS
+            )));
+        }
+        (Some(ids), None) => BatchTarget::Ids(ids),
+        (None, Some(filter)) => BatchTarget::Filter(filter),
+        (None, None) => {
+            return Err(AppError::BadRequest(
+                "ids or filter is required".to_string(),
+            ));
+        }
+    };
 
-    info!(
-        "Redirecting: short_code={}, ip={}, user_agent={:?}, referer={:?}",
-        short_code, ip_address, user_agent, referer
-    );
+    if req.status.is_none() && req.description.is_none() && req.tags.is_none() {
+        return Err(AppError::BadRequest(
+            "status, description or tags is required".to_string(),
+        ));
+    }
+    match &target {
+        BatchTarget::Ids(ids) => info!("Batch updating {} short URLs", ids.len()),
+        BatchTarget::Filter(filter) => info!("Batch updating short URLs matching {:?}", filter),
+    }
 
-    // Redirect to the original URL
-    Ok(Redirect::permanent(&shorten_response.original_url))
+    let changes = UpdateShortenRequest {
+        status: req.status,
+        description: req.description,
+        tags: req.tags,
+        ..Default::default()
+    };
+    let results = service.update_batch(target, changes, &actor).await?;
+
+    Ok(Json(BatchResponse::new(
+        results.into_iter().map(|(id, result)| (Some(id), result)),
+    )))
+}
+
+/// List the targeting rules of a short URL
+///
+/// GET /api/shortens/{short_code}/rules
+pub async fn list_targeting_rules(
+    State(service): State<Arc<ShortenService>>,
+    Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
+) -> Result<Json<Vec<TargetingRule>>, AppError> {
+    info!("Listing targeting rules: {}", short_code);
+
+    let rules = service.list_targeting_rules(domain.key(short_code)).await?;
+
+    Ok(Json(rules))
+}
+
+/// Append a targeting rule to a short URL
+///
+/// POST /api/shortens/{short_code}/rules
+pub async fn add_targeting_rule(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Path(short_code): Path<String>,
+    Query(domain): Query<DomainQuery>,
+    Json(rule): Json<TargetingRule>,
+) -> Result<(StatusCode, Json<Vec<TargetingRule>>), AppError> {
+    info!("Adding targeting rule: {}", short_code);
+
+    let rules = service
+        .add_targeting_rule(domain.key(short_code), rule, &actor)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(rules)))
+}
+
+/// Replace a targeting rule of a short URL
+///
+/// PUT /api/shortens/{short_code}/rules/{index}
+pub async fn update_targeting_rule(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Path((short_code, index)): Path<(String, usize)>,
+    Query(domain): Query<DomainQuery>,
+    Json(rule): Json<TargetingRule>,
+) -> Result<Json<Vec<TargetingRule>>, AppError> {
+    info!("Updating targeting rule {}: {}", index, short_code);
+
+    let rules = service
+        .update_targeting_rule(domain.key(short_code), index, rule, &actor)
+        .await?;
+
+    Ok(Json(rules))
+}
+
+/// Delete a targeting rule of a short URL
+///
+/// DELETE /api/shortens/{short_code}/rules/{index}
+pub async fn delete_targeting_rule(
+    State(service): State<Arc<ShortenService>>,
+    Actor(actor): Actor,
+    Path((short_code, index)): Path<(String, usize)>,
+    Query(domain): Query<DomainQuery>,
+) -> Result<Json<Vec<TargetingRule>>, AppError> {
+    info!("Deleting targeting rule {}: {}", index, short_code);
+
+    let rules = service
+        .delete_targeting_rule(domain.key(short_code), index, &actor)
+        .await?;
+
+    Ok(Json(rules))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cache::NullCache;
-    use crate::config::{Config, DatabaseConfig, DatabaseType, SqliteConfig};
+    use crate::config::Config;
     use crate::db::DbFactory;
+    use crate::repositories::revision_repository::RevisionRepositoryImpl;
+    use crate::repositories::tag_repository::TagRepositoryImpl;
     use crate::repositories::url_repository::UrlRepositoryImpl;
     use axum::Router;
     use axum::body::Body;
@@ -189,55 +559,20 @@ mod tests {
     use tower::ServiceExt;
 
     async fn setup_test_app() -> Router {
-        let config = Config {
-            server: crate::config::ServerConfig {
-                address: ":8080".to_string(),
-                trusted_platform: None,
-                site_url: "http://localhost:8080".to_string(),
-                api_key: "test-key".to_string(),
-            },
-            shortener: crate::config::ShortenerConfig {
-                code_length: 6,
-                code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-                    .to_string(),
-            },
-            admin: crate::config::AdminConfig {
-                username: "admin".to_string(),
-                password: "admin123".to_string(),
-            },
-            database: DatabaseConfig {
-                db_type: DatabaseType::Sqlite,
-                log_level: 0,
-                sqlite: Some(SqliteConfig {
-                    path: ":memory:".to_string(),
-                }),
-                postgres: None,
-                mysql: None,
-            },
-            cache: crate::config::CacheConfig {
-                enabled: false,
-                cache_type: crate::config::CacheType::Redis,
-                expire: 3600,
-                prefix: "shorten:".to_string(),
-                redis: None,
-                valkey: None,
-            },
-            geoip: crate::config::GeoIpConfig {
-                enabled: false,
-                geoip_type: crate::config::GeoIpType::Ip2region,
-                ip2region: None,
-            },
-            logging: crate::logging::LoggingConfig::default(),
-        };
+        let config = Config::for_tests();
 
         let db = DbFactory::create_connection(&config).await.unwrap();
         DbFactory::run_migrations(&db).await.unwrap();
 
-        let url_repo = Arc::new(UrlRepositoryImpl::new(db));
+        let url_repo = Arc::new(UrlRepositoryImpl::new(db.clone()));
+        let tag_repo = Arc::new(TagRepositoryImpl::new(db.clone()));
+        let revision_repo = Arc::new(RevisionRepositoryImpl::new(db));
         let cache = Arc::new(NullCache::new());
 
         let service = Arc::new(ShortenService::new(
             url_repo,
+            tag_repo,
+            revision_repo,
             cache,
             config.shortener.clone(),
             config.server.site_url.clone(),
@@ -256,6 +591,14 @@ mod tests {
                 "/api/shortens/{code}",
                 axum::routing::delete(delete_shorten),
             )
+            .route(
+                "/api/shortens/{code}/rules",
+                axum::routing::get(list_targeting_rules).post(add_targeting_rule),
+            )
+            .route(
+                "/api/shortens/{code}/rules/{index}",
+                axum::routing::put(update_targeting_rule).delete(delete_targeting_rule),
+            )
             .with_state(service)
     }
 
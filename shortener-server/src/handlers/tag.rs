@@ -0,0 +1,65 @@
+use crate::errors::AppError;
+use crate::services::{ShortenService, TagRequest, TagResponse};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::sync::Arc;
+use tracing::info;
+
+/// List all tags
+///
+/// GET /api/tags
+pub async fn list_tags(
+    State(service): State<Arc<ShortenService>>,
+) -> Result<Json<Vec<TagResponse>>, AppError> {
+    info!("Listing tags");
+
+    let tags = service.list_tags().await?;
+
+    Ok(Json(tags))
+}
+
+/// Create a tag
+///
+/// POST /api/tags
+pub async fn create_tag(
+    State(service): State<Arc<ShortenService>>,
+    Json(req): Json<TagRequest>,
+) -> Result<(StatusCode, Json<TagResponse>), AppError> {
+    info!("Creating tag: {}", req.name);
+
+    let tag = service.create_tag(&req.name).await?;
+
+    Ok((StatusCode::CREATED, Json(tag)))
+}
+
+/// Rename a tag
+///
+/// PUT /api/tags/{name}
+pub async fn rename_tag(
+    State(service): State<Arc<ShortenService>>,
+    Path(name): Path<String>,
+    Json(req): Json<TagRequest>,
+) -> Result<Json<TagResponse>, AppError> {
+    info!("Renaming tag: {} -> {}", name, req.name);
+
+    let tag = service.rename_tag(&name, &req.name).await?;
+
+    Ok(Json(tag))
+}
+
+/// Delete a tag
+///
+/// DELETE /api/tags/{name}
+pub async fn delete_tag(
+    State(service): State<Arc<ShortenService>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    info!("Deleting tag: {}", name);
+
+    service.delete_tag(&name).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,151 @@
+use crate::errors::AppError;
+use crate::handlers::account::{
+    API_KEY_ACTOR, ApiKeyCaller, LoginResponse, User, generate_token, revoke_tokens,
+};
+use crate::services::{
+    ChangePasswordRequest, CreateUserRequest, UpdateUserRequest, UserResponse, UserService,
+};
+use axum::{
+    Extension, Json,
+    extract::{FromRequestParts, Path, State},
+    http::{StatusCode, request::Parts},
+};
+use std::sync::Arc;
+use tracing::info;
+
+/// Caller allowed to manage users: the API key or an enabled administrator
+///
+/// Holds the actor name, like [`Actor`](crate::handlers::account::Actor).
+/// Requests carrying neither a [`User`] nor the [`ApiKeyCaller`] marker of
+/// the auth middleware are rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequireAdmin(pub String);
+
+impl FromRequestParts<Arc<UserService>> for RequireAdmin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        service: &Arc<UserService>,
+    ) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<User>() {
+            Some(user) if service.is_admin(&user.username).await? => {
+                Ok(RequireAdmin(user.username.clone()))
+            }
+            Some(_) => Err(AppError::Forbidden(
+                "Administrator role required".to_string(),
+            )),
+            None if parts.extensions.get::<ApiKeyCaller>().is_some() => {
+                Ok(RequireAdmin(API_KEY_ACTOR.to_string()))
+            }
+            None => Err(AppError::Unauthorized(
+                "Authentication required".to_string(),
+            )),
+        }
+    }
+}
+
+/// List all users
+///
+/// GET /api/users
+pub async fn list_users(
+    State(service): State<Arc<UserService>>,
+    RequireAdmin(actor): RequireAdmin,
+) -> Result<Json<Vec<UserResponse>>, AppError> {
+    info!("Listing users for {}", actor);
+
+    let users = service.list_users().await?;
+
+    Ok(Json(users))
+}
+
+/// Create a user
+///
+/// POST /api/users
+pub async fn create_user(
+    State(service): State<Arc<UserService>>,
+    RequireAdmin(actor): RequireAdmin,
+    Json(req): Json<CreateUserRequest>,
+) -> Result<(StatusCode, Json<UserResponse>), AppError> {
+    info!("Creating user {} by {}", req.username, actor);
+
+    let user = service.create_user(req).await?;
+
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+/// Get a user
+///
+/// GET /api/users/{username}
+pub async fn get_user(
+    State(service): State<Arc<UserService>>,
+    RequireAdmin(_): RequireAdmin,
+    Path(username): Path<String>,
+) -> Result<Json<UserResponse>, AppError> {
+    info!("Getting user: {}", username);
+
+    let user = service.get_user(&username).await?;
+
+    Ok(Json(user))
+}
+
+/// Update a user
+///
+/// PUT /api/users/{username}
+///
+/// Disabling the user or resetting the password ends their sessions.
+pub async fn update_user(
+    State(service): State<Arc<UserService>>,
+    RequireAdmin(actor): RequireAdmin,
+    Path(username): Path<String>,
+    Json(req): Json<UpdateUserRequest>,
+) -> Result<Json<UserResponse>, AppError> {
+    info!("Updating user {} by {}", username, actor);
+
+    let logout = req.disabled == Some(true) || req.password.is_some();
+    let user = service.update_user(&username, req, &actor).await?;
+    if logout {
+        revoke_tokens(&username);
+    }
+
+    Ok(Json(user))
+}
+
+/// Delete a user
+///
+/// DELETE /api/users/{username}
+pub async fn delete_user(
+    State(service): State<Arc<UserService>>,
+    RequireAdmin(actor): RequireAdmin,
+    Path(username): Path<String>,
+) -> Result<StatusCode, AppError> {
+    info!("Deleting user {} by {}", username, actor);
+
+    service.delete_user(&username, &actor).await?;
+    revoke_tokens(&username);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Change the password of the logged-in user
+///
+/// PUT /api/users/current/password
+///
+/// Ends every session of the user and returns a new token for this one.
+pub async fn change_password(
+    State(service): State<Arc<UserService>>,
+    Extension(user): Extension<User>,
+    Json(req): Json<ChangePasswordRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    info!("Changing password of user: {}", user.username);
+
+    service.change_password(&user.username, req).await?;
+    revoke_tokens(&user.username);
+    let token = generate_token(&user.username)?;
+
+    Ok(Json(LoginResponse {
+        token,
+        error_code: None,
+        error_message: None,
+    }))
+}
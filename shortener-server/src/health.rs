@@ -0,0 +1,469 @@
+use crate::config::ShortenerConfig;
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Client, Method, StatusCode, Url, redirect};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tokio::time::{Interval, MissedTickBehavior};
+
+/// Redirects followed before a destination counts as broken
+const MAX_REDIRECTS: usize = 10;
+
+/// Longest error message stored on a link, in characters
+const MAX_ERROR_LEN: usize = 500;
+
+/// Result of checking one destination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheck {
+    pub checked_at: DateTime<Utc>,
+    /// HTTP status of the final response, after redirects
+    pub status: Option<i32>,
+    /// Why the destination counts as broken (`None` when it is fine)
+    pub error: Option<String>,
+}
+
+impl HealthCheck {
+    /// Whether the destination answered with a non-error status
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// A destination or redirect target that is not a public address
+#[derive(Debug, Error)]
+#[error("Address {0} is not public")]
+struct PrivateAddress(IpAddr);
+
+/// Checks destinations with HEAD requests, falling back to GET
+///
+/// Servers often reject or mishandle HEAD, so any HEAD failure is retried
+/// with GET; only the response headers of the GET are read. Statuses below
+/// 400 (after following redirects) count as healthy.
+///
+/// Unless private targets are allowed, hosts are only contacted on public
+/// addresses: IP literals are checked before the first request and on every
+/// redirect, host names when they are resolved.
+pub struct HealthChecker {
+    client: Client,
+    concurrency: usize,
+    rate_limit: u32,
+    allow_private: bool,
+}
+
+impl HealthChecker {
+    /// Create a checker
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Timeout of a single request
+    /// * `concurrency` - Requests in flight at the same time
+    /// * `rate_limit` - Requests started per second (0 for no limit)
+    /// * `allow_private` - Also check loopback, private and link-local addresses
+    pub fn new(
+        timeout: Duration,
+        concurrency: usize,
+        rate_limit: u32,
+        allow_private: bool,
+    ) -> Result<Self, String> {
+        let mut builder = Client::builder().timeout(timeout).user_agent(concat!(
+            "shortener-health-check/",
+            env!("CARGO_PKG_VERSION")
+        ));
+        builder = if allow_private {
+            builder.redirect(redirect::Policy::limited(MAX_REDIRECTS))
+        } else {
+            // A proxy would resolve the host names itself
+            builder
+                .no_proxy()
+                .dns_resolver(Arc::new(PublicResolver))
+                .redirect(redirect::Policy::custom(|attempt| {
+                    if attempt.previous().len() > MAX_REDIRECTS {
+                        attempt.error("too many redirects")
+                    } else if let Err(e) = check_public(attempt.url()) {
+                        attempt.error(e)
+                    } else {
+                        attempt.follow()
+                    }
+                }))
+        };
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            concurrency: concurrency.max(1),
+            rate_limit,
+            allow_private,
+        })
+    }
+
+    /// Create a checker from the `health_check_*` settings
+    pub fn from_config(config: &ShortenerConfig) -> Result<Self, String> {
+        Self::new(
+            Duration::from_secs(config.health_check_timeout),
+            config.health_check_concurrency,
+            config.health_check_rate_limit,
+            config.health_check_allow_private,
+        )
+    }
+
+    /// Check one destination
+    pub async fn check(&self, url: &str) -> HealthCheck {
+        if !self.allow_private
+            && let Ok(parsed) = Url::parse(url)
+            && let Err(e) = check_public(&parsed)
+        {
+            return HealthCheck {
+                checked_at: Utc::now(),
+                status: None,
+                error: Some(e.to_string()),
+            };
+        }
+
+        let result = match self.request(Method::HEAD, url).await {
+            Ok(status) if status.as_u16() < 400 => Ok(status),
+            _ => self.request(Method::GET, url).await,
+        };
+
+        let (status, error) = match result {
+            Ok(status) if status.as_u16() < 400 => (Some(status), None),
+            Ok(status) => (Some(status), Some(format!("HTTP {}", status))),
+            Err(e) => (e.status(), Some(describe_error(&e))),
+        };
+
+        HealthCheck {
+            checked_at: Utc::now(),
+            status: status.map(|status| status.as_u16() as i32),
+            error: error.map(|error| error.chars().take(MAX_ERROR_LEN).collect()),
+        }
+    }
+
+    /// Check many destinations within the concurrency and rate limits
+    ///
+    /// Each target is paired with its URL; results come back in completion order.
+    pub async fn check_all<T>(&self, targets: Vec<(T, String)>) -> Vec<(T, HealthCheck)> {
+        let ticker = (self.rate_limit > 0).then(|| Mutex::new(rate_ticker(self.rate_limit)));
+        let ticker = &ticker;
+
+        stream::iter(targets)
+            .map(|(target, url)| async move {
+                if let Some(ticker) = ticker {
+                    ticker.lock().await.tick().await;
+                }
+                (target, self.check(&url).await)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    async fn request(&self, method: Method, url: &str) -> Result<StatusCode, reqwest::Error> {
+        let response = self.client.request(method, url).send().await?;
+        Ok(response.status())
+    }
+}
+
+/// Interval letting `rate_limit` requests start per second
+fn rate_ticker(rate_limit: u32) -> Interval {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1) / rate_limit);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+/// Resolves host names to their public addresses only
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            let public: Vec<SocketAddr> = addrs
+                .iter()
+                .copied()
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+
+            match (public.is_empty(), addrs.first()) {
+                (true, Some(addr)) => Err(PrivateAddress(addr.ip()).into()),
+                _ => Ok(Box::new(public.into_iter()) as Addrs),
+            }
+        })
+    }
+}
+
+/// Refuse a URL whose host is a non-public IP literal
+///
+/// Host names are checked by [`PublicResolver`] when they are resolved.
+fn check_public(url: &Url) -> Result<(), PrivateAddress> {
+    let ip = url
+        .host_str()
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+        .and_then(|host| host.parse::<IpAddr>().ok());
+
+    match ip {
+        Some(ip) if !is_public(ip) => Err(PrivateAddress(ip)),
+        _ => Ok(()),
+    }
+}
+
+/// Whether an address is globally routable
+///
+/// Loopback, private, shared (CGNAT), link-local (including cloud metadata
+/// endpoints), documentation, benchmarking, reserved, multicast and
+/// unspecified addresses are not, nor IPv6 addresses embedding one of those.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space (RFC 6598)
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking (RFC 2544)
+        || (a == 198 && (18..20).contains(&b))
+        // Reserved
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+
+    let segments = ip.segments();
+    // NAT64 (RFC 6052) embeds an IPv4 address in the last 32 bits
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., high, low] = segments;
+        return is_public_v4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)));
+    }
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // Deprecated site-local
+        || (segments[0] & 0xffc0) == 0xfec0
+        // Documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        // IPv4-compatible (deprecated)
+        || segments[..6] == [0; 6])
+}
+
+fn describe_error(e: &reqwest::Error) -> String {
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        if let Some(private) = cause.downcast_ref::<PrivateAddress>() {
+            return private.to_string();
+        }
+        source = cause.source();
+    }
+
+    if e.is_timeout() {
+        "Request timed out".to_string()
+    } else if e.is_redirect() {
+        format!("More than {} redirects", MAX_REDIRECTS)
+    } else if e.is_connect() {
+        "Connection failed".to_string()
+    } else {
+        let mut message = e.to_string();
+        let mut source = std::error::Error::source(e);
+        while let Some(cause) = source {
+            message = format!("{}: {}", message, cause);
+            source = cause.source();
+        }
+        message
+    }
+}
+
+/// Local HTTP stub standing in for destinations in tests
+#[cfg(test)]
+pub(crate) mod stub {
+    use axum::{Router, http::StatusCode, response::Redirect, routing::get};
+    use std::net::SocketAddr;
+
+    /// Serve `/ok`, `/no-head` (HEAD answers 405), `/missing` (404),
+    /// `/error` (500) and `/moved` (redirects to `/ok`) on a random local port
+    pub(crate) async fn spawn() -> SocketAddr {
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/no-head",
+                get(|| async { "ok" }).head(|| async { StatusCode::METHOD_NOT_ALLOWED }),
+            )
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+            .route(
+                "/error",
+                get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            )
+            .route("/moved", get(|| async { Redirect::temporary("/ok") }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        addr
+    }
+
+    /// Address nothing listens on
+    pub(crate) async fn closed() -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checker allowed to reach the local stub
+    fn checker() -> HealthChecker {
+        HealthChecker::new(Duration::from_secs(5), 4, 0, true).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_statuses() {
+        let addr = stub::spawn().await;
+        let checker = checker();
+
+        let check = checker.check(&format!("http://{}/ok", addr)).await;
+        assert!(check.is_ok());
+        assert_eq!(check.status, Some(200));
+
+        // HEAD is rejected, GET works
+        let check = checker.check(&format!("http://{}/no-head", addr)).await;
+        assert!(check.is_ok());
+        assert_eq!(check.status, Some(200));
+
+        let check = checker.check(&format!("http://{}/moved", addr)).await;
+        assert!(check.is_ok());
+        assert_eq!(check.status, Some(200));
+
+        let check = checker.check(&format!("http://{}/missing", addr)).await;
+        assert!(!check.is_ok());
+        assert_eq!(check.status, Some(404));
+        assert_eq!(check.error.as_deref(), Some("HTTP 404 Not Found"));
+
+        let check = checker.check(&format!("http://{}/error", addr)).await;
+        assert_eq!(check.status, Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_check_unreachable() {
+        let addr = stub::closed().await;
+
+        let check = checker().check(&format!("http://{}/", addr)).await;
+        assert!(!check.is_ok());
+        assert_eq!(check.status, None);
+        assert_eq!(check.error.as_deref(), Some("Connection failed"));
+    }
+
+    #[tokio::test]
+    async fn test_check_private_refused() {
+        let addr = stub::spawn().await;
+        let public_only = HealthChecker::new(Duration::from_secs(5), 4, 0, false).unwrap();
+
+        let check = public_only.check(&format!("http://{}/ok", addr)).await;
+        assert!(!check.is_ok());
+        assert_eq!(check.status, None);
+        assert_eq!(
+            check.error.as_deref(),
+            Some("Address 127.0.0.1 is not public")
+        );
+
+        // Host names are checked once resolved
+        let check = public_only
+            .check(&format!("http://localhost:{}/ok", addr.port()))
+            .await;
+        assert_eq!(check.status, None);
+        assert!(check.error.unwrap().ends_with("is not public"));
+
+        let check = public_only
+            .check("http://169.254.169.254/latest/meta-data/")
+            .await;
+        assert_eq!(
+            check.error.as_deref(),
+            Some("Address 169.254.169.254 is not public")
+        );
+
+        // Allowed with the opt-in
+        assert!(
+            checker()
+                .check(&format!("http://{}/ok", addr))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_is_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "93.184.216.34",
+            "8.8.8.8",
+            "2606:4700::1111",
+            "::ffff:8.8.8.8",
+        ] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+
+        // Redirect targets: IP literals are refused, host names left to the resolver
+        assert!(check_public(&Url::parse("http://[::1]:8080/").unwrap()).is_err());
+        assert!(check_public(&Url::parse("http://10.0.0.1/admin").unwrap()).is_err());
+        assert!(check_public(&Url::parse("https://example.com/").unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_all_rate_limited() {
+        let addr = stub::spawn().await;
+        let checker = HealthChecker::new(Duration::from_secs(5), 2, 20, true).unwrap();
+
+        let targets: Vec<(usize, String)> =
+            (0..5).map(|i| (i, format!("http://{}/ok", addr))).collect();
+        let started = std::time::Instant::now();
+        let mut results = checker.check_all(targets).await;
+
+        // 20 requests per second: 5 requests take at least 4 intervals of 50ms
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        results.sort_by_key(|(i, _)| *i);
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|(_, check)| check.is_ok()));
+    }
+}
@@ -0,0 +1,463 @@
+use crate::models::url::UrlStatus;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Column names accepted for each field, including the ones of YOURLS and Shlink exports
+const CODE_COLUMNS: &[&str] = &["code", "short_code", "shortcode", "keyword"];
+const URL_COLUMNS: &[&str] = &["url", "original_url", "long_url", "longurl"];
+const DESCRIPTION_COLUMNS: &[&str] = &["description", "title"];
+const STATUS_COLUMNS: &[&str] = &["status"];
+const CREATED_AT_COLUMNS: &[&str] = &["created_at", "createdat", "date_created", "timestamp"];
+
+/// Format of an import file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// Comma separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl ImportFormat {
+    /// Format matching a `Content-Type` header
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_lowercase();
+        match mime.as_str() {
+            "text/csv" | "application/csv" => Some(Self::Csv),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => {
+                Some(Self::Ndjson)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What to do with an imported code that is already taken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Keep the existing link
+    Skip,
+    /// Replace the destination, description, status and creation time of the existing link
+    ///
+    /// Links in the trash are not overwritten; they count as conflicts.
+    Overwrite,
+    /// Abort the whole import
+    #[default]
+    Fail,
+}
+
+/// Options of an import
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImportOptions {
+    /// Taken from the `Content-Type` header when missing
+    pub format: Option<ImportFormat>,
+    #[serde(default)]
+    pub on_conflict: ConflictPolicy,
+    /// Validate and resolve conflicts without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Short domain of the imported links (the default domain when missing)
+    pub domain: Option<String>,
+}
+
+/// One link read from an import file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportRecord {
+    /// Line of the record in the file, starting at 1
+    pub line: usize,
+    /// Generated when missing
+    pub code: Option<String>,
+    pub url: String,
+    pub description: Option<String>,
+    /// Enabled when missing
+    pub status: Option<UrlStatus>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Row of an import file that could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// What happened to one row of an import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowResult {
+    Created,
+    Updated,
+    Skipped,
+    /// The code is taken and the conflict policy is `fail`, or it belongs to
+    /// a link in the trash and the policy is `overwrite`
+    Conflict,
+    Invalid,
+}
+
+/// Report of one row of an import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowReport {
+    pub line: usize,
+    pub short_code: Option<String>,
+    pub result: RowResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Report of an import
+///
+/// The counts tell what the import did, or would have done when it was not
+/// committed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// Whether the links were written; imports with invalid rows or
+    /// conflicts under the `fail` policy write nothing
+    pub committed: bool,
+    pub total: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub conflicts: usize,
+    pub invalid: usize,
+    /// One entry per row, in file order
+    pub rows: Vec<RowReport>,
+}
+
+impl ImportReport {
+    /// Build a report from its rows
+    pub fn new(rows: Vec<RowReport>, dry_run: bool, committed: bool) -> Self {
+        let count = |result| rows.iter().filter(|row| row.result == result).count();
+
+        Self {
+            dry_run,
+            committed,
+            total: rows.len(),
+            created: count(RowResult::Created),
+            updated: count(RowResult::Updated),
+            skipped: count(RowResult::Skipped),
+            conflicts: count(RowResult::Conflict),
+            invalid: count(RowResult::Invalid),
+            rows,
+        }
+    }
+}
+
+/// Parse an import file
+///
+/// CSV files need a header row naming the columns: `code`, `url`,
+/// `description`, `status` and `created_at` (in any order, only `url` is
+/// required). NDJSON lines use the same names as keys. The column names of
+/// YOURLS and Shlink exports (`keyword`, `shortCode`, `longUrl`, `title`,
+/// `timestamp`, `createdAt`) are understood as well; other columns are ignored.
+///
+/// # Returns
+///
+/// * `Ok(Vec<Result<ImportRecord, RowError>>)` - One entry per row
+/// * `Err(String)` - The file as a whole cannot be read
+pub fn parse(
+    format: ImportFormat,
+    text: &str,
+) -> Result<Vec<Result<ImportRecord, RowError>>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    match format {
+        ImportFormat::Csv => parse_csv(text),
+        ImportFormat::Ndjson => Ok(parse_ndjson(text)),
+    }
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Result<ImportRecord, RowError>>, String> {
+    let mut rows = csv_rows(text)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = header
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    if !header
+        .iter()
+        .any(|name| URL_COLUMNS.contains(&name.as_str()))
+    {
+        return Err(format!(
+            "CSV header must have a url column ({})",
+            URL_COLUMNS.join(", ")
+        ));
+    }
+
+    Ok(rows
+        .map(|(line, fields)| {
+            let field = |names: &[&str]| {
+                header
+                    .iter()
+                    .position(|name| names.contains(&name.as_str()))
+                    .and_then(|i| fields.get(i).cloned())
+            };
+            record(line, field).map_err(|message| RowError { line, message })
+        })
+        .collect())
+}
+
+fn parse_ndjson(text: &str) -> Vec<Result<ImportRecord, RowError>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let line_number = i + 1;
+            let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(line)
+                .map_err(|e| RowError {
+                    line: line_number,
+                    message: format!("invalid JSON: {}", e),
+                })?;
+
+            let field = |names: &[&str]| {
+                object
+                    .iter()
+                    .find(|(key, _)| names.contains(&key.to_lowercase().as_str()))
+                    .and_then(|(_, value)| match value {
+                        serde_json::Value::Null => None,
+                        serde_json::Value::String(value) => Some(value.clone()),
+                        value => Some(value.to_string()),
+                    })
+            };
+            record(line_number, field).map_err(|message| RowError {
+                line: line_number,
+                message,
+            })
+        })
+        .collect()
+}
+
+/// Build a record from the fields of a row
+fn record(line: usize, field: impl Fn(&[&str]) -> Option<String>) -> Result<ImportRecord, String> {
+    let field = |names: &[&str]| {
+        field(names)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    Ok(ImportRecord {
+        line,
+        code: field(CODE_COLUMNS),
+        url: field(URL_COLUMNS).ok_or("url is missing")?,
+        description: field(DESCRIPTION_COLUMNS),
+        status: field(STATUS_COLUMNS)
+            .map(|status| parse_status(&status))
+            .transpose()?,
+        created_at: field(CREATED_AT_COLUMNS)
+            .map(|at| parse_datetime(&at))
+            .transpose()?,
+    })
+}
+
+/// Status given as its number or name
+fn parse_status(value: &str) -> Result<UrlStatus, String> {
+    match value.to_lowercase().as_str() {
+        "0" | "enabled" => Ok(UrlStatus::Enabled),
+        "1" | "disabled" => Ok(UrlStatus::Disabled),
+        "2" | "expired" => Ok(UrlStatus::Expired),
+        _ => Err(format!("invalid status '{}'", value)),
+    }
+}
+
+/// RFC 3339 time, `YYYY-MM-DD HH:MM:SS` in UTC or a Unix timestamp
+fn parse_datetime(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(at) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(at.and_utc());
+        }
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| format!("invalid created_at '{}'", value))
+}
+
+/// Split CSV text into rows of fields, each with the line it starts on
+///
+/// Fields may be quoted with `"`, holding commas, line breaks and `""` for a
+/// quote. Blank lines are skipped.
+fn csv_rows(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut rows = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                if fields.len() > 1 || !fields[0].trim().is_empty() {
+                    rows.push((row_line, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                row_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(format!("line {}: unterminated quoted field", row_line));
+    }
+    fields.push(field);
+    if fields.len() > 1 || !fields[0].trim().is_empty() {
+        rows.push((row_line, fields));
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_rows() {
+        let rows = csv_rows(
+            "code,url\r\na,\"https://example.com/?q=1,2\"\n\nb,\"say \"\"hi\"\"\nthere\"\nc,x",
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[1],
+            (
+                2,
+                vec!["a".to_string(), "https://example.com/?q=1,2".to_string()]
+            )
+        );
+        assert_eq!(
+            rows[2],
+            (4, vec!["b".to_string(), "say \"hi\"\nthere".to_string()])
+        );
+        assert_eq!(rows[3].0, 6);
+
+        assert!(csv_rows("code,url\na,\"open").is_err());
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let text = "\u{feff}Code,URL,Description,Status,Created_At,Clicks\n\
+                    docs,https://example.com/docs,Docs,disabled,2024-01-15T08:30:00Z,3\n\
+                    ,https://example.com/auto,,,,\n\
+                    bad,,,,,\n\
+                    when,https://example.com,,,yesterday,\n";
+        let rows = parse(ImportFormat::Csv, text).unwrap();
+
+        assert_eq!(rows.len(), 4);
+        let docs = rows[0].as_ref().unwrap();
+        assert_eq!(docs.line, 2);
+        assert_eq!(docs.code.as_deref(), Some("docs"));
+        assert_eq!(docs.description.as_deref(), Some("Docs"));
+        assert_eq!(docs.status, Some(UrlStatus::Disabled));
+        assert_eq!(
+            docs.created_at.unwrap().to_rfc3339(),
+            "2024-01-15T08:30:00+00:00"
+        );
+        let auto = rows[1].as_ref().unwrap();
+        assert_eq!(auto.code, None);
+        assert_eq!(auto.status, None);
+        assert_eq!(
+            rows[2].as_ref().unwrap_err(),
+            &RowError {
+                line: 4,
+                message: "url is missing".to_string(),
+            }
+        );
+        assert_eq!(
+            rows[3].as_ref().unwrap_err().message,
+            "invalid created_at 'yesterday'"
+        );
+
+        assert!(parse(ImportFormat::Csv, "code,target\na,b\n").is_err());
+        assert!(parse(ImportFormat::Csv, "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_yourls_and_shlink_columns() {
+        let yourls = "keyword,url,title,timestamp,ip,clicks\n\
+                      gh,https://github.com,GitHub,2024-01-15 08:30:00,127.0.0.1,5\n";
+        let record = parse(ImportFormat::Csv, yourls).unwrap().remove(0).unwrap();
+        assert_eq!(record.code.as_deref(), Some("gh"));
+        assert_eq!(record.description.as_deref(), Some("GitHub"));
+        assert_eq!(
+            record.created_at.unwrap().to_rfc3339(),
+            "2024-01-15T08:30:00+00:00"
+        );
+
+        let shlink = r#"{"shortCode":"gh","longUrl":"https://github.com","createdAt":"2024-01-15T10:30:00+02:00"}"#;
+        let record = parse(ImportFormat::Ndjson, shlink)
+            .unwrap()
+            .remove(0)
+            .unwrap();
+        assert_eq!(record.code.as_deref(), Some("gh"));
+        assert_eq!(record.url, "https://github.com");
+        assert_eq!(
+            record.created_at.unwrap().to_rfc3339(),
+            "2024-01-15T08:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_ndjson() {
+        let text = "{\"code\":\"a\",\"url\":\"https://example.com\",\"status\":1,\"created_at\":1700000000}\n\
+                    \n\
+                    not json\n\
+                    {\"code\":\"c\",\"url\":\"https://example.com\",\"status\":7}\n";
+        let rows = parse(ImportFormat::Ndjson, text).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        let record = rows[0].as_ref().unwrap();
+        assert_eq!(record.status, Some(UrlStatus::Disabled));
+        assert_eq!(record.created_at.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(rows[1].as_ref().unwrap_err().line, 3);
+        assert!(
+            rows[1]
+                .as_ref()
+                .unwrap_err()
+                .message
+                .starts_with("invalid JSON")
+        );
+        assert_eq!(rows[2].as_ref().unwrap_err().message, "invalid status '7'");
+    }
+
+    #[test]
+    fn test_format_from_content_type() {
+        assert_eq!(
+            ImportFormat::from_content_type("text/csv; charset=utf-8"),
+            Some(ImportFormat::Csv)
+        );
+        assert_eq!(
+            ImportFormat::from_content_type("application/x-ndjson"),
+            Some(ImportFormat::Ndjson)
+        );
+        assert_eq!(ImportFormat::from_content_type("text/plain"), None);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::services::ShortenService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Spawn the expiry sweeper
+///
+/// Periodically flips links whose `expires_at` has passed to `UrlStatus::Expired`
+/// and evicts them from the cache.
+///
+/// # Arguments
+///
+/// * `service` - Shorten service used to run the sweep
+/// * `interval` - Time between two sweeps
+///
+/// # Returns
+///
+/// * `JoinHandle<()>` - Handle of the spawned task
+pub fn spawn_expiry_sweeper(service: Arc<ShortenService>, interval: Duration) -> JoinHandle<()> {
+    info!("Starting expiry sweeper (interval: {:?})", interval);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = service.expire_due().await {
+                error!("Expiry sweep failed: {}", e);
+            }
+        }
+    })
+}
@@ -0,0 +1,45 @@
+use crate::health::HealthChecker;
+use crate::services::ShortenService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Spawn the destination health checker
+///
+/// Periodically requests the destination of every enabled link, records
+/// `last_checked_at`, `last_status` and `last_error`, and disables links that
+/// keep failing when `health_check_disable_after` is set.
+///
+/// # Arguments
+///
+/// * `service` - Shorten service used to run the checks
+/// * `checker` - HTTP checker with the configured timeout, concurrency and rate limit
+/// * `interval` - Time between two runs
+///
+/// # Returns
+///
+/// * `JoinHandle<()>` - Handle of the spawned task
+pub fn spawn_health_checker(
+    service: Arc<ShortenService>,
+    checker: HealthChecker,
+    interval: Duration,
+) -> JoinHandle<()> {
+    info!(
+        "Starting destination health checker (interval: {:?})",
+        interval
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = service.check_health(&checker).await {
+                error!("Destination health check failed: {}", e);
+            }
+        }
+    })
+}
@@ -0,0 +1,11 @@
+//! Background jobs running alongside the HTTP server
+
+mod expiry;
+mod health;
+mod policy;
+mod trash;
+
+pub use expiry::spawn_expiry_sweeper;
+pub use health::spawn_health_checker;
+pub use policy::spawn_policy_reloader;
+pub use trash::spawn_trash_purger;
@@ -0,0 +1,38 @@
+use crate::services::ShortenService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Spawn the destination policy reloader
+///
+/// Periodically reloads the destination blocklist and allowlist files when
+/// their modification time changed, so edits take effect without a restart.
+///
+/// # Arguments
+///
+/// * `service` - Shorten service holding the policy
+/// * `interval` - Time between two checks
+///
+/// # Returns
+///
+/// * `JoinHandle<()>` - Handle of the spawned task
+pub fn spawn_policy_reloader(service: Arc<ShortenService>, interval: Duration) -> JoinHandle<()> {
+    info!(
+        "Starting destination policy reloader (interval: {:?})",
+        interval
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = service.reload_policy_if_changed() {
+                error!("Destination policy reload failed: {}", e);
+            }
+        }
+    })
+}
@@ -0,0 +1,35 @@
+use crate::services::ShortenService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Spawn the trash purger
+///
+/// Periodically deletes links that stayed in the trash longer than
+/// `trash_retention_days` for good, along with their access history.
+///
+/// # Arguments
+///
+/// * `service` - Shorten service used to run the purge
+/// * `interval` - Time between two purges
+///
+/// # Returns
+///
+/// * `JoinHandle<()>` - Handle of the spawned task
+pub fn spawn_trash_purger(service: Arc<ShortenService>, interval: Duration) -> JoinHandle<()> {
+    info!("Starting trash purger (interval: {:?})", interval);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = service.purge_trash().await {
+                error!("Trash purge failed: {}", e);
+            }
+        }
+    })
+}
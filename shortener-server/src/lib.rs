@@ -1,13 +1,30 @@
 pub mod cache;
+pub mod codegen;
 pub mod config;
 pub mod db;
+pub mod destination;
+pub mod domains;
 pub mod errors;
+pub mod export;
 pub mod geoip;
 pub mod handlers;
+pub mod health;
+pub mod import;
+pub mod jobs;
 pub mod logging;
+pub mod metadata;
 pub mod middleware;
 pub mod migration;
 pub mod models;
+pub mod nullable;
+pub mod password;
+pub mod policy;
+pub mod qr;
 pub mod repositories;
+pub mod reserved;
 pub mod router;
 pub mod services;
+pub mod tags;
+pub mod targeting;
+pub mod url_hash;
+pub mod variants;
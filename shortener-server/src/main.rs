@@ -1,13 +1,21 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use sea_orm::DatabaseConnection;
 use shortener_server::{
     cache::create_cache,
     config::Config,
     db::DbFactory,
     geoip::create_geoip,
-    repositories::{HistoryRepositoryImpl, UrlRepositoryImpl},
+    health::HealthChecker,
+    jobs::{spawn_expiry_sweeper, spawn_health_checker, spawn_policy_reloader, spawn_trash_purger},
+    models::user::UserRole,
+    repositories::{
+        HistoryRepositoryImpl, RevisionRepositoryImpl, TagRepositoryImpl, UrlRepositoryImpl,
+        UserRepositoryImpl,
+    },
     router::{AppState, create_router},
-    services::{HistoryService, ShortenService},
+    services::{CreateUserRequest, HistoryService, ShortenService, UserService},
 };
+use std::io::{BufRead, Write};
 use std::sync::Arc;
 use tokio::signal;
 use tracing::{error, info};
@@ -34,6 +42,58 @@ enum Commands {
         #[arg(short, long)]
         force: bool,
     },
+    /// Manage the accounts that can log in to the admin API
+    User {
+        #[command(subcommand)]
+        action: UserCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+    /// Create a user
+    Add {
+        /// Username (letters, digits, '.', '-' or '_')
+        username: String,
+        /// Password, read from standard input when unset
+        #[arg(short, long, env = "SHORTENER_USER_PASSWORD")]
+        password: Option<String>,
+        /// Role of the user
+        #[arg(short, long, value_enum, default_value_t = Role::User)]
+        role: Role,
+        /// Name shown instead of the username
+        #[arg(short, long)]
+        display_name: Option<String>,
+        /// Email address
+        #[arg(short, long)]
+        email: Option<String>,
+    },
+    /// Set the password of a user
+    Passwd {
+        /// Username
+        username: String,
+        /// New password, read from standard input when unset
+        #[arg(short, long, env = "SHORTENER_USER_PASSWORD")]
+        password: Option<String>,
+    },
+    /// List users
+    List,
+}
+
+/// Role of a user on the command line
+#[derive(Clone, Copy, ValueEnum)]
+enum Role {
+    Admin,
+    User,
+}
+
+impl From<Role> for UserRole {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Admin => UserRole::Admin,
+            Role::User => UserRole::User,
+        }
+    }
 }
 
 #[tokio::main]
@@ -46,34 +106,17 @@ async fn main() {
         match command {
             Commands::Init { force } => {
                 handle_init_command(force);
-                return;
+            }
+            Commands::User { action } => {
+                let (config, _) = load_config(&args.config);
+                handle_user_command(&config, action).await;
             }
         }
+        return;
     }
 
     // Load configuration first (before logging initialization)
-    // Try default path first, fallback to config.toml if not exists
-    let config_path = if std::path::Path::new(&args.config).exists() {
-        args.config.clone()
-    } else if args.config == "config/config.toml" && std::path::Path::new("config.toml").exists() {
-        "config.toml".to_string()
-    } else {
-        args.config.clone()
-    };
-
-    let config = match Config::from_file(&config_path) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            eprintln!(
-                "✗ Failed to load configuration from '{}': {}",
-                config_path, e
-            );
-            if config_path != args.config {
-                eprintln!("  (fallback from '{}')", args.config);
-            }
-            std::process::exit(1);
-        }
-    };
+    let (config, config_path) = load_config(&args.config);
 
     // Initialize logging with configuration
     if let Err(e) = config.logging.init() {
@@ -84,20 +127,8 @@ async fn main() {
     info!("Shortener Server v{}", env!("CARGO_PKG_VERSION"));
     info!("Configuration loaded from: {}", config_path);
 
-    // 初始化数据库
-    let db = match DbFactory::create_connection(&config).await {
-        Ok(connection) => connection,
-        Err(e) => {
-            error!("Failed to connect to database: {}", e);
-            std::process::exit(1);
-        }
-    };
-
-    // 运行数据库迁移
-    if let Err(e) = DbFactory::run_migrations(&db).await {
-        error!("Failed to run database migrations: {}", e);
-        std::process::exit(1);
-    }
+    // 初始化数据库并运行迁移
+    let db = connect_database(&config).await;
 
     // 初始化缓存
     let cache = create_cache(&config.cache).await;
@@ -107,11 +138,16 @@ async fn main() {
 
     // 初始化 repositories
     let url_repo = Arc::new(UrlRepositoryImpl::new(db.clone()));
-    let history_repo = Arc::new(HistoryRepositoryImpl::new(db));
+    let tag_repo = Arc::new(TagRepositoryImpl::new(db.clone()));
+    let revision_repo = Arc::new(RevisionRepositoryImpl::new(db.clone()));
+    let history_repo = Arc::new(HistoryRepositoryImpl::new(db.clone()));
+    let user_repo = Arc::new(UserRepositoryImpl::new(db));
 
     // 初始化 services
     let shorten_service = Arc::new(ShortenService::new(
         url_repo,
+        tag_repo,
+        revision_repo,
         cache,
         config.shortener.clone(),
         config.server.site_url.clone(),
@@ -119,10 +155,59 @@ async fn main() {
 
     let history_service = Arc::new(HistoryService::new(history_repo, geoip));
 
+    // 首次启动时根据 [admin] 配置创建管理员账号
+    let user_service = Arc::new(UserService::new(user_repo));
+    if let Err(e) = user_service.ensure_admin(&config.admin).await {
+        error!("Failed to create administrator: {}", e);
+        std::process::exit(1);
+    }
+
+    // 启动过期链接清理任务
+    if config.shortener.expiry_sweep_interval > 0 {
+        spawn_expiry_sweeper(
+            shorten_service.clone(),
+            std::time::Duration::from_secs(config.shortener.expiry_sweep_interval),
+        );
+    }
+
+    // 启动目标地址策略热加载任务
+    if config.shortener.policy_reload_interval > 0
+        && (config.shortener.destination_blocklist_file.is_some()
+            || config.shortener.destination_allowlist_file.is_some())
+    {
+        spawn_policy_reloader(
+            shorten_service.clone(),
+            std::time::Duration::from_secs(config.shortener.policy_reload_interval),
+        );
+    }
+
+    // 启动目标地址健康检查任务
+    if config.shortener.health_check_interval > 0 {
+        match HealthChecker::from_config(&config.shortener) {
+            Ok(checker) => {
+                spawn_health_checker(
+                    shorten_service.clone(),
+                    checker,
+                    std::time::Duration::from_secs(config.shortener.health_check_interval),
+                );
+            }
+            Err(e) => error!("Destination health checker not started: {}", e),
+        }
+    }
+
+    // 启动回收站清理任务
+    if config.shortener.trash_retention_days > 0 {
+        spawn_trash_purger(
+            shorten_service.clone(),
+            std::time::Duration::from_secs(3600),
+        );
+    }
+
     // 创建应用状态
     let state = AppState {
         shorten_service,
         history_service,
+        user_service,
         config: Arc::new(config.clone()),
     };
 
@@ -160,7 +245,6 @@ async fn main() {
 
     info!("Server listening on http://{}", addr);
     info!("Site URL: {}", config.server.site_url);
-    info!("Admin: {}", config.admin.username);
 
     // 启动服务器并处理优雅关闭
     if let Err(e) = axum::serve(listener, app)
@@ -174,6 +258,133 @@ async fn main() {
     info!("Server shutdown complete");
 }
 
+/// Load the configuration file, exiting on failure
+///
+/// Falls back to `config.toml` when the default path does not exist.
+/// Returns the configuration and the path it was read from.
+fn load_config(path: &str) -> (Config, String) {
+    let config_path = if std::path::Path::new(path).exists() {
+        path.to_string()
+    } else if path == "config/config.toml" && std::path::Path::new("config.toml").exists() {
+        "config.toml".to_string()
+    } else {
+        path.to_string()
+    };
+
+    match Config::from_file(&config_path) {
+        Ok(config) => (config, config_path),
+        Err(e) => {
+            eprintln!(
+                "✗ Failed to load configuration from '{}': {}",
+                config_path, e
+            );
+            if config_path != path {
+                eprintln!("  (fallback from '{}')", path);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Connect to the database and run migrations, exiting on failure
+async fn connect_database(config: &Config) -> DatabaseConnection {
+    let db = match DbFactory::create_connection(config).await {
+        Ok(connection) => connection,
+        Err(e) => {
+            error!("Failed to connect to database: {}", e);
+            eprintln!("✗ Failed to connect to database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = DbFactory::run_migrations(&db).await {
+        error!("Failed to run database migrations: {}", e);
+        eprintln!("✗ Failed to run database migrations: {}", e);
+        std::process::exit(1);
+    }
+
+    db
+}
+
+/// Handle user command to manage accounts in the database
+async fn handle_user_command(config: &Config, action: UserCommand) {
+    let db = connect_database(config).await;
+    let service = UserService::new(Arc::new(UserRepositoryImpl::new(db)));
+
+    // The [admin] account exists before the first user is added by hand
+    if let Err(e) = service.ensure_admin(&config.admin).await {
+        eprintln!("✗ Failed to create administrator: {}", e);
+        std::process::exit(1);
+    }
+
+    let result = match action {
+        UserCommand::Add {
+            username,
+            password,
+            role,
+            display_name,
+            email,
+        } => {
+            let req = CreateUserRequest {
+                password: password.unwrap_or_else(|| read_password(&username)),
+                username,
+                display_name,
+                email,
+                role: Some(role.into()),
+            };
+            service.create_user(req).await.map(|user| {
+                println!(
+                    "✓ Created user '{}' ({})",
+                    user.username,
+                    format!("{:?}", user.role).to_lowercase()
+                );
+            })
+        }
+        UserCommand::Passwd { username, password } => {
+            let password = password.unwrap_or_else(|| read_password(&username));
+            service
+                .set_password(&username, &password)
+                .await
+                .map(|_| println!("✓ Changed password of '{}'", username))
+        }
+        UserCommand::List => service.list_users().await.map(|users| {
+            println!(
+                "{:<24} {:<6} {:<9} {:<25} NAME",
+                "USERNAME", "ROLE", "STATUS", "LAST LOGIN"
+            );
+            for user in users {
+                println!(
+                    "{:<24} {:<6} {:<9} {:<25} {}",
+                    user.username,
+                    format!("{:?}", user.role).to_lowercase(),
+                    if user.disabled { "disabled" } else { "active" },
+                    user.last_login_at.as_deref().unwrap_or("-"),
+                    user.display_name.as_deref().unwrap_or("")
+                );
+            }
+        }),
+    };
+
+    if let Err(e) = result {
+        eprintln!("✗ {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Read a password from the first line of standard input
+fn read_password(username: &str) -> String {
+    eprint!("Password for '{}': ", username);
+    let _ = std::io::stderr().flush();
+
+    let mut line = String::new();
+    if let Err(e) = std::io::stdin().lock().read_line(&mut line) {
+        eprintln!("✗ Failed to read password: {}", e);
+        std::process::exit(1);
+    }
+
+    line.trim_end_matches(['\r', '\n']).to_string()
+}
+
 /// Handle init command to create config.toml
 fn handle_init_command(force: bool) {
     const DEFAULT_CONFIG: &str = include_str!("../../config/config.toml");
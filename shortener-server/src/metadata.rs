@@ -0,0 +1,180 @@
+use sea_orm::DbBackend;
+use sea_orm::FromJsonQueryResult;
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Longest allowed metadata key
+pub const MAX_METADATA_KEY_LEN: usize = 64;
+
+/// Largest allowed metadata object, in bytes of JSON
+pub const MAX_METADATA_SIZE: usize = 4096;
+
+/// Free-form attributes of a URL (campaign, owner, ticket...), stored as a JSON column
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct Metadata(pub Map<String, Value>);
+
+/// Check a metadata key is usable
+///
+/// Keys end up in JSON paths of filter queries, so they are limited to
+/// letters, digits, `-` and `_`.
+pub fn check_metadata_key(key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Err("metadata key cannot be empty".to_string());
+    }
+    if key.len() > MAX_METADATA_KEY_LEN
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid metadata key: {} (use up to {} letters, digits, '-' or '_')",
+            key, MAX_METADATA_KEY_LEN
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check metadata before storing it
+pub fn normalize_metadata(metadata: Map<String, Value>) -> Result<Metadata, String> {
+    for key in metadata.keys() {
+        check_metadata_key(key)?;
+    }
+
+    let size = serde_json::to_string(&metadata)
+        .map_err(|e| e.to_string())?
+        .len();
+    if size > MAX_METADATA_SIZE {
+        return Err(format!(
+            "metadata is too large: {} bytes (at most {})",
+            size, MAX_METADATA_SIZE
+        ));
+    }
+
+    Ok(Metadata(metadata))
+}
+
+/// Filter on a metadata attribute, written `key` or `key:value`
+///
+/// Without a value, URLs having the key match. With one, the attribute must
+/// equal the value as text, so `ticket:42` matches both `"42"` and `42`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MetadataFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl TryFrom<String> for MetadataFilter {
+    type Error = String;
+
+    fn try_from(filter: String) -> Result<Self, Self::Error> {
+        let (key, value) = match filter.split_once(':') {
+            Some((key, value)) => (key, Some(value.to_string())),
+            None => (filter.as_str(), None),
+        };
+        check_metadata_key(key)?;
+
+        Ok(Self {
+            key: key.to_string(),
+            value,
+        })
+    }
+}
+
+impl From<MetadataFilter> for String {
+    fn from(filter: MetadataFilter) -> Self {
+        match filter.value {
+            Some(value) => format!("{}:{}", filter.key, value),
+            None => filter.key,
+        }
+    }
+}
+
+impl MetadataFilter {
+    /// Condition on the `metadata` column using the JSON operators of the backend
+    pub fn condition(&self, backend: DbBackend) -> SimpleExpr {
+        // The key is checked to be a plain identifier, the path stays a bound value
+        let path = format!("$.\"{}\"", self.key);
+
+        let (attribute, key) = match backend {
+            DbBackend::Postgres => ("(metadata ->> $1)", self.key.clone()),
+            DbBackend::MySql => ("JSON_UNQUOTE(JSON_EXTRACT(metadata, ?))", path),
+            DbBackend::Sqlite => ("CAST(json_extract(metadata, ?) AS TEXT)", path),
+        };
+
+        match (&self.value, backend) {
+            (None, _) => Expr::cust_with_values(format!("{} IS NOT NULL", attribute), [key]),
+            (Some(value), DbBackend::Postgres) => {
+                Expr::cust_with_values(format!("{} = $2", attribute), [key, value.clone()])
+            }
+            (Some(value), _) => {
+                Expr::cust_with_values(format!("{} = ?", attribute), [key, value.clone()])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_metadata() {
+        let metadata = json!({"campaign": "spring-2026", "ticket": 42, "owner_team": "growth"});
+        let Value::Object(map) = metadata else {
+            unreachable!()
+        };
+        assert_eq!(normalize_metadata(map.clone()).unwrap(), Metadata(map));
+
+        let mut map = Map::new();
+        map.insert("bad key".to_string(), json!(1));
+        assert!(normalize_metadata(map).is_err());
+
+        let mut map = Map::new();
+        map.insert("notes".to_string(), json!("x".repeat(MAX_METADATA_SIZE)));
+        assert!(normalize_metadata(map).is_err());
+    }
+
+    #[test]
+    fn test_parse_metadata_filter() {
+        let filter = MetadataFilter::try_from("ticket:JIRA-1:2".to_string()).unwrap();
+        assert_eq!(filter.key, "ticket");
+        assert_eq!(filter.value.as_deref(), Some("JIRA-1:2"));
+        assert_eq!(String::from(filter), "ticket:JIRA-1:2");
+
+        let filter = MetadataFilter::try_from("campaign".to_string()).unwrap();
+        assert_eq!(filter.value, None);
+
+        assert!(MetadataFilter::try_from("$.x:1".to_string()).is_err());
+        assert!(MetadataFilter::try_from(":1".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_metadata_filter_condition() {
+        use crate::models::url::{Column, Entity};
+        use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+
+        let filter = MetadataFilter::try_from("ticket:42".to_string()).unwrap();
+        let sql = |backend| {
+            Entity::find()
+                .select_only()
+                .column(Column::Id)
+                .filter(filter.condition(backend))
+                .build(backend)
+                .to_string()
+        };
+
+        assert!(sql(DbBackend::Postgres).ends_with(r#"WHERE (metadata ->> 'ticket') = '42'"#));
+        assert!(
+            sql(DbBackend::MySql)
+                .ends_with(r#"WHERE JSON_UNQUOTE(JSON_EXTRACT(metadata, '$.\"ticket\"')) = '42'"#)
+        );
+        assert!(
+            sql(DbBackend::Sqlite)
+                .ends_with(r#"WHERE CAST(json_extract(metadata, '$."ticket"') AS TEXT) = '42'"#)
+        );
+    }
+}
@@ -2,6 +2,7 @@ use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Respon
 use std::sync::Arc;
 
 use crate::errors::AppError;
+use crate::handlers::account::ApiKeyCaller;
 
 /// API Key 认证中间件
 #[derive(Clone)]
@@ -22,7 +23,7 @@ impl ApiKeyAuth {
     pub async fn check_api_key(
         api_key: Arc<String>,
         headers: HeaderMap,
-        request: Request,
+        mut request: Request,
         next: Next,
     ) -> Result<Response, AppError> {
         // 从请求头中获取 X-API-KEY
@@ -42,8 +43,9 @@ impl ApiKeyAuth {
             return Err(AppError::Unauthorized("Invalid API Key".to_string()));
         }
 
-        // API Key 验证通过，继续处理请求
+        // API Key 验证通过，标记请求来自 API Key 后继续处理
         tracing::debug!("API Key validated successfully");
+        request.extensions_mut().insert(ApiKeyCaller);
         Ok(next.run(request).await)
     }
 }
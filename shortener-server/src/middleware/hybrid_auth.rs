@@ -1,5 +1,5 @@
 use crate::errors::AppError;
-use crate::handlers::account::{User, verify_token};
+use crate::handlers::account::{ApiKeyCaller, User, verify_token};
 use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Response};
 use std::sync::Arc;
 
@@ -55,8 +55,9 @@ impl HybridAuth {
             return Err(AppError::Unauthorized("Invalid API Key".to_string()));
         }
 
-        // API Key 验证通过
+        // API Key 验证通过，标记请求来自 API Key
         tracing::debug!("API Key validated successfully");
+        request.extensions_mut().insert(ApiKeyCaller);
         Ok(next.run(request).await)
     }
 }
@@ -105,12 +106,16 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    async fn test_handler_with_api_key(Extension(caller): Extension<ApiKeyCaller>) -> String {
+        format!("{:?}", caller)
+    }
+
     #[tokio::test]
     async fn test_hybrid_auth_with_valid_api_key() {
         let api_key = "test-api-key-123".to_string();
 
         let app = Router::new()
-            .route("/test", get(test_handler))
+            .route("/test", get(test_handler_with_api_key))
             .layer(middleware::from_fn(move |headers, req, next| {
                 let api_key = Arc::new(api_key.clone());
                 HybridAuth::check_auth(api_key, headers, req, next)
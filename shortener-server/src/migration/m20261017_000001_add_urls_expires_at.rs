@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Urls::Table)
+                    .add_column(ColumnDef::new(Urls::ExpiresAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create index on expires_at (used by the expiry sweeper)
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_urls_expires_at")
+                    .table(Urls::Table)
+                    .col(Urls::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_urls_expires_at")
+                    .table(Urls::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Urls::Table)
+                    .drop_column(Urls::ExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Urls {
+    Table,
+    ExpiresAt,
+}
@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tags::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Tags::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Tags::Name)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Tags::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UrlTags::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UrlTags::UrlId).big_integer().not_null())
+                    .col(ColumnDef::new(UrlTags::TagId).big_integer().not_null())
+                    .primary_key(Index::create().col(UrlTags::UrlId).col(UrlTags::TagId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_url_tags_url_id")
+                            .from(UrlTags::Table, UrlTags::UrlId)
+                            .to(Urls::Table, Urls::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_url_tags_tag_id")
+                            .from(UrlTags::Table, UrlTags::TagId)
+                            .to(Tags::Table, Tags::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create index on tag_id (used by the tag filter of the URL list)
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_url_tags_tag_id")
+                    .table(UrlTags::Table)
+                    .col(UrlTags::TagId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UrlTags::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Tags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tags {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum UrlTags {
+    Table,
+    UrlId,
+    TagId,
+}
+
+#[derive(DeriveIden)]
+enum Urls {
+    Table,
+    Id,
+}
@@ -0,0 +1,176 @@
+use sea_orm::sqlx::{self, Row};
+use sea_orm_migration::SchemaManagerConnection;
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Short codes become unique per domain instead of globally
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => drop_sqlite_short_code_unique(manager).await?,
+            DbBackend::Postgres => {
+                manager
+                    .get_connection()
+                    .execute_unprepared(
+                        "ALTER TABLE urls DROP CONSTRAINT IF EXISTS urls_short_code_key",
+                    )
+                    .await?;
+            }
+            DbBackend::MySql => {
+                manager
+                    .get_connection()
+                    .execute_unprepared("ALTER TABLE urls DROP INDEX short_code")
+                    .await?;
+            }
+        }
+
+        // Links on the default domain keep an empty domain
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Urls::Table)
+                    .add_column(
+                        ColumnDef::new(Urls::Domain)
+                            .string_len(255)
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_urls_domain_short_code")
+                    .table(Urls::Table)
+                    .col(Urls::Domain)
+                    .col(Urls::ShortCode)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The global uniqueness of short_code is not restored: codes may now
+        // repeat across domains
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_urls_domain_short_code")
+                    .table(Urls::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Urls::Table)
+                    .drop_column(Urls::Domain)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Rebuild the SQLite `urls` table without the UNIQUE constraint of `short_code`
+///
+/// SQLite cannot drop a column constraint, so the table is copied into a new one.
+/// Foreign keys have to be off meanwhile, otherwise dropping the old table would
+/// cascade into `histories` and `url_tags`; the pragma is ignored inside a
+/// transaction, hence the dedicated pool connection.
+async fn drop_sqlite_short_code_unique(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let SchemaManagerConnection::Connection(db) = manager.get_connection() else {
+        return Err(DbErr::Migration(
+            "Rebuilding the urls table needs a plain connection".to_string(),
+        ));
+    };
+    let mut conn = db
+        .get_sqlite_connection_pool()
+        .acquire()
+        .await
+        .map_err(sqlx_error)?;
+
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .map_err(sqlx_error)?;
+
+    let result = async {
+        let table_sql: String =
+            sqlx::query("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'urls'")
+                .fetch_one(&mut *conn)
+                .await?
+                .try_get("sql")?;
+        let index_sqls: Vec<String> = sqlx::query(
+            "SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = 'urls' AND sql IS NOT NULL",
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|row| row.try_get("sql"))
+        .collect::<Result<_, _>>()?;
+
+        let new_sql = without_short_code_unique(&table_sql).replacen("\"urls\"", "\"urls_new\"", 1);
+
+        sqlx::query("BEGIN").execute(&mut *conn).await?;
+        for sql in [
+            new_sql.as_str(),
+            "INSERT INTO urls_new SELECT * FROM urls",
+            "DROP TABLE urls",
+            "ALTER TABLE urls_new RENAME TO urls",
+        ]
+        .into_iter()
+        .chain(index_sqls.iter().map(String::as_str))
+        {
+            if let Err(e) = sqlx::query(sql).execute(&mut *conn).await {
+                sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+                return Err(e);
+            }
+        }
+        sqlx::query("COMMIT").execute(&mut *conn).await?;
+
+        Ok(())
+    }
+    .await;
+
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .map_err(sqlx_error)?;
+
+    result.map_err(sqlx_error)
+}
+
+/// Remove `UNIQUE` from the `short_code` column definition of a CREATE TABLE statement
+fn without_short_code_unique(sql: &str) -> String {
+    let Some(start) = sql.find("\"short_code\"") else {
+        return sql.to_string();
+    };
+    let end = sql[start..].find(',').map_or(sql.len(), |i| start + i);
+
+    format!(
+        "{}{}{}",
+        &sql[..start],
+        sql[start..end].replace(" UNIQUE", ""),
+        &sql[end..]
+    )
+}
+
+fn sqlx_error(e: sqlx::Error) -> DbErr {
+    DbErr::Migration(format!("Failed to rebuild urls table: {}", e))
+}
+
+#[derive(DeriveIden)]
+enum Urls {
+    Table,
+    ShortCode,
+    Domain,
+}
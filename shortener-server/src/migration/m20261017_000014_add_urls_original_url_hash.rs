@@ -0,0 +1,128 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, TryGetable};
+use sha2::{Digest, Sha256};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Urls::Table)
+                    .add_column(
+                        ColumnDef::new(Urls::OriginalUrlHash)
+                            .string_len(64)
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Hash the destinations of existing links
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        let rows = db
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .columns([Urls::Id, Urls::OriginalUrl])
+                        .from(Urls::Table),
+                ),
+            )
+            .await?;
+        for row in rows {
+            let id = i64::try_get(&row, "", "id").map_err(DbErr::from)?;
+            let original_url = String::try_get(&row, "", "original_url").map_err(DbErr::from)?;
+
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Urls::Table)
+                        .value(Urls::OriginalUrlHash, hash(&original_url))
+                        .and_where(Expr::col(Urls::Id).eq(id))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_urls_domain_original_url_hash")
+                    .table(Urls::Table)
+                    .col(Urls::Domain)
+                    .col(Urls::OriginalUrlHash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_urls_domain_original_url_hash")
+                    .table(Urls::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Urls::Table)
+                    .drop_column(Urls::OriginalUrlHash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// URL hash as computed when this migration was written
+///
+/// A private copy so later changes to the application's hashing do not
+/// change what this migration writes.
+fn hash(url: &str) -> String {
+    Sha256::digest(normalize(url).as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Lowercase scheme and host, and write an empty path as `/`
+fn normalize(url: &str) -> String {
+    let url = url.trim();
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+
+    let (authority, tail) = rest
+        .find(['/', '?', '#'])
+        .map_or((rest, ""), |i| rest.split_at(i));
+    let tail = if tail.starts_with('/') {
+        tail.to_string()
+    } else {
+        format!("/{}", tail)
+    };
+
+    format!(
+        "{}://{}{}",
+        scheme.to_lowercase(),
+        authority.to_lowercase(),
+        tail
+    )
+}
+
+#[derive(DeriveIden)]
+enum Urls {
+    Table,
+    Id,
+    Domain,
+    OriginalUrl,
+    OriginalUrlHash,
+}
@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UrlRevisions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UrlRevisions::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UrlRevisions::UrlId).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(UrlRevisions::Domain)
+                            .string_len(255)
+                            .not_null()
+                            .default(""),
+                    )
+                    .col(
+                        ColumnDef::new(UrlRevisions::ShortCode)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(UrlRevisions::Action)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(UrlRevisions::Actor)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(UrlRevisions::OldValue).json().null())
+                    .col(ColumnDef::new(UrlRevisions::NewValue).json().null())
+                    .col(
+                        ColumnDef::new(UrlRevisions::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Revisions are listed by short code, and stay after the URL is deleted
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_url_revisions_domain_short_code")
+                    .table(UrlRevisions::Table)
+                    .col(UrlRevisions::Domain)
+                    .col(UrlRevisions::ShortCode)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_url_revisions_url_id")
+                    .table(UrlRevisions::Table)
+                    .col(UrlRevisions::UrlId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UrlRevisions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UrlRevisions {
+    Table,
+    Id,
+    UrlId,
+    Domain,
+    ShortCode,
+    Action,
+    Actor,
+    OldValue,
+    NewValue,
+    CreatedAt,
+}
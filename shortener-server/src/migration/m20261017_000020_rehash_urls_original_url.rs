@@ -0,0 +1,104 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, TryGetable};
+use sha2::{Digest, Sha256};
+use url::{Position, Url};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Hash existing destinations the way the destination checks normalize them
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        let rows = db
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .columns([Urls::Id, Urls::OriginalUrl])
+                        .from(Urls::Table),
+                ),
+            )
+            .await?;
+        for row in rows {
+            let id = i64::try_get(&row, "", "id").map_err(DbErr::from)?;
+            let original_url = String::try_get(&row, "", "original_url").map_err(DbErr::from)?;
+
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Urls::Table)
+                        .value(Urls::OriginalUrlHash, hash(&original_url))
+                        .and_where(Expr::col(Urls::Id).eq(id))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // The previous hashes are not restored
+        Ok(())
+    }
+}
+
+/// URL hash as computed when this migration was written
+///
+/// A private copy so later changes to the application's hashing do not
+/// change what this migration writes.
+fn hash(url: &str) -> String {
+    let url = url.trim();
+    let normalized = normalize(url).unwrap_or_else(|| url.to_string());
+
+    Sha256::digest(normalized.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Lowercase scheme and host in punycode, no default port, empty path kept
+///
+/// `None` for URLs the destination checks reject. Those only accept new
+/// links, so the homograph and script URL checks are left out here.
+fn normalize(url: &str) -> Option<String> {
+    if url.is_empty()
+        || url.len() > 2048
+        || url.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return None;
+    }
+
+    let parsed = Url::parse(url).ok()?;
+    if !matches!(parsed.scheme(), "http" | "https")
+        || !parsed.username().is_empty()
+        || parsed.password().is_some()
+        || parsed.host_str().is_none_or(str::is_empty)
+    {
+        return None;
+    }
+
+    let had_path = url.split_once("://").is_some_and(|(_, rest)| {
+        rest.find(['/', '?', '#'])
+            .is_some_and(|i| rest[i..].starts_with('/'))
+    });
+    if !had_path && parsed.path() == "/" {
+        return Some(format!(
+            "{}{}",
+            &parsed[..Position::BeforePath],
+            &parsed[Position::AfterPath..]
+        ));
+    }
+
+    Some(parsed.to_string())
+}
+
+#[derive(DeriveIden)]
+enum Urls {
+    Table,
+    Id,
+    OriginalUrl,
+    OriginalUrlHash,
+}
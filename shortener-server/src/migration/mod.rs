@@ -8,9 +8,51 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20240101_000001_create_urls_table::Migration),
             Box::new(m20240101_000002_create_histories_table::Migration),
+            Box::new(m20261017_000001_add_urls_expires_at::Migration),
+            Box::new(m20261017_000002_add_urls_click_quota::Migration),
+            Box::new(m20261017_000003_add_urls_password_hash::Migration),
+            Box::new(m20261017_000004_add_urls_redirect_type::Migration),
+            Box::new(m20261017_000005_add_urls_active_window::Migration),
+            Box::new(m20261017_000006_add_urls_targeting_rules::Migration),
+            Box::new(m20261017_000007_add_histories_targeting_rule::Migration),
+            Box::new(m20261017_000008_add_urls_variants::Migration),
+            Box::new(m20261017_000009_add_histories_variant::Migration),
+            Box::new(m20261017_000010_add_urls_passthrough::Migration),
+            Box::new(m20261017_000011_create_tags_tables::Migration),
+            Box::new(m20261017_000012_add_urls_domain::Migration),
+            Box::new(m20261017_000013_create_code_sequences_table::Migration),
+            Box::new(m20261017_000014_add_urls_original_url_hash::Migration),
+            Box::new(m20261017_000015_add_urls_health_check::Migration),
+            Box::new(m20261017_000016_create_url_revisions_table::Migration),
+            Box::new(m20261017_000017_add_urls_deleted_at::Migration),
+            Box::new(m20261017_000018_add_urls_metadata::Migration),
+            Box::new(m20261017_000019_create_users_table::Migration),
+            Box::new(m20261017_000020_rehash_urls_original_url::Migration),
+            Box::new(m20261017_000021_rehash_urls_empty_path::Migration),
         ]
     }
 }
 
 mod m20240101_000001_create_urls_table;
 mod m20240101_000002_create_histories_table;
+mod m20261017_000001_add_urls_expires_at;
+mod m20261017_000002_add_urls_click_quota;
+mod m20261017_000003_add_urls_password_hash;
+mod m20261017_000004_add_urls_redirect_type;
+mod m20261017_000005_add_urls_active_window;
+mod m20261017_000006_add_urls_targeting_rules;
+mod m20261017_000007_add_histories_targeting_rule;
+mod m20261017_000008_add_urls_variants;
+mod m20261017_000009_add_histories_variant;
+mod m20261017_000010_add_urls_passthrough;
+mod m20261017_000011_create_tags_tables;
+mod m20261017_000012_add_urls_domain;
+mod m20261017_000013_create_code_sequences_table;
+mod m20261017_000014_add_urls_original_url_hash;
+mod m20261017_000015_add_urls_health_check;
+mod m20261017_000016_create_url_revisions_table;
+mod m20261017_000017_add_urls_deleted_at;
+mod m20261017_000018_add_urls_metadata;
+mod m20261017_000019_create_users_table;
+mod m20261017_000020_rehash_urls_original_url;
+mod m20261017_000021_rehash_urls_empty_path;
@@ -31,6 +31,12 @@ pub struct Model {
     pub os: Option<String>,
     pub browser: Option<String>,
 
+    /// Index of the targeting rule that chose the destination (none for `original_url`)
+    pub targeting_rule: Option<i32>,
+
+    /// Name of the A/B variant that was served
+    pub variant: Option<String>,
+
     #[sea_orm(indexed)]
     pub accessed_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
@@ -77,6 +83,8 @@ mod tests {
             device_type: Some("Desktop".to_string()),
             os: Some("Windows".to_string()),
             browser: Some("Chrome".to_string()),
+            targeting_rule: None,
+            variant: None,
             accessed_at: chrono::Utc::now(),
             created_at: chrono::Utc::now(),
         };
@@ -105,6 +113,8 @@ mod tests {
             device_type: None,
             os: None,
             browser: None,
+            targeting_rule: None,
+            variant: None,
             accessed_at: chrono::Utc::now(),
             created_at: chrono::Utc::now(),
         };
@@ -132,6 +142,8 @@ mod tests {
             device_type: Some("Desktop".to_string()),
             os: Some("Windows".to_string()),
             browser: Some("Chrome".to_string()),
+            targeting_rule: None,
+            variant: None,
             accessed_at: now,
             created_at: now,
         };
@@ -157,6 +169,8 @@ mod tests {
             device_type: Some("Desktop".to_string()),
             os: Some("Windows".to_string()),
             browser: Some("Chrome".to_string()),
+            targeting_rule: None,
+            variant: None,
             accessed_at: chrono::Utc::now(),
             created_at: chrono::Utc::now(),
         };
@@ -187,6 +201,8 @@ mod tests {
             device_type: None,
             os: None,
             browser: None,
+            targeting_rule: None,
+            variant: None,
             accessed_at: chrono::Utc::now(),
             created_at: chrono::Utc::now(),
         };
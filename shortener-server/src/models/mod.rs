@@ -1,5 +1,15 @@
+pub mod code_sequence;
 pub mod history;
+pub mod tag;
 pub mod url;
+pub mod url_revision;
+pub mod url_tag;
+pub mod user;
 
+pub use code_sequence::Entity as CodeSequenceEntity;
 pub use history::Entity as HistoryEntity;
+pub use tag::Entity as TagEntity;
 pub use url::Entity as UrlEntity;
+pub use url_revision::Entity as UrlRevisionEntity;
+pub use url_tag::Entity as UrlTagEntity;
+pub use user::Entity as UserEntity;
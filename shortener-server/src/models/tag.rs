@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Tag entity model
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    #[sea_orm(unique, indexed)]
+    pub name: String,
+
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::url_tag::Entity")]
+    UrlTag,
+}
+
+impl Related<super::url_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UrlTag.def()
+    }
+}
+
+impl Related<super::url::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::url_tag::Relation::Url.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::url_tag::Relation::Tag.def().rev())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
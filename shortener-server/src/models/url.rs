@@ -1,3 +1,7 @@
+use crate::domains::LinkKey;
+use crate::metadata::Metadata;
+use crate::targeting::TargetingRules;
+use crate::variants::Variants;
 use chrono::{DateTime, Utc};
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -9,47 +13,188 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
 
-    #[sea_orm(unique, indexed)]
+    /// Host of the short domain the code belongs to (empty for the default domain)
+    #[serde(default)]
+    pub domain: String,
+
+    /// Unique per domain
+    #[sea_orm(indexed)]
     pub short_code: String,
 
     pub original_url: String,
 
+    /// SHA-256 of the normalized `original_url`, used to find duplicates
+    #[serde(default)]
+    #[sea_orm(indexed)]
+    pub original_url_hash: String,
+
     pub description: Option<String>,
 
     #[sea_orm(indexed)]
     pub status: i32,
 
+    #[sea_orm(indexed)]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Start of the activation window (link is scheduled until then)
+    pub active_from: Option<DateTime<Utc>>,
+
+    /// End of the activation window (link has ended from then on)
+    pub active_until: Option<DateTime<Utc>>,
+
+    pub max_clicks: Option<i64>,
+
+    #[serde(default)]
+    pub click_count: i64,
+
+    /// Argon2 hash of the link password (password-protected links only)
+    pub password_hash: Option<String>,
+
+    /// HTTP redirect status for this link (server default when unset)
+    pub redirect_type: Option<i32>,
+
+    /// Ordered device/OS destination overrides
+    pub targeting_rules: Option<TargetingRules>,
+
+    /// Weighted A/B destinations used when no targeting rule matches
+    pub variants: Option<Variants>,
+
+    /// Keep returning visitors on the variant they were first assigned
+    #[serde(default)]
+    pub sticky_variants: bool,
+
+    /// Append the incoming query string to the destination (server default when unset)
+    pub forward_query: Option<bool>,
+
+    /// Append the path after the short code to the destination (server default when unset)
+    pub forward_path: Option<bool>,
+
+    /// Free-form attributes set by API clients
+    pub metadata: Option<Metadata>,
+
+    /// When the destination was last checked by the health checker
+    pub last_checked_at: Option<DateTime<Utc>>,
+
+    /// HTTP status of the last check (none when the request failed)
+    pub last_status: Option<i32>,
+
+    /// Why the last check failed (none when the destination answered fine)
+    pub last_error: Option<String>,
+
+    /// Consecutive failed checks
+    #[serde(default)]
+    pub check_failures: i32,
+
+    /// When the link was moved to the trash (none for live links)
+    #[sea_orm(indexed)]
+    pub deleted_at: Option<DateTime<Utc>>,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Model {
+    /// Domain and short code identifying the link
+    pub fn link_key(&self) -> LinkKey {
+        LinkKey::new(self.domain.clone(), self.short_code.clone())
+    }
+
+    /// Whether the link is past its `expires_at` (or already flagged as expired)
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.status == UrlStatus::Expired as i32 || self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    /// Whether the activation window has not started yet
+    pub fn is_scheduled(&self, now: DateTime<Utc>) -> bool {
+        self.active_from.is_some_and(|at| at > now)
+    }
+
+    /// Whether the activation window is over
+    pub fn has_ended(&self, now: DateTime<Utc>) -> bool {
+        self.active_until.is_some_and(|at| at <= now)
+    }
+
+    /// Whether visitors must enter a password before being redirected
+    pub fn is_password_protected(&self) -> bool {
+        self.password_hash.is_some()
+    }
+
+    /// Whether the last health check of the destination failed
+    pub fn is_broken(&self) -> bool {
+        self.last_error.is_some()
+    }
+
+    /// Whether the link is in the trash
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Whether the link has used up its `max_clicks` quota
+    pub fn is_quota_exhausted(&self) -> bool {
+        self.max_clicks.is_some_and(|max| self.click_count >= max)
+    }
+}
+
 /// URL status enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UrlStatus {
     Enabled = 0,
     Disabled = 1,
+    Expired = 2,
 }
 
-impl From<i32> for UrlStatus {
-    fn from(value: i32) -> Self {
+impl UrlStatus {
+    /// The status stored as `value`, `None` for unknown values
+    pub fn from_value(value: i32) -> Option<Self> {
         match value {
-            0 => UrlStatus::Enabled,
-            1 => UrlStatus::Disabled,
-            _ => UrlStatus::Enabled, // Default to Enabled
+            0 => Some(UrlStatus::Enabled),
+            1 => Some(UrlStatus::Disabled),
+            2 => Some(UrlStatus::Expired),
+            _ => None,
         }
     }
 }
 
+impl From<i32> for UrlStatus {
+    fn from(value: i32) -> Self {
+        Self::from_value(value).unwrap_or(UrlStatus::Enabled) // Default to Enabled
+    }
+}
+
 impl From<UrlStatus> for i32 {
     fn from(status: UrlStatus) -> Self {
         status as i32
     }
 }
 
+/// HTTP status used when redirecting to the original URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedirectType {
+    MovedPermanently = 301,
+    Found = 302,
+    TemporaryRedirect = 307,
+    PermanentRedirect = 308,
+}
+
+impl RedirectType {
+    /// Parse an HTTP status code into a redirect type
+    pub fn from_code(code: i32) -> Option<Self> {
+        match code {
+            301 => Some(RedirectType::MovedPermanently),
+            302 => Some(RedirectType::Found),
+            307 => Some(RedirectType::TemporaryRedirect),
+            308 => Some(RedirectType::PermanentRedirect),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::history::Entity")]
     History,
+    #[sea_orm(has_many = "super::url_tag::Entity")]
+    UrlTag,
 }
 
 impl Related<super::history::Entity> for Entity {
@@ -58,6 +203,22 @@ impl Related<super::history::Entity> for Entity {
     }
 }
 
+impl Related<super::url_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UrlTag.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::url_tag::Relation::Tag.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::url_tag::Relation::Url.def().rev())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 #[cfg(test)]
@@ -68,10 +229,15 @@ mod tests {
     fn test_url_status_conversion() {
         assert_eq!(UrlStatus::from(0), UrlStatus::Enabled);
         assert_eq!(UrlStatus::from(1), UrlStatus::Disabled);
+        assert_eq!(UrlStatus::from(2), UrlStatus::Expired);
         assert_eq!(UrlStatus::from(999), UrlStatus::Enabled); // Unknown defaults to Enabled
+        assert_eq!(UrlStatus::from_value(1), Some(UrlStatus::Disabled));
+        assert_eq!(UrlStatus::from_value(3), None);
+        assert_eq!(UrlStatus::from_value(-1), None);
 
         assert_eq!(i32::from(UrlStatus::Enabled), 0);
         assert_eq!(i32::from(UrlStatus::Disabled), 1);
+        assert_eq!(i32::from(UrlStatus::Expired), 2);
     }
 
     #[test]
@@ -92,10 +258,30 @@ mod tests {
     fn test_url_model_clone() {
         let model = Model {
             id: 1,
+            domain: String::new(),
             short_code: "test123".to_string(),
             original_url: "https://example.com".to_string(),
+            original_url_hash: String::new(),
             description: Some("Test".to_string()),
             status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            active_from: None,
+            active_until: None,
+            max_clicks: None,
+            click_count: 0,
+            password_hash: None,
+            redirect_type: None,
+            targeting_rules: None,
+            variants: None,
+            sticky_variants: false,
+            forward_query: None,
+            forward_path: None,
+            metadata: None,
+            last_checked_at: None,
+            last_status: None,
+            last_error: None,
+            check_failures: 0,
+            deleted_at: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -111,20 +297,60 @@ mod tests {
         let now = chrono::Utc::now();
         let model1 = Model {
             id: 1,
+            domain: String::new(),
             short_code: "test123".to_string(),
             original_url: "https://example.com".to_string(),
+            original_url_hash: String::new(),
             description: Some("Test".to_string()),
             status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            active_from: None,
+            active_until: None,
+            max_clicks: None,
+            click_count: 0,
+            password_hash: None,
+            redirect_type: None,
+            targeting_rules: None,
+            variants: None,
+            sticky_variants: false,
+            forward_query: None,
+            forward_path: None,
+            metadata: None,
+            last_checked_at: None,
+            last_status: None,
+            last_error: None,
+            check_failures: 0,
+            deleted_at: None,
             created_at: now,
             updated_at: now,
         };
 
         let model2 = Model {
             id: 1,
+            domain: String::new(),
             short_code: "test123".to_string(),
             original_url: "https://example.com".to_string(),
+            original_url_hash: String::new(),
             description: Some("Test".to_string()),
             status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            active_from: None,
+            active_until: None,
+            max_clicks: None,
+            click_count: 0,
+            password_hash: None,
+            redirect_type: None,
+            targeting_rules: None,
+            variants: None,
+            sticky_variants: false,
+            forward_query: None,
+            forward_path: None,
+            metadata: None,
+            last_checked_at: None,
+            last_status: None,
+            last_error: None,
+            check_failures: 0,
+            deleted_at: None,
             created_at: now,
             updated_at: now,
         };
@@ -136,10 +362,30 @@ mod tests {
     fn test_url_model_with_none_description() {
         let model = Model {
             id: 1,
+            domain: String::new(),
             short_code: "test123".to_string(),
             original_url: "https://example.com".to_string(),
+            original_url_hash: String::new(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            active_from: None,
+            active_until: None,
+            max_clicks: None,
+            click_count: 0,
+            password_hash: None,
+            redirect_type: None,
+            targeting_rules: None,
+            variants: None,
+            sticky_variants: false,
+            forward_query: None,
+            forward_path: None,
+            metadata: None,
+            last_checked_at: None,
+            last_status: None,
+            last_error: None,
+            check_failures: 0,
+            deleted_at: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -147,6 +393,161 @@ mod tests {
         assert!(model.description.is_none());
     }
 
+    #[test]
+    fn test_url_model_is_expired() {
+        let now = chrono::Utc::now();
+        let mut model = Model {
+            id: 1,
+            domain: String::new(),
+            short_code: "test123".to_string(),
+            original_url: "https://example.com".to_string(),
+            original_url_hash: String::new(),
+            description: None,
+            status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            active_from: None,
+            active_until: None,
+            max_clicks: None,
+            click_count: 0,
+            password_hash: None,
+            redirect_type: None,
+            targeting_rules: None,
+            variants: None,
+            sticky_variants: false,
+            forward_query: None,
+            forward_path: None,
+            metadata: None,
+            last_checked_at: None,
+            last_status: None,
+            last_error: None,
+            check_failures: 0,
+            deleted_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(!model.is_expired(now));
+
+        model.expires_at = Some(now + chrono::Duration::hours(1));
+        assert!(!model.is_expired(now));
+
+        model.expires_at = Some(now - chrono::Duration::seconds(1));
+        assert!(model.is_expired(now));
+
+        model.expires_at = None;
+        model.status = UrlStatus::Expired as i32;
+        assert!(model.is_expired(now));
+    }
+
+    #[test]
+    fn test_url_model_activation_window() {
+        let now = chrono::Utc::now();
+        let mut model = Model {
+            id: 1,
+            domain: String::new(),
+            short_code: "test123".to_string(),
+            original_url: "https://example.com".to_string(),
+            original_url_hash: String::new(),
+            description: None,
+            status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            active_from: None,
+            active_until: None,
+            max_clicks: None,
+            click_count: 0,
+            password_hash: None,
+            redirect_type: None,
+            targeting_rules: None,
+            variants: None,
+            sticky_variants: false,
+            forward_query: None,
+            forward_path: None,
+            metadata: None,
+            last_checked_at: None,
+            last_status: None,
+            last_error: None,
+            check_failures: 0,
+            deleted_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(!model.is_scheduled(now));
+        assert!(!model.has_ended(now));
+
+        model.active_from = Some(now + chrono::Duration::hours(1));
+        assert!(model.is_scheduled(now));
+
+        model.active_from = Some(now);
+        assert!(!model.is_scheduled(now));
+
+        model.active_until = Some(now + chrono::Duration::hours(1));
+        assert!(!model.has_ended(now));
+
+        model.active_until = Some(now);
+        assert!(model.has_ended(now));
+    }
+
+    #[test]
+    fn test_url_model_is_quota_exhausted() {
+        let now = chrono::Utc::now();
+        let mut model = Model {
+            id: 1,
+            domain: String::new(),
+            short_code: "test123".to_string(),
+            original_url: "https://example.com".to_string(),
+            original_url_hash: String::new(),
+            description: None,
+            status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            active_from: None,
+            active_until: None,
+            max_clicks: None,
+            click_count: 10,
+            password_hash: None,
+            redirect_type: None,
+            targeting_rules: None,
+            variants: None,
+            sticky_variants: false,
+            forward_query: None,
+            forward_path: None,
+            metadata: None,
+            last_checked_at: None,
+            last_status: None,
+            last_error: None,
+            check_failures: 0,
+            deleted_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(!model.is_quota_exhausted());
+
+        model.max_clicks = Some(11);
+        assert!(!model.is_quota_exhausted());
+
+        model.max_clicks = Some(10);
+        assert!(model.is_quota_exhausted());
+    }
+
+    #[test]
+    fn test_redirect_type_from_code() {
+        assert_eq!(
+            RedirectType::from_code(301),
+            Some(RedirectType::MovedPermanently)
+        );
+        assert_eq!(RedirectType::from_code(302), Some(RedirectType::Found));
+        assert_eq!(
+            RedirectType::from_code(307),
+            Some(RedirectType::TemporaryRedirect)
+        );
+        assert_eq!(
+            RedirectType::from_code(308),
+            Some(RedirectType::PermanentRedirect)
+        );
+        assert_eq!(RedirectType::from_code(303), None);
+        assert_eq!(RedirectType::from_code(200), None);
+
+        assert_eq!(RedirectType::PermanentRedirect as i32, 308);
+    }
+
     #[test]
     fn test_url_status_serialization() {
         let status = UrlStatus::Enabled;
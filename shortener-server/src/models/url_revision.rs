@@ -0,0 +1,103 @@
+use crate::metadata::Metadata;
+use crate::targeting::TargetingRules;
+use crate::variants::Variants;
+use chrono::{DateTime, Utc};
+use sea_orm::FromJsonQueryResult;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Change made to a URL, recorded with the state before and after it
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "url_revisions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// Not a foreign key: revisions outlive the URL they belong to
+    #[sea_orm(indexed)]
+    pub url_id: i64,
+
+    pub domain: String,
+
+    pub short_code: String,
+
+    pub action: RevisionAction,
+
+    /// Username, or `api-key` / `system` for changes not made by a user
+    pub actor: String,
+
+    /// State before the change (none for `create`)
+    pub old_value: Option<LinkSnapshot>,
+
+    /// State after the change (none for `delete`)
+    pub new_value: Option<LinkSnapshot>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Kind of change a revision records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionAction {
+    #[sea_orm(string_value = "create")]
+    Create,
+    #[sea_orm(string_value = "update")]
+    Update,
+    #[sea_orm(string_value = "delete")]
+    Delete,
+    #[sea_orm(string_value = "restore")]
+    Restore,
+}
+
+/// Editable state of a URL, stored as a JSON column
+///
+/// The password hash is left out; only whether the link had a password is kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct LinkSnapshot {
+    pub original_url: String,
+    pub description: Option<String>,
+    pub status: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i64>,
+    pub password_protected: bool,
+    pub redirect_type: Option<i32>,
+    pub targeting_rules: Option<TargetingRules>,
+    pub variants: Option<Variants>,
+    pub sticky_variants: bool,
+    pub forward_query: Option<bool>,
+    pub forward_path: Option<bool>,
+    pub metadata: Option<Metadata>,
+    pub tags: Vec<String>,
+}
+
+impl LinkSnapshot {
+    /// Snapshot of a URL carrying the given tags
+    pub fn of(url: &super::url::Model, tags: Vec<String>) -> Self {
+        Self {
+            original_url: url.original_url.clone(),
+            description: url.description.clone(),
+            status: url.status,
+            expires_at: url.expires_at,
+            active_from: url.active_from,
+            active_until: url.active_until,
+            max_clicks: url.max_clicks,
+            password_protected: url.is_password_protected(),
+            redirect_type: url.redirect_type,
+            targeting_rules: url.targeting_rules.clone(),
+            variants: url.variants.clone(),
+            sticky_variants: url.sticky_variants,
+            forward_query: url.forward_query,
+            forward_path: url.forward_path,
+            metadata: url.metadata.clone(),
+            tags,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Join table between URLs and tags
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "url_tags")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub url_id: i64,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub tag_id: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::url::Entity",
+        from = "Column::UrlId",
+        to = "super::url::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Url,
+    #[sea_orm(
+        belongs_to = "super::tag::Entity",
+        from = "Column::TagId",
+        to = "super::tag::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Tag,
+}
+
+impl Related<super::url::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Url.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tag.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Account that can log in to the admin API
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "users")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    #[sea_orm(unique, indexed)]
+    pub username: String,
+
+    /// Argon2 hash of the password
+    #[serde(skip_serializing)]
+    pub password_hash: String,
+
+    pub display_name: Option<String>,
+
+    pub email: Option<String>,
+
+    pub role: UserRole,
+
+    /// Disabled users cannot log in
+    pub disabled: bool,
+
+    pub last_login_at: Option<DateTime<Utc>>,
+
+    pub created_at: DateTime<Utc>,
+
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What a user is allowed to do
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Manages links and other users
+    #[sea_orm(string_value = "admin")]
+    Admin,
+    /// Manages links
+    #[default]
+    #[sea_orm(string_value = "user")]
+    User,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
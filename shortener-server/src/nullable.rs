@@ -0,0 +1,52 @@
+//! Serde helpers for update fields that `null` clears
+//!
+//! Use with `#[serde(default, with = "crate::nullable")]` on an
+//! `Option<Option<T>>` field: a missing field is `None` (left untouched),
+//! `null` is `Some(None)` (cleared) and a value is `Some(Some(value))`.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Read a present field, `null` included, as `Some`
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Write a cleared field as `null`; pair with `skip_serializing_if = "Option::is_none"`
+pub fn serialize<T, S>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match value {
+        Some(inner) => inner.serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Update {
+        #[serde(default, with = "super", skip_serializing_if = "Option::is_none")]
+        max_clicks: Option<Option<i64>>,
+    }
+
+    #[test]
+    fn test_nullable() {
+        let parse = |json: &str| serde_json::from_str::<Update>(json).unwrap().max_clicks;
+        assert_eq!(parse("{}"), None);
+        assert_eq!(parse(r#"{"max_clicks":null}"#), Some(None));
+        assert_eq!(parse(r#"{"max_clicks":5}"#), Some(Some(5)));
+
+        let write = |max_clicks| serde_json::to_string(&Update { max_clicks }).unwrap();
+        assert_eq!(write(None), "{}");
+        assert_eq!(write(Some(None)), r#"{"max_clicks":null}"#);
+        assert_eq!(write(Some(Some(5))), r#"{"max_clicks":5}"#);
+    }
+}
@@ -0,0 +1,58 @@
+//! Argon2 password hashing shared by admin accounts and password-protected links
+
+use crate::errors::ServiceError;
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+
+/// Hash a password using argon2
+pub fn hash_password(password: &str) -> Result<String, ServiceError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let argon2 = Argon2::default();
+
+    let password_hash = argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| ServiceError::Internal(format!("Failed to hash password: {}", e)))?
+        .to_string();
+
+    Ok(password_hash)
+}
+
+/// Verify a password against a hash using argon2
+pub fn verify_password(password: &str, hash: &str) -> Result<bool, ServiceError> {
+    let parsed_hash = PasswordHash::new(hash)
+        .map_err(|e| ServiceError::Internal(format!("Failed to parse password hash: {}", e)))?;
+
+    let argon2 = Argon2::default();
+
+    Ok(argon2
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify_password() {
+        let password = "test_password_123";
+        let hash = hash_password(password).unwrap();
+
+        assert!(verify_password(password, &hash).unwrap());
+        assert!(!verify_password("wrong_password", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_hash_password_is_salted() {
+        let first = hash_password("same").unwrap();
+        let second = hash_password("same").unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_verify_password_invalid_hash() {
+        assert!(verify_password("password", "not-a-hash").is_err());
+    }
+}
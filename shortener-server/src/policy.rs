@@ -0,0 +1,316 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use url::Url;
+
+/// Prefix of rule lines holding a regular expression instead of a domain
+pub const REGEX_PREFIX: &str = "regex:";
+
+/// Domains and URL patterns of one blocklist or allowlist file
+///
+/// Each line holds a domain, matching the domain and all of its subdomains,
+/// or `regex:` followed by a pattern matched against the whole URL. Blank
+/// lines and lines starting with `#` are skipped.
+#[derive(Debug, Default)]
+pub struct RuleList {
+    domains: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+impl RuleList {
+    /// Parse the content of a rule file
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut list = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(pattern) = line.strip_prefix(REGEX_PREFIX) {
+                let regex = Regex::new(pattern.trim())
+                    .map_err(|e| format!("line {}: invalid regex: {}", number + 1, e))?;
+                list.patterns.push(regex);
+            } else {
+                list.domains.insert(
+                    normalize_domain(line)
+                        .ok_or_else(|| format!("line {}: invalid domain: {}", number + 1, line))?,
+                );
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Read and parse a rule file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Rule matching a URL with the given (lowercase, punycode) host
+    pub fn find_match(&self, host: &str, url: &str) -> Option<String> {
+        let mut suffix = host;
+        loop {
+            if self.domains.contains(suffix) {
+                return Some(suffix.to_string());
+            }
+            match suffix.split_once('.') {
+                Some((_, parent)) => suffix = parent,
+                None => break,
+            }
+        }
+
+        self.patterns
+            .iter()
+            .find(|regex| regex.is_match(url))
+            .map(|regex| format!("{}{}", REGEX_PREFIX, regex.as_str()))
+    }
+
+    /// Whether the list has no rules
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty() && self.patterns.is_empty()
+    }
+}
+
+/// Lowercase punycode form of a rule domain; `*.` and surrounding dots are dropped
+fn normalize_domain(domain: &str) -> Option<String> {
+    let domain = domain.trim_start_matches("*.").trim_matches('.');
+    if domain.is_empty() || domain.contains(['/', ':', ' ']) {
+        return None;
+    }
+
+    idna::domain_to_ascii(domain).ok()
+}
+
+/// Number of rules in the active blocklist and allowlist
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PolicyStats {
+    pub blocked_domains: usize,
+    pub blocked_patterns: usize,
+    pub allowed_domains: usize,
+    pub allowed_patterns: usize,
+}
+
+/// Rules loaded from the policy files, with the modification times they were read at
+#[derive(Debug, Default)]
+struct PolicyRules {
+    blocked: RuleList,
+    allowed: RuleList,
+    modified: [Option<SystemTime>; 2],
+}
+
+/// Destination policy checking URLs against a blocklist and an allowlist
+///
+/// Allowlist entries take precedence, so a subdomain or URL pattern can be
+/// let through from a blocked domain. The rules are swapped atomically on
+/// reload; a file that fails to load keeps the previous rules in place.
+#[derive(Debug)]
+pub struct DestinationPolicy {
+    blocklist_file: Option<PathBuf>,
+    allowlist_file: Option<PathBuf>,
+    rules: RwLock<Arc<PolicyRules>>,
+}
+
+impl DestinationPolicy {
+    /// Create a policy without rules; call [`reload`](Self::reload) to read the files
+    pub fn new(blocklist_file: Option<&Path>, allowlist_file: Option<&Path>) -> Self {
+        Self {
+            blocklist_file: blocklist_file.map(Path::to_path_buf),
+            allowlist_file: allowlist_file.map(Path::to_path_buf),
+            rules: RwLock::new(Arc::default()),
+        }
+    }
+
+    /// Create a policy and read its files
+    pub fn load(
+        blocklist_file: Option<&Path>,
+        allowlist_file: Option<&Path>,
+    ) -> Result<Self, String> {
+        let policy = Self::new(blocklist_file, allowlist_file);
+        policy.reload()?;
+        Ok(policy)
+    }
+
+    /// Re-read the blocklist and allowlist files
+    pub fn reload(&self) -> Result<PolicyStats, String> {
+        let read = |file: &Option<PathBuf>| match file {
+            Some(path) => RuleList::load(path),
+            None => Ok(RuleList::default()),
+        };
+
+        let rules = PolicyRules {
+            modified: self.modified(),
+            blocked: read(&self.blocklist_file)?,
+            allowed: read(&self.allowlist_file)?,
+        };
+        let stats = stats_of(&rules);
+
+        *self.rules.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(rules);
+
+        Ok(stats)
+    }
+
+    /// Re-read the files if one of them changed since the last load
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(PolicyStats))` - Files changed and were reloaded
+    /// * `Ok(None)` - Nothing changed
+    /// * `Err(String)` - A file could not be read or parsed
+    pub fn reload_if_changed(&self) -> Result<Option<PolicyStats>, String> {
+        if self.current().modified == self.modified() {
+            return Ok(None);
+        }
+
+        self.reload().map(Some)
+    }
+
+    /// Number of rules currently in effect
+    pub fn stats(&self) -> PolicyStats {
+        stats_of(&self.current())
+    }
+
+    /// Why a destination URL is blocked, `None` when it is allowed
+    pub fn check(&self, url: &str) -> Option<String> {
+        let rules = self.current();
+        if rules.blocked.is_empty() {
+            return None;
+        }
+
+        let parsed = Url::parse(url).ok()?;
+        let host = parsed
+            .host_str()
+            .unwrap_or_default()
+            .trim_end_matches('.')
+            .to_lowercase();
+
+        if rules.allowed.find_match(&host, url).is_some() {
+            return None;
+        }
+
+        rules
+            .blocked
+            .find_match(&host, url)
+            .map(|rule| format!("Destination {} is blocked by rule '{}'", host, rule))
+    }
+
+    fn current(&self) -> Arc<PolicyRules> {
+        self.rules.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn modified(&self) -> [Option<SystemTime>; 2] {
+        [&self.blocklist_file, &self.allowlist_file].map(|file| {
+            file.as_ref()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .and_then(|metadata| metadata.modified().ok())
+        })
+    }
+}
+
+fn stats_of(rules: &PolicyRules) -> PolicyStats {
+    PolicyStats {
+        blocked_domains: rules.blocked.domains.len(),
+        blocked_patterns: rules.blocked.patterns.len(),
+        allowed_domains: rules.allowed.domains.len(),
+        allowed_patterns: rules.allowed.patterns.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_domain_and_regex_rules() {
+        let list = RuleList::parse(
+            "# phishing\nEvil.example\n*.bad.test\n\nregex:^https?://[^/]+/login\\.php",
+        )
+        .unwrap();
+
+        assert_eq!(
+            list.find_match("evil.example", "https://evil.example/"),
+            Some("evil.example".to_string())
+        );
+        assert!(
+            list.find_match("www.evil.example", "https://www.evil.example/")
+                .is_some()
+        );
+        assert!(
+            list.find_match("a.b.bad.test", "https://a.b.bad.test/")
+                .is_some()
+        );
+        assert!(
+            list.find_match("notevil.example", "https://notevil.example/")
+                .is_none()
+        );
+        assert_eq!(
+            list.find_match("ok.test", "https://ok.test/login.php"),
+            Some("regex:^https?://[^/]+/login\\.php".to_string())
+        );
+        assert!(list.find_match("ok.test", "https://ok.test/").is_none());
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let error = RuleList::parse("ok.test\nregex:(unclosed").unwrap_err();
+        assert!(error.starts_with("line 2: invalid regex"));
+
+        let error = RuleList::parse("https://evil.example/").unwrap_err();
+        assert!(error.starts_with("line 1: invalid domain"));
+    }
+
+    #[test]
+    fn test_allowlist_overrides_blocklist() {
+        let mut blocklist = tempfile::NamedTempFile::new().unwrap();
+        writeln!(blocklist, "example.com\nпример.рф").unwrap();
+        let mut allowlist = tempfile::NamedTempFile::new().unwrap();
+        writeln!(allowlist, "docs.example.com").unwrap();
+
+        let policy =
+            DestinationPolicy::load(Some(blocklist.path()), Some(allowlist.path())).unwrap();
+
+        assert!(policy.check("https://example.com/").is_some());
+        assert!(policy.check("https://WWW.Example.com/").is_some());
+        assert!(policy.check("https://docs.example.com/page").is_none());
+        assert!(policy.check("https://xn--e1afmkfd.xn--p1ai/").is_some());
+        assert!(policy.check("https://example.org/").is_none());
+        assert_eq!(
+            policy.stats(),
+            PolicyStats {
+                blocked_domains: 2,
+                blocked_patterns: 0,
+                allowed_domains: 1,
+                allowed_patterns: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reload() {
+        let mut blocklist = tempfile::NamedTempFile::new().unwrap();
+        writeln!(blocklist, "old.test").unwrap();
+
+        let policy = DestinationPolicy::load(Some(blocklist.path()), None).unwrap();
+        assert!(policy.check("https://old.test/").is_some());
+        assert!(policy.reload_if_changed().unwrap().is_none());
+
+        std::fs::write(blocklist.path(), "new.test\n").unwrap();
+        let stats = policy.reload().unwrap();
+        assert_eq!(stats.blocked_domains, 1);
+        assert!(policy.check("https://old.test/").is_none());
+        assert!(policy.check("https://new.test/").is_some());
+
+        // A broken file keeps the previous rules
+        std::fs::write(blocklist.path(), "regex:[\n").unwrap();
+        assert!(policy.reload().is_err());
+        assert!(policy.check("https://new.test/").is_some());
+    }
+}
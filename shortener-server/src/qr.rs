@@ -0,0 +1,325 @@
+use qrcode::{Color, EcLevel, QrCode};
+use serde::Deserialize;
+use std::fmt::Write;
+
+/// Smallest accepted image size in pixels
+pub const MIN_QR_SIZE: u32 = 64;
+
+/// Largest accepted image size in pixels
+pub const MAX_QR_SIZE: u32 = 2048;
+
+/// Largest accepted quiet zone in modules
+pub const MAX_QR_MARGIN: u32 = 16;
+
+/// Image format of a rendered QR code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QrFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+impl QrFormat {
+    /// MIME type of the rendered image
+    pub fn content_type(self) -> &'static str {
+        match self {
+            QrFormat::Png => "image/png",
+            QrFormat::Svg => "image/svg+xml",
+        }
+    }
+}
+
+/// Error correction level: how much of the code may be damaged and still scan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QrErrorCorrection {
+    /// About 7% recoverable
+    L,
+    /// About 15% recoverable
+    #[default]
+    M,
+    /// About 25% recoverable
+    Q,
+    /// About 30% recoverable
+    H,
+}
+
+impl From<QrErrorCorrection> for EcLevel {
+    fn from(level: QrErrorCorrection) -> Self {
+        match level {
+            QrErrorCorrection::L => EcLevel::L,
+            QrErrorCorrection::M => EcLevel::M,
+            QrErrorCorrection::Q => EcLevel::Q,
+            QrErrorCorrection::H => EcLevel::H,
+        }
+    }
+}
+
+/// Rendering options, taken from the query string
+#[derive(Debug, Clone, Deserialize)]
+pub struct QrOptions {
+    #[serde(default)]
+    pub format: QrFormat,
+    /// Image width and height in pixels
+    #[serde(default = "default_size")]
+    pub size: u32,
+    /// Quiet zone around the code in modules
+    #[serde(default = "default_margin")]
+    pub margin: u32,
+    /// Error correction level (l, m, q or h)
+    #[serde(default)]
+    pub ec: QrErrorCorrection,
+    /// Foreground colour as hex (`000000`, `#000` ...)
+    #[serde(default = "default_fg")]
+    pub fg: String,
+    /// Background colour as hex
+    #[serde(default = "default_bg")]
+    pub bg: String,
+}
+
+fn default_size() -> u32 {
+    256
+}
+
+fn default_margin() -> u32 {
+    4
+}
+
+fn default_fg() -> String {
+    "000000".to_string()
+}
+
+fn default_bg() -> String {
+    "ffffff".to_string()
+}
+
+impl Default for QrOptions {
+    fn default() -> Self {
+        Self {
+            format: QrFormat::default(),
+            size: default_size(),
+            margin: default_margin(),
+            ec: QrErrorCorrection::default(),
+            fg: default_fg(),
+            bg: default_bg(),
+        }
+    }
+}
+
+/// Render `data` as a QR code image
+///
+/// PNG modules are scaled by a whole number of pixels, so the image can be
+/// slightly smaller than `size`; SVG output is always exactly `size`.
+pub fn render(data: &str, options: &QrOptions) -> Result<Vec<u8>, String> {
+    if !(MIN_QR_SIZE..=MAX_QR_SIZE).contains(&options.size) {
+        return Err(format!(
+            "size must be between {} and {}",
+            MIN_QR_SIZE, MAX_QR_SIZE
+        ));
+    }
+    if options.margin > MAX_QR_MARGIN {
+        return Err(format!("margin must be at most {}", MAX_QR_MARGIN));
+    }
+    let fg = parse_color(&options.fg)?;
+    let bg = parse_color(&options.bg)?;
+
+    let code = QrCode::with_error_correction_level(data, options.ec.into())
+        .map_err(|e| format!("Failed to encode QR code: {}", e))?;
+    let modules = Modules {
+        width: code.width() as u32,
+        margin: options.margin,
+        colors: code.to_colors(),
+    };
+
+    match options.format {
+        QrFormat::Png => render_png(&modules, options.size, fg, bg),
+        QrFormat::Svg => Ok(render_svg(&modules, options.size, fg, bg).into_bytes()),
+    }
+}
+
+/// Module matrix of an encoded QR code plus its quiet zone
+struct Modules {
+    width: u32,
+    margin: u32,
+    colors: Vec<Color>,
+}
+
+impl Modules {
+    /// Width including the quiet zone on both sides
+    fn total(&self) -> u32 {
+        self.width + 2 * self.margin
+    }
+
+    /// Whether the module at (x, y), counted from the outer edge, is dark
+    fn is_dark(&self, x: u32, y: u32) -> bool {
+        let (Some(x), Some(y)) = (x.checked_sub(self.margin), y.checked_sub(self.margin)) else {
+            return false;
+        };
+        x < self.width
+            && y < self.width
+            && self.colors[(y * self.width + x) as usize] == Color::Dark
+    }
+}
+
+fn render_png(modules: &Modules, size: u32, fg: [u8; 3], bg: [u8; 3]) -> Result<Vec<u8>, String> {
+    let total = modules.total();
+    let scale = (size / total).max(1);
+    let dimension = total * scale;
+
+    let mut pixels = Vec::with_capacity((dimension * dimension * 3) as usize);
+    for y in 0..dimension {
+        for x in 0..dimension {
+            let color = if modules.is_dark(x / scale, y / scale) {
+                fg
+            } else {
+                bg
+            };
+            pixels.extend_from_slice(&color);
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, dimension, dimension);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+
+    Ok(out)
+}
+
+fn render_svg(modules: &Modules, size: u32, fg: [u8; 3], bg: [u8; 3]) -> String {
+    let total = modules.total();
+
+    let mut path = String::new();
+    for y in 0..total {
+        for x in 0..total {
+            if modules.is_dark(x, y) {
+                let _ = write!(path, "M{} {}h1v1h-1z", x, y);
+            }
+        }
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {total} {total}" shape-rendering="crispEdges">
+<rect width="{total}" height="{total}" fill="{bg}"/>
+<path fill="{fg}" d="{path}"/>
+</svg>
+"#,
+        bg = hex(bg),
+        fg = hex(fg),
+    )
+}
+
+/// Parse `rrggbb` or `rgb`, with or without a leading `#`
+fn parse_color(value: &str) -> Result<[u8; 3], String> {
+    let digits = value.trim().trim_start_matches('#');
+    let invalid = || format!("Invalid colour: {} (expected hex like 000000)", value);
+
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let channel = |s: &str| u8::from_str_radix(s, 16).map_err(|_| invalid());
+    match digits.len() {
+        6 => Ok([
+            channel(&digits[0..2])?,
+            channel(&digits[2..4])?,
+            channel(&digits[4..6])?,
+        ]),
+        3 => Ok([
+            channel(&digits[0..1].repeat(2))?,
+            channel(&digits[1..2].repeat(2))?,
+            channel(&digits[2..3].repeat(2))?,
+        ]),
+        _ => Err(invalid()),
+    }
+}
+
+fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff8000").unwrap(), [255, 128, 0]);
+        assert_eq!(parse_color("FF8000").unwrap(), [255, 128, 0]);
+        assert_eq!(parse_color("#fff").unwrap(), [255, 255, 255]);
+        assert!(parse_color("red").is_err());
+        assert!(parse_color("#12345").is_err());
+    }
+
+    #[test]
+    fn test_render_png() {
+        let image = render("https://s.example.com/abc123", &QrOptions::default()).unwrap();
+        assert!(image.starts_with(PNG_SIGNATURE));
+
+        // Width and height are stored big-endian in the IHDR chunk
+        let width = u32::from_be_bytes(image[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(image[20..24].try_into().unwrap());
+        assert_eq!(width, height);
+        assert!(width <= 256 && width > 128);
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let options = QrOptions {
+            format: QrFormat::Svg,
+            size: 300,
+            margin: 0,
+            ec: QrErrorCorrection::H,
+            fg: "#336699".to_string(),
+            bg: "fff".to_string(),
+        };
+        let image =
+            String::from_utf8(render("https://s.example.com/abc", &options).unwrap()).unwrap();
+
+        assert!(image.contains(r#"width="300" height="300""#));
+        assert!(image.contains(r##"fill="#336699""##));
+        assert!(image.contains(r##"fill="#ffffff""##));
+        // Without a margin the finder pattern starts in the corner
+        assert!(image.contains("d=\"M0 0h1v1h-1z"));
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_options() {
+        let render_with = |options: QrOptions| render("https://s.example.com/abc", &options);
+
+        assert!(
+            render_with(QrOptions {
+                size: 10,
+                ..Default::default()
+            })
+            .is_err()
+        );
+        assert!(
+            render_with(QrOptions {
+                margin: 100,
+                ..Default::default()
+            })
+            .is_err()
+        );
+        assert!(
+            render_with(QrOptions {
+                fg: "nope".to_string(),
+                ..Default::default()
+            })
+            .is_err()
+        );
+    }
+}
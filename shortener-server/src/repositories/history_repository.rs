@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, Set,
+    QueryFilter, QueryOrder, QuerySelect, Select, Set,
 };
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +23,8 @@ pub struct CreateHistoryDto {
     pub device_type: Option<String>,
     pub os: Option<String>,
     pub browser: Option<String>,
+    pub targeting_rule: Option<i32>,
+    pub variant: Option<String>,
     pub accessed_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -36,6 +38,7 @@ pub struct HistoryListParams {
     pub short_code: Option<String>,
     pub url_id: Option<i32>,
     pub ip_address: Option<String>,
+    pub variant: Option<String>,
     #[serde(default = "default_sort_by")]
     pub sort_by: Option<String>,
     #[serde(default = "default_order")]
@@ -66,6 +69,7 @@ impl Default for HistoryListParams {
             short_code: None,
             url_id: None,
             ip_address: None,
+            variant: None,
             sort_by: Some("accessed_at".to_string()),
             order: Some("desc".to_string()),
         }
@@ -81,6 +85,23 @@ pub trait HistoryRepository: Send + Sync {
     /// List history records with pagination
     async fn list(&self, params: HistoryListParams) -> Result<(Vec<Model>, u64), DbErr>;
 
+    /// List the records matching the filters of `params` with an ID above `after_id`
+    ///
+    /// Returns at most `limit` records ordered by ID. Paging and sorting
+    /// parameters are ignored.
+    async fn export_batch(
+        &self,
+        params: &HistoryListParams,
+        after_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
+
+    /// Count the filtered records per A/B variant, ordered by variant name
+    async fn count_by_variant(
+        &self,
+        params: HistoryListParams,
+    ) -> Result<Vec<(String, u64)>, DbErr>;
+
     /// Delete multiple history records by IDs
     async fn delete_batch(&self, ids: Vec<i64>) -> Result<u64, DbErr>;
 }
@@ -94,6 +115,26 @@ impl HistoryRepositoryImpl {
     pub fn new(db: DatabaseConnection) -> Self {
         Self { db }
     }
+
+    /// Apply the filters of the list parameters to a query
+    fn filtered(params: &HistoryListParams) -> Select<Entity> {
+        let mut query = Entity::find();
+
+        if let Some(short_code) = &params.short_code {
+            query = query.filter(Column::ShortCode.eq(short_code));
+        }
+        if let Some(url_id) = params.url_id {
+            query = query.filter(Column::UrlId.eq(url_id));
+        }
+        if let Some(ip_address) = &params.ip_address {
+            query = query.filter(Column::IpAddress.eq(ip_address));
+        }
+        if let Some(variant) = &params.variant {
+            query = query.filter(Column::Variant.eq(variant));
+        }
+
+        query
+    }
 }
 
 #[async_trait]
@@ -115,6 +156,8 @@ impl HistoryRepository for HistoryRepositoryImpl {
             device_type: Set(history.device_type),
             os: Set(history.os),
             browser: Set(history.browser),
+            targeting_rule: Set(history.targeting_rule),
+            variant: Set(history.variant),
             accessed_at: Set(history.accessed_at),
             created_at: Set(now),
             ..Default::default()
@@ -124,18 +167,8 @@ impl HistoryRepository for HistoryRepositoryImpl {
     }
 
     async fn list(&self, params: HistoryListParams) -> Result<(Vec<Model>, u64), DbErr> {
-        let mut query = Entity::find();
-
         // Apply filters
-        if let Some(short_code) = params.short_code {
-            query = query.filter(Column::ShortCode.eq(short_code));
-        }
-        if let Some(url_id) = params.url_id {
-            query = query.filter(Column::UrlId.eq(url_id));
-        }
-        if let Some(ip_address) = params.ip_address {
-            query = query.filter(Column::IpAddress.eq(ip_address));
-        }
+        let mut query = Self::filtered(&params);
 
         // Apply sorting
         let sort_column = params.sort_by.as_deref().unwrap_or("accessed_at");
@@ -161,6 +194,41 @@ impl HistoryRepository for HistoryRepositoryImpl {
         Ok((items, total))
     }
 
+    async fn export_batch(
+        &self,
+        params: &HistoryListParams,
+        after_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::filtered(params)
+            .filter(Column::Id.gt(after_id))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+    }
+
+    async fn count_by_variant(
+        &self,
+        params: HistoryListParams,
+    ) -> Result<Vec<(String, u64)>, DbErr> {
+        let rows: Vec<(String, i64)> = Self::filtered(&params)
+            .select_only()
+            .column(Column::Variant)
+            .column_as(Column::Id.count(), "clicks")
+            .filter(Column::Variant.is_not_null())
+            .group_by(Column::Variant)
+            .order_by_asc(Column::Variant)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(variant, clicks)| (variant, clicks as u64))
+            .collect())
+    }
+
     async fn delete_batch(&self, ids: Vec<i64>) -> Result<u64, DbErr> {
         let result = Entity::delete_many()
             .filter(Column::Id.is_in(ids))
@@ -174,52 +242,13 @@ impl HistoryRepository for HistoryRepositoryImpl {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, DatabaseConfig, DatabaseType, SqliteConfig};
+    use crate::config::Config;
     use crate::db::DbFactory;
     use crate::models::url::UrlStatus;
     use crate::repositories::url_repository::{CreateUrlDto, UrlRepository, UrlRepositoryImpl};
 
     async fn setup_test_db() -> DatabaseConnection {
-        let config = Config {
-            server: crate::config::ServerConfig {
-                address: ":8080".to_string(),
-                trusted_platform: None,
-                site_url: "http://localhost:8080".to_string(),
-                api_key: "test-key".to_string(),
-            },
-            shortener: crate::config::ShortenerConfig {
-                code_length: 6,
-                code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-                    .to_string(),
-            },
-            admin: crate::config::AdminConfig {
-                username: "admin".to_string(),
-                password: "admin123".to_string(),
-            },
-            database: DatabaseConfig {
-                db_type: DatabaseType::Sqlite,
-                log_level: 0,
-                sqlite: Some(SqliteConfig {
-                    path: ":memory:".to_string(),
-                }),
-                postgres: None,
-                mysql: None,
-            },
-            cache: crate::config::CacheConfig {
-                enabled: false,
-                cache_type: crate::config::CacheType::Redis,
-                expire: 3600,
-                prefix: "shorten:".to_string(),
-                redis: None,
-                valkey: None,
-            },
-            geoip: crate::config::GeoIpConfig {
-                enabled: false,
-                geoip_type: crate::config::GeoIpType::Ip2region,
-                ip2region: None,
-            },
-            logging: crate::logging::LoggingConfig::default(),
-        };
+        let config = Config::for_tests();
 
         let db = DbFactory::create_connection(&config).await.unwrap();
         DbFactory::run_migrations(&db).await.unwrap();
@@ -233,8 +262,9 @@ mod tests {
             original_url: "https://example.com".to_string(),
             description: Some("Test URL".to_string()),
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        let url = url_repo.create(create_dto).await.unwrap();
+        let url = url_repo.create(create_dto, "admin").await.unwrap();
         url.id
     }
 
@@ -259,6 +289,8 @@ mod tests {
             device_type: Some("Desktop".to_string()),
             os: Some("Windows".to_string()),
             browser: Some("Chrome".to_string()),
+            targeting_rule: None,
+            variant: None,
             accessed_at,
         };
 
@@ -295,6 +327,8 @@ mod tests {
                 device_type: None,
                 os: None,
                 browser: None,
+                targeting_rule: None,
+                variant: None,
                 accessed_at,
             };
             repo.create(create_dto).await.unwrap();
@@ -354,6 +388,8 @@ mod tests {
             device_type: None,
             os: None,
             browser: None,
+            targeting_rule: None,
+            variant: None,
             accessed_at,
         };
         repo.create(create_dto).await.unwrap();
@@ -405,6 +441,8 @@ mod tests {
                 device_type: None,
                 os: None,
                 browser: None,
+                targeting_rule: None,
+                variant: None,
                 accessed_at,
             };
             let created = repo.create(create_dto).await.unwrap();
@@ -447,6 +485,8 @@ mod tests {
                 device_type: None,
                 os: None,
                 browser: None,
+                targeting_rule: None,
+                variant: None,
                 accessed_at,
             };
             repo.create(create_dto).await.unwrap();
@@ -499,6 +539,8 @@ mod tests {
                 device_type: None,
                 os: None,
                 browser: None,
+                targeting_rule: None,
+                variant: None,
                 accessed_at,
             };
             repo.create(create_dto).await.unwrap();
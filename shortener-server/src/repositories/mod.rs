@@ -1,5 +1,11 @@
 pub mod history_repository;
+pub mod revision_repository;
+pub mod tag_repository;
 pub mod url_repository;
+pub mod user_repository;
 
 pub use history_repository::{HistoryRepository, HistoryRepositoryImpl};
+pub use revision_repository::{RevisionRepository, RevisionRepositoryImpl};
+pub use tag_repository::{TagRepository, TagRepositoryImpl};
 pub use url_repository::{UrlRepository, UrlRepositoryImpl};
+pub use user_repository::{UserRepository, UserRepositoryImpl};
@@ -0,0 +1,176 @@
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::models::url_revision::{
+    ActiveModel, Column, Entity, LinkSnapshot, Model, RevisionAction,
+};
+
+/// DTO for recording a revision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRevisionDto {
+    pub url_id: i64,
+    pub domain: String,
+    pub short_code: String,
+    pub action: RevisionAction,
+    pub actor: String,
+    pub old_value: Option<LinkSnapshot>,
+    pub new_value: Option<LinkSnapshot>,
+}
+
+/// Revision Repository trait
+#[async_trait]
+pub trait RevisionRepository: Send + Sync {
+    /// Record a revision
+    async fn create(&self, revision: CreateRevisionDto) -> Result<Model, DbErr>;
+
+    /// Find revision by ID
+    async fn find_by_id(&self, id: i64) -> Result<Option<Model>, DbErr>;
+
+    /// Revisions of a short code within a domain, newest first
+    ///
+    /// Includes the revisions of deleted URLs that used the same code.
+    async fn list_by_code(&self, domain: &str, code: &str) -> Result<Vec<Model>, DbErr>;
+}
+
+/// Record a revision on `db`, usually the transaction of the change itself
+pub(crate) async fn insert_revision<C: ConnectionTrait>(
+    db: &C,
+    revision: CreateRevisionDto,
+) -> Result<Model, DbErr> {
+    ActiveModel {
+        url_id: Set(revision.url_id),
+        domain: Set(revision.domain),
+        short_code: Set(revision.short_code),
+        action: Set(revision.action),
+        actor: Set(revision.actor),
+        old_value: Set(revision.old_value),
+        new_value: Set(revision.new_value),
+        created_at: Set(chrono::Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Revision Repository implementation
+pub struct RevisionRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl RevisionRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl RevisionRepository for RevisionRepositoryImpl {
+    async fn create(&self, revision: CreateRevisionDto) -> Result<Model, DbErr> {
+        insert_revision(&self.db, revision).await
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(id).one(&self.db).await
+    }
+
+    async fn list_by_code(&self, domain: &str, code: &str) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Domain.eq(domain))
+            .filter(Column::ShortCode.eq(code))
+            .order_by_desc(Column::Id)
+            .all(&self.db)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::DbFactory;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let config = Config::for_tests();
+
+        let db = DbFactory::create_connection(&config).await.unwrap();
+        DbFactory::run_migrations(&db).await.unwrap();
+        db
+    }
+
+    fn revision(code: &str, action: RevisionAction, url: Option<&str>) -> CreateRevisionDto {
+        CreateRevisionDto {
+            url_id: 1,
+            domain: String::new(),
+            short_code: code.to_string(),
+            action,
+            actor: "admin".to_string(),
+            old_value: None,
+            new_value: url.map(|url| LinkSnapshot {
+                original_url: url.to_string(),
+                tags: vec!["ads".to_string()],
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_revisions() {
+        let db = setup_test_db().await;
+        let repo = RevisionRepositoryImpl::new(db);
+
+        let created = repo
+            .create(revision(
+                "abc",
+                RevisionAction::Create,
+                Some("https://a.test/"),
+            ))
+            .await
+            .unwrap();
+        repo.create(revision(
+            "abc",
+            RevisionAction::Update,
+            Some("https://b.test/"),
+        ))
+        .await
+        .unwrap();
+        repo.create(revision(
+            "other",
+            RevisionAction::Create,
+            Some("https://c.test/"),
+        ))
+        .await
+        .unwrap();
+        repo.create(revision("abc", RevisionAction::Delete, None))
+            .await
+            .unwrap();
+
+        let found = repo.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(found.action, RevisionAction::Create);
+        assert_eq!(found.actor, "admin");
+        assert_eq!(found.new_value.unwrap().tags, vec!["ads"]);
+
+        let revisions = repo.list_by_code("", "abc").await.unwrap();
+        let actions: Vec<RevisionAction> = revisions.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                RevisionAction::Delete,
+                RevisionAction::Update,
+                RevisionAction::Create
+            ]
+        );
+        assert!(revisions[0].new_value.is_none());
+
+        assert!(
+            repo.list_by_code("other.test", "abc")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(repo.find_by_id(999).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,315 @@
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait,
+};
+use std::collections::HashMap;
+
+use crate::models::tag::{ActiveModel, Column, Entity, Model};
+use crate::models::{url, url_tag};
+
+/// Tag Repository trait
+#[async_trait]
+pub trait TagRepository: Send + Sync {
+    /// Create a new tag
+    async fn create(&self, name: &str) -> Result<Model, DbErr>;
+
+    /// Find tag by name
+    async fn find_by_name(&self, name: &str) -> Result<Option<Model>, DbErr>;
+
+    /// List all tags ordered by name, with the number of live URLs carrying each
+    async fn list(&self) -> Result<Vec<(Model, u64)>, DbErr>;
+
+    /// Rename a tag; the URLs carrying it keep it under the new name
+    async fn rename(&self, name: &str, new_name: &str) -> Result<Model, DbErr>;
+
+    /// Delete a tag by name, detaching it from every URL
+    async fn delete(&self, name: &str) -> Result<(), DbErr>;
+
+    /// Tag names of each of the given URLs, sorted by name
+    ///
+    /// URLs without tags are missing from the map.
+    async fn tags_for_urls(&self, url_ids: &[i64]) -> Result<HashMap<i64, Vec<String>>, DbErr>;
+
+    /// Replace the tags of a URL, creating tags that do not exist yet
+    async fn set_url_tags(&self, url_id: i64, names: &[String]) -> Result<(), DbErr>;
+}
+
+/// Tag Repository implementation
+pub struct TagRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl TagRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl TagRepository for TagRepositoryImpl {
+    async fn create(&self, name: &str) -> Result<Model, DbErr> {
+        let active_model = ActiveModel {
+            name: Set(name.to_string()),
+            created_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        };
+
+        active_model.insert(&self.db).await
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Name.eq(name))
+            .one(&self.db)
+            .await
+    }
+
+    async fn list(&self) -> Result<Vec<(Model, u64)>, DbErr> {
+        let tags = Entity::find()
+            .order_by_asc(Column::Name)
+            .all(&self.db)
+            .await?;
+
+        let counts: HashMap<i64, i64> = url_tag::Entity::find()
+            .select_only()
+            .column(url_tag::Column::TagId)
+            .column_as(url_tag::Column::UrlId.count(), "urls")
+            .join(JoinType::InnerJoin, url_tag::Relation::Url.def())
+            .filter(url::Column::DeletedAt.is_null())
+            .group_by(url_tag::Column::TagId)
+            .into_tuple::<(i64, i64)>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(tags
+            .into_iter()
+            .map(|tag| {
+                let count = counts.get(&tag.id).copied().unwrap_or(0) as u64;
+                (tag, count)
+            })
+            .collect())
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<Model, DbErr> {
+        let tag = self
+            .find_by_name(name)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("Tag '{}' not found", name)))?;
+
+        let mut active_model: ActiveModel = tag.into();
+        active_model.name = Set(new_name.to_string());
+
+        active_model.update(&self.db).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), DbErr> {
+        let tag = self
+            .find_by_name(name)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("Tag '{}' not found", name)))?;
+
+        let txn = self.db.begin().await?;
+
+        url_tag::Entity::delete_many()
+            .filter(url_tag::Column::TagId.eq(tag.id))
+            .exec(&txn)
+            .await?;
+        Entity::delete_by_id(tag.id).exec(&txn).await?;
+
+        txn.commit().await
+    }
+
+    async fn tags_for_urls(&self, url_ids: &[i64]) -> Result<HashMap<i64, Vec<String>>, DbErr> {
+        url_tags(&self.db, url_ids).await
+    }
+
+    async fn set_url_tags(&self, url_id: i64, names: &[String]) -> Result<(), DbErr> {
+        let txn = self.db.begin().await?;
+        replace_url_tags(&txn, url_id, names).await?;
+        txn.commit().await
+    }
+}
+
+/// Tag names of each of the given URLs on `db`, sorted by name
+pub(crate) async fn url_tags<C: ConnectionTrait>(
+    db: &C,
+    url_ids: &[i64],
+) -> Result<HashMap<i64, Vec<String>>, DbErr> {
+    if url_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let links = url_tag::Entity::find()
+        .filter(url_tag::Column::UrlId.is_in(url_ids.to_vec()))
+        .find_also_related(Entity)
+        .order_by_asc(Column::Name)
+        .all(db)
+        .await?;
+
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for (link, tag) in links {
+        if let Some(tag) = tag {
+            tags.entry(link.url_id).or_default().push(tag.name);
+        }
+    }
+
+    Ok(tags)
+}
+
+/// Replace the tags of a URL on `db`, creating tags that do not exist yet
+///
+/// Runs inside the caller's transaction.
+pub(crate) async fn replace_url_tags<C: ConnectionTrait>(
+    db: &C,
+    url_id: i64,
+    names: &[String],
+) -> Result<(), DbErr> {
+    url_tag::Entity::delete_many()
+        .filter(url_tag::Column::UrlId.eq(url_id))
+        .exec(db)
+        .await?;
+
+    if !names.is_empty() {
+        let mut tags = Entity::find()
+            .filter(Column::Name.is_in(names.to_vec()))
+            .all(db)
+            .await?;
+
+        let now = chrono::Utc::now();
+        for name in names {
+            if tags.iter().any(|tag| &tag.name == name) {
+                continue;
+            }
+            let tag = ActiveModel {
+                name: Set(name.clone()),
+                created_at: Set(now),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+            tags.push(tag);
+        }
+
+        url_tag::Entity::insert_many(tags.into_iter().map(|tag| url_tag::ActiveModel {
+            url_id: Set(url_id),
+            tag_id: Set(tag.id),
+        }))
+        .exec(db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::DbFactory;
+    use crate::repositories::url_repository::{CreateUrlDto, UrlRepository, UrlRepositoryImpl};
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let config = Config::for_tests();
+
+        let db = DbFactory::create_connection(&config).await.unwrap();
+        DbFactory::run_migrations(&db).await.unwrap();
+        db
+    }
+
+    async fn create_url(db: &DatabaseConnection, code: &str) -> i64 {
+        UrlRepositoryImpl::new(db.clone())
+            .create(
+                CreateUrlDto {
+                    short_code: code.to_string(),
+                    original_url: "https://example.com".to_string(),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap()
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_set_url_tags() {
+        let db = setup_test_db().await;
+        let repo = TagRepositoryImpl::new(db.clone());
+        let first = create_url(&db, "first").await;
+        let second = create_url(&db, "second").await;
+
+        repo.create("ads").await.unwrap();
+        repo.set_url_tags(first, &["ads".to_string(), "social".to_string()])
+            .await
+            .unwrap();
+        repo.set_url_tags(second, &["social".to_string()])
+            .await
+            .unwrap();
+
+        let tags = repo.tags_for_urls(&[first, second]).await.unwrap();
+        assert_eq!(tags[&first], vec!["ads", "social"]);
+        assert_eq!(tags[&second], vec!["social"]);
+
+        // Missing tags were created on the fly
+        let listed = repo.list().await.unwrap();
+        let summary: Vec<(&str, u64)> = listed
+            .iter()
+            .map(|(tag, count)| (tag.name.as_str(), *count))
+            .collect();
+        assert_eq!(summary, vec![("ads", 1), ("social", 2)]);
+
+        // Setting tags replaces the previous ones
+        repo.set_url_tags(first, &[]).await.unwrap();
+        let tags = repo.tags_for_urls(&[first, second]).await.unwrap();
+        assert!(!tags.contains_key(&first));
+        assert_eq!(tags[&second], vec!["social"]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_delete_tag() {
+        let db = setup_test_db().await;
+        let repo = TagRepositoryImpl::new(db.clone());
+        let url_id = create_url(&db, "tagged").await;
+
+        repo.set_url_tags(url_id, &["old".to_string()])
+            .await
+            .unwrap();
+
+        let renamed = repo.rename("old", "new").await.unwrap();
+        assert_eq!(renamed.name, "new");
+        assert!(repo.find_by_name("old").await.unwrap().is_none());
+        assert_eq!(
+            repo.tags_for_urls(&[url_id]).await.unwrap()[&url_id],
+            vec!["new"]
+        );
+
+        repo.delete("new").await.unwrap();
+        assert!(repo.list().await.unwrap().is_empty());
+        assert!(repo.tags_for_urls(&[url_id]).await.unwrap().is_empty());
+
+        assert!(repo.delete("new").await.is_err());
+        assert!(repo.rename("missing", "other").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deleting_url_detaches_tags() {
+        let db = setup_test_db().await;
+        let repo = TagRepositoryImpl::new(db.clone());
+        let url_id = create_url(&db, "gone").await;
+
+        repo.set_url_tags(url_id, &["ads".to_string()])
+            .await
+            .unwrap();
+        UrlRepositoryImpl::new(db.clone())
+            .delete("", "gone", "admin")
+            .await
+            .unwrap();
+
+        let listed = repo.list().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].1, 0);
+    }
+}
@@ -1,27 +1,124 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, OnConflict};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, Set,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Select, Set,
+    TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::models::url::{ActiveModel, Column, Entity, Model};
+use crate::domains::LinkKey;
+use crate::health::HealthCheck;
+use crate::import::ConflictPolicy;
+use crate::metadata::{Metadata, MetadataFilter};
+use crate::models::url::{ActiveModel, Column, Entity, Model, UrlStatus};
+use crate::models::url_revision::{LinkSnapshot, RevisionAction};
+use crate::models::{code_sequence, history, tag, url_tag};
+use crate::repositories::revision_repository::{CreateRevisionDto, insert_revision};
+use crate::repositories::tag_repository::{replace_url_tags, url_tags};
+use crate::targeting::TargetingRules;
+use crate::url_hash;
+use crate::variants::Variants;
+
+/// Links purged per statement, keeping `IN` lists within bind parameter limits
+const PURGE_CHUNK_SIZE: usize = 500;
 
 /// DTO for creating a URL
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateUrlDto {
+    /// Empty for the default domain
+    pub domain: String,
+    pub short_code: String,
+    pub original_url: String,
+    pub description: Option<String>,
+    pub status: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i64>,
+    pub password_hash: Option<String>,
+    pub redirect_type: Option<i32>,
+    pub targeting_rules: Option<TargetingRules>,
+    pub variants: Option<Variants>,
+    pub sticky_variants: bool,
+    pub forward_query: Option<bool>,
+    pub forward_path: Option<bool>,
+    pub metadata: Option<Metadata>,
+    /// Tag names, created when they do not exist yet
+    pub tags: Vec<String>,
+}
+
+/// DTO for a URL of an import, already validated
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportUrlDto {
+    /// Empty for the default domain
+    pub domain: String,
     pub short_code: String,
     pub original_url: String,
     pub description: Option<String>,
     pub status: i32,
+    /// Now when missing; left alone when overwriting
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// What importing one URL did
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    Created(Box<Model>),
+    Updated {
+        old: Box<Model>,
+        new: Box<Model>,
+    },
+    Skipped,
+    /// The code is taken and the conflict policy is [`ConflictPolicy::Fail`]
+    Conflict,
+    /// The code belongs to a URL in the trash and the conflict policy is
+    /// [`ConflictPolicy::Overwrite`]; it has to be restored first
+    Trashed,
 }
 
 /// DTO for updating a URL
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateUrlDto {
     pub original_url: Option<String>,
     pub description: Option<String>,
     pub status: Option<i32>,
+    /// `Some(None)` removes the expiry, `None` leaves it untouched
+    pub expires_at: Option<Option<DateTime<Utc>>>,
+    /// `Some(None)` removes the start of the activation window
+    pub active_from: Option<Option<DateTime<Utc>>>,
+    /// `Some(None)` removes the end of the activation window
+    pub active_until: Option<Option<DateTime<Utc>>>,
+    /// `Some(None)` removes the click quota, `None` leaves it untouched
+    pub max_clicks: Option<Option<i64>>,
+    /// `Some(None)` removes the password, `None` leaves it untouched
+    pub password_hash: Option<Option<String>>,
+    /// `Some(None)` goes back to the server default, `None` leaves it untouched
+    pub redirect_type: Option<Option<i32>>,
+    /// Replaces the targeting rules; an empty list removes them
+    pub targeting_rules: Option<TargetingRules>,
+    /// Replaces the A/B variants; an empty list removes them
+    pub variants: Option<Variants>,
+    pub sticky_variants: Option<bool>,
+    pub forward_query: Option<bool>,
+    pub forward_path: Option<bool>,
+    /// Replaces the metadata; an empty object removes it
+    pub metadata: Option<Metadata>,
+    /// Replaces the tags; an empty list removes them
+    pub tags: Option<Vec<String>>,
+}
+
+/// Position of a URL relative to its activation window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleState {
+    /// `active_from` is still in the future
+    Scheduled,
+    /// Inside the window (or no window at all)
+    Live,
+    /// `active_until` has passed
+    Ended,
 }
 
 /// Parameters for listing URLs
@@ -31,9 +128,21 @@ pub struct ListParams {
     pub page: u64,
     #[serde(default = "default_per_page", rename = "per_page")]
     pub page_size: u64,
+    /// Only URLs on this domain (empty for the default domain)
+    pub domain: Option<String>,
     pub short_code: Option<String>,
     pub original_url: Option<String>,
     pub status: Option<i32>,
+    pub schedule: Option<ScheduleState>,
+    /// Only URLs carrying this tag
+    pub tag: Option<String>,
+    /// Only URLs whose last health check failed (`true`) or did not (`false`)
+    pub broken: Option<bool>,
+    /// Only URLs with this metadata attribute (`key` or `key:value`)
+    pub metadata: Option<MetadataFilter>,
+    /// List the trash instead of live URLs (set by the trash listing)
+    #[serde(skip)]
+    pub deleted: bool,
     #[serde(default = "default_sort_by")]
     pub sort_by: Option<String>,
     #[serde(default = "default_order")]
@@ -61,9 +170,15 @@ impl Default for ListParams {
         Self {
             page: 1,
             page_size: 10,
+            domain: None,
             short_code: None,
             original_url: None,
             status: None,
+            schedule: None,
+            tag: None,
+            broken: None,
+            metadata: None,
+            deleted: false,
             sort_by: Some("created_at".to_string()),
             order: Some("desc".to_string()),
         }
@@ -73,26 +188,123 @@ impl Default for ListParams {
 /// URL Repository trait
 #[async_trait]
 pub trait UrlRepository: Send + Sync {
-    /// Create a new URL
-    async fn create(&self, url: CreateUrlDto) -> Result<Model, DbErr>;
-
-    /// Find URL by code
-    async fn find_by_code(&self, code: &str) -> Result<Option<Model>, DbErr>;
-
-    /// Find URL by ID
+    /// Create a new URL with its tags
+    ///
+    /// The creation is recorded as a revision by `actor` in the same transaction.
+    async fn create(&self, url: CreateUrlDto, actor: &str) -> Result<Model, DbErr>;
+
+    /// Find URL by code within a domain; URLs in the trash are skipped
+    async fn find_by_code(&self, domain: &str, code: &str) -> Result<Option<Model>, DbErr>;
+
+    /// Find a URL in the trash by code within a domain
+    async fn find_deleted_by_code(&self, domain: &str, code: &str) -> Result<Option<Model>, DbErr>;
+
+    /// Find the oldest enabled URL of a domain pointing to the same destination
+    ///
+    /// Destinations are compared by their normalized form, see [`url_hash`].
+    /// URLs with a password, expiry, activation window or click quota are
+    /// skipped, as handing them out would impose those on the caller.
+    async fn find_by_original_url(
+        &self,
+        domain: &str,
+        original_url: &str,
+    ) -> Result<Option<Model>, DbErr>;
+
+    /// Find URL by ID, including URLs in the trash
     async fn find_by_id(&self, id: i64) -> Result<Option<Model>, DbErr>;
 
     /// List URLs with pagination
     async fn list(&self, params: ListParams) -> Result<(Vec<Model>, u64), DbErr>;
 
-    /// Update URL by code
-    async fn update(&self, code: &str, data: UpdateUrlDto) -> Result<Model, DbErr>;
-
-    /// Delete URL by code
-    async fn delete(&self, code: &str) -> Result<(), DbErr>;
+    /// List the URLs matching the filters of `params` with an ID above `after_id`
+    ///
+    /// Returns at most `limit` URLs ordered by ID, so walking the results
+    /// batch by batch never needs an offset. Paging and sorting parameters
+    /// are ignored.
+    async fn export_batch(
+        &self,
+        params: &ListParams,
+        after_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr>;
 
-    /// Delete multiple URLs by IDs
-    async fn delete_batch(&self, ids: Vec<i64>) -> Result<u64, DbErr>;
+    /// Update URL by code
+    ///
+    /// The change is recorded as a revision by `actor` in the same
+    /// transaction, unless nothing changed.
+    async fn update(
+        &self,
+        domain: &str,
+        code: &str,
+        data: UpdateUrlDto,
+        actor: &str,
+    ) -> Result<Model, DbErr>;
+
+    /// Put a URL back into the state of a revision snapshot, tags included
+    ///
+    /// Unlike [`update`](Self::update), fields missing from the snapshot are
+    /// cleared. The password is left alone. Recorded as a restore by `actor`.
+    async fn restore(&self, id: i64, snapshot: &LinkSnapshot, actor: &str) -> Result<Model, DbErr>;
+
+    /// Move URL to the trash by code, recording the deletion by `actor`
+    ///
+    /// The row stays, keeping its code taken and its history, until it is purged.
+    async fn delete(&self, domain: &str, code: &str, actor: &str) -> Result<(), DbErr>;
+
+    /// Move multiple URLs to the trash by IDs, recording each deletion by `actor`
+    async fn delete_batch(&self, ids: Vec<i64>, actor: &str) -> Result<u64, DbErr>;
+
+    /// Take a URL out of the trash by code, recording the restore by `actor`
+    async fn undelete(&self, domain: &str, code: &str, actor: &str) -> Result<Model, DbErr>;
+
+    /// Permanently delete URLs moved to the trash before `before`
+    ///
+    /// Irreversible: their access history and tag assignments go with them.
+    /// Revisions are kept, so the changes made to a purged link stay listed.
+    async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<u64, DbErr>;
+
+    /// Import URLs in one transaction
+    ///
+    /// Codes taken by a live URL or one in the trash are handled by
+    /// `on_conflict`, except that URLs in the trash are never overwritten. The
+    /// transaction is only committed when `commit` is set and no URL ran into
+    /// a conflict, otherwise it is rolled back.
+    ///
+    /// Created and overwritten URLs are recorded as revisions by `actor`.
+    /// Returns one outcome per URL and whether the transaction was committed.
+    async fn import(
+        &self,
+        urls: Vec<ImportUrlDto>,
+        on_conflict: ConflictPolicy,
+        commit: bool,
+        actor: &str,
+    ) -> Result<(Vec<ImportOutcome>, bool), DbErr>;
+
+    /// Mark enabled URLs whose `expires_at` has passed as expired
+    ///
+    /// The due URLs are locked, flipped by a single UPDATE and recorded as
+    /// revisions by `actor` in one transaction, so a link extended in the
+    /// meantime is left alone. Returns the keys of the URLs that were flipped.
+    async fn mark_expired(&self, now: DateTime<Utc>, actor: &str) -> Result<Vec<LinkKey>, DbErr>;
+
+    /// Atomically count one click against the URL's `max_clicks` quota
+    ///
+    /// Returns `false` when the quota is already used up. The check and the
+    /// increment run as a single conditional UPDATE, so it stays correct when
+    /// several server instances share the database.
+    async fn consume_click(&self, id: i64) -> Result<bool, DbErr>;
+
+    /// Take the next value of a named sequence, starting at 1
+    ///
+    /// The increment and the read share a transaction, so every caller gets
+    /// its own value even when several server instances share the database.
+    async fn next_sequence(&self, name: &str) -> Result<u64, DbErr>;
+
+    /// Store the result of a destination health check
+    ///
+    /// A failed check bumps `check_failures`, a successful one resets it.
+    /// `updated_at` is left alone since the link itself did not change.
+    async fn record_health_check(&self, id: i64, check: &HealthCheck) -> Result<Model, DbErr>;
 }
 
 /// URL Repository implementation
@@ -104,29 +316,163 @@ impl UrlRepositoryImpl {
     pub fn new(db: DatabaseConnection) -> Self {
         Self { db }
     }
+
+    /// Apply the filters of the list parameters to a query
+    fn filtered(&self, params: &ListParams) -> Select<Entity> {
+        let mut query = if params.deleted {
+            Entity::find().filter(Column::DeletedAt.is_not_null())
+        } else {
+            Entity::find().filter(Column::DeletedAt.is_null())
+        };
+
+        // Apply domain filter if provided
+        if let Some(domain) = &params.domain {
+            query = query.filter(Column::Domain.eq(domain));
+        }
+
+        // Apply code filter if provided
+        if let Some(code) = &params.short_code {
+            query = query.filter(Column::ShortCode.eq(code));
+        }
+
+        // Apply original_url filter if provided (fuzzy search using LIKE)
+        if let Some(original_url) = &params.original_url {
+            query = query.filter(Column::OriginalUrl.contains(original_url));
+        }
+
+        // Apply status filter if provided
+        if let Some(status) = params.status {
+            query = query.filter(Column::Status.eq(status));
+        }
+
+        // Apply activation window filter if provided
+        if let Some(schedule) = params.schedule {
+            let now = Utc::now();
+            query = match schedule {
+                ScheduleState::Scheduled => query.filter(Column::ActiveFrom.gt(now)),
+                ScheduleState::Live => query
+                    .filter(
+                        Condition::any()
+                            .add(Column::ActiveFrom.is_null())
+                            .add(Column::ActiveFrom.lte(now)),
+                    )
+                    .filter(
+                        Condition::any()
+                            .add(Column::ActiveUntil.is_null())
+                            .add(Column::ActiveUntil.gt(now)),
+                    ),
+                ScheduleState::Ended => query.filter(Column::ActiveUntil.lte(now)),
+            };
+        }
+
+        // Apply tag filter if provided
+        if let Some(tag) = &params.tag {
+            query = query.filter(
+                Column::Id.in_subquery(
+                    url_tag::Entity::find()
+                        .select_only()
+                        .column(url_tag::Column::UrlId)
+                        .inner_join(tag::Entity)
+                        .filter(tag::Column::Name.eq(tag))
+                        .into_query(),
+                ),
+            );
+        }
+
+        // Apply health filter if provided
+        if let Some(broken) = params.broken {
+            query = if broken {
+                query.filter(Column::LastError.is_not_null())
+            } else {
+                query.filter(Column::LastError.is_null())
+            };
+        }
+
+        // Apply metadata filter if provided
+        if let Some(metadata) = &params.metadata {
+            query = query.filter(metadata.condition(self.db.get_database_backend()));
+        }
+
+        query
+    }
 }
 
 #[async_trait]
 impl UrlRepository for UrlRepositoryImpl {
-    async fn create(&self, url: CreateUrlDto) -> Result<Model, DbErr> {
+    async fn create(&self, url: CreateUrlDto, actor: &str) -> Result<Model, DbErr> {
+        let txn = self.db.begin().await?;
         let now = chrono::Utc::now();
 
         let active_model = ActiveModel {
+            domain: Set(url.domain),
             short_code: Set(url.short_code),
+            original_url_hash: Set(url_hash::hash(&url.original_url)),
             original_url: Set(url.original_url),
             description: Set(url.description),
             status: Set(url.status),
+            expires_at: Set(url.expires_at),
+            active_from: Set(url.active_from),
+            active_until: Set(url.active_until),
+            max_clicks: Set(url.max_clicks),
+            password_hash: Set(url.password_hash),
+            redirect_type: Set(url.redirect_type),
+            targeting_rules: Set(url.targeting_rules),
+            variants: Set(url.variants),
+            sticky_variants: Set(url.sticky_variants),
+            forward_query: Set(url.forward_query),
+            forward_path: Set(url.forward_path),
+            metadata: Set(url.metadata),
+            check_failures: Set(0),
             created_at: Set(now),
             updated_at: Set(now),
             ..Default::default()
         };
 
-        active_model.insert(&self.db).await
+        let model = active_model.insert(&txn).await?;
+        if !url.tags.is_empty() {
+            replace_url_tags(&txn, model.id, &url.tags).await?;
+        }
+        let new = LinkSnapshot::of(&model, url.tags);
+        record(&txn, &model, RevisionAction::Create, actor, None, Some(new)).await?;
+        txn.commit().await?;
+
+        Ok(model)
     }
 
-    async fn find_by_code(&self, code: &str) -> Result<Option<Model>, DbErr> {
+    async fn find_by_code(&self, domain: &str, code: &str) -> Result<Option<Model>, DbErr> {
         Entity::find()
+            .filter(Column::Domain.eq(domain))
             .filter(Column::ShortCode.eq(code))
+            .filter(Column::DeletedAt.is_null())
+            .one(&self.db)
+            .await
+    }
+
+    async fn find_deleted_by_code(&self, domain: &str, code: &str) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Domain.eq(domain))
+            .filter(Column::ShortCode.eq(code))
+            .filter(Column::DeletedAt.is_not_null())
+            .one(&self.db)
+            .await
+    }
+
+    async fn find_by_original_url(
+        &self,
+        domain: &str,
+        original_url: &str,
+    ) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Domain.eq(domain))
+            .filter(Column::OriginalUrlHash.eq(url_hash::hash(original_url)))
+            .filter(Column::Status.eq(UrlStatus::Enabled as i32))
+            .filter(Column::PasswordHash.is_null())
+            .filter(Column::ExpiresAt.is_null())
+            .filter(Column::ActiveFrom.is_null())
+            .filter(Column::ActiveUntil.is_null())
+            .filter(Column::MaxClicks.is_null())
+            .filter(Column::DeletedAt.is_null())
+            .order_by_asc(Column::Id)
             .one(&self.db)
             .await
     }
@@ -136,22 +482,7 @@ impl UrlRepository for UrlRepositoryImpl {
     }
 
     async fn list(&self, params: ListParams) -> Result<(Vec<Model>, u64), DbErr> {
-        let mut query = Entity::find();
-
-        // Apply code filter if provided
-        if let Some(code) = params.short_code {
-            query = query.filter(Column::ShortCode.eq(code));
-        }
-
-        // Apply original_url filter if provided (fuzzy search using LIKE)
-        if let Some(original_url) = params.original_url {
-            query = query.filter(Column::OriginalUrl.contains(&original_url));
-        }
-
-        // Apply status filter if provided
-        if let Some(status) = params.status {
-            query = query.filter(Column::Status.eq(status));
-        }
+        let mut query = self.filtered(&params);
 
         // Apply sorting
         let sort_column = params.sort_by.as_deref().unwrap_or("created_at");
@@ -166,6 +497,8 @@ impl UrlRepository for UrlRepositoryImpl {
             ("created_at", "desc") => query.order_by_desc(Column::CreatedAt),
             ("updated_at", "asc") => query.order_by_asc(Column::UpdatedAt),
             ("updated_at", "desc") => query.order_by_desc(Column::UpdatedAt),
+            ("deleted_at", "asc") => query.order_by_asc(Column::DeletedAt),
+            ("deleted_at", "desc") => query.order_by_desc(Column::DeletedAt),
             _ => query.order_by_desc(Column::CreatedAt), // Default
         };
 
@@ -179,18 +512,46 @@ impl UrlRepository for UrlRepositoryImpl {
         Ok((items, total))
     }
 
-    async fn update(&self, code: &str, data: UpdateUrlDto) -> Result<Model, DbErr> {
+    async fn export_batch(
+        &self,
+        params: &ListParams,
+        after_id: i64,
+        limit: u64,
+    ) -> Result<Vec<Model>, DbErr> {
+        self.filtered(params)
+            .filter(Column::Id.gt(after_id))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+    }
+
+    async fn update(
+        &self,
+        domain: &str,
+        code: &str,
+        data: UpdateUrlDto,
+        actor: &str,
+    ) -> Result<Model, DbErr> {
+        let txn = self.db.begin().await?;
+
         // First find the URL
-        let url = self
-            .find_by_code(code)
-            .await?
-            .ok_or_else(|| DbErr::RecordNotFound(format!("URL with code '{}' not found", code)))?;
+        let url = live_by_code(&txn, domain, code).await?;
+        let old = snapshot_of(&txn, &url).await?;
 
         // Create active model for update
         let mut active_model: ActiveModel = url.into();
 
         // Update fields if provided
         if let Some(original_url) = data.original_url {
+            // Checks of the previous destination no longer apply
+            if *active_model.original_url.as_ref() != original_url {
+                active_model.last_checked_at = Set(None);
+                active_model.last_status = Set(None);
+                active_model.last_error = Set(None);
+                active_model.check_failures = Set(0);
+            }
+            active_model.original_url_hash = Set(url_hash::hash(&original_url));
             active_model.original_url = Set(original_url);
         }
         if let Some(description) = data.description {
@@ -199,83 +560,493 @@ impl UrlRepository for UrlRepositoryImpl {
         if let Some(status) = data.status {
             active_model.status = Set(status);
         }
+        if let Some(expires_at) = data.expires_at {
+            active_model.expires_at = Set(expires_at);
+        }
+        if let Some(active_from) = data.active_from {
+            active_model.active_from = Set(active_from);
+        }
+        if let Some(active_until) = data.active_until {
+            active_model.active_until = Set(active_until);
+        }
+        if let Some(max_clicks) = data.max_clicks {
+            active_model.max_clicks = Set(max_clicks);
+        }
+        if let Some(password_hash) = data.password_hash {
+            active_model.password_hash = Set(password_hash);
+        }
+        if let Some(redirect_type) = data.redirect_type {
+            active_model.redirect_type = Set(redirect_type);
+        }
+        if let Some(targeting_rules) = data.targeting_rules {
+            active_model.targeting_rules =
+                Set((!targeting_rules.0.is_empty()).then_some(targeting_rules));
+        }
+        if let Some(variants) = data.variants {
+            active_model.variants = Set((!variants.0.is_empty()).then_some(variants));
+        }
+        if let Some(sticky_variants) = data.sticky_variants {
+            active_model.sticky_variants = Set(sticky_variants);
+        }
+        if let Some(forward_query) = data.forward_query {
+            active_model.forward_query = Set(Some(forward_query));
+        }
+        if let Some(forward_path) = data.forward_path {
+            active_model.forward_path = Set(Some(forward_path));
+        }
+        if let Some(metadata) = data.metadata {
+            active_model.metadata = Set((!metadata.0.is_empty()).then_some(metadata));
+        }
 
         // Always update the updated_at timestamp
         active_model.updated_at = Set(chrono::Utc::now());
 
-        active_model.update(&self.db).await
+        let model = active_model.update(&txn).await?;
+        if let Some(tags) = &data.tags {
+            replace_url_tags(&txn, model.id, tags).await?;
+        }
+        let new = snapshot_of(&txn, &model).await?;
+        record(
+            &txn,
+            &model,
+            RevisionAction::Update,
+            actor,
+            Some(old),
+            Some(new),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(model)
     }
 
-    async fn delete(&self, code: &str) -> Result<(), DbErr> {
-        let url = self
-            .find_by_code(code)
+    async fn restore(&self, id: i64, snapshot: &LinkSnapshot, actor: &str) -> Result<Model, DbErr> {
+        let txn = self.db.begin().await?;
+        let url = Entity::find_by_id(id)
+            .one(&txn)
             .await?
-            .ok_or_else(|| DbErr::RecordNotFound(format!("URL with code '{}' not found", code)))?;
+            .ok_or_else(|| DbErr::RecordNotFound(format!("URL with id {} not found", id)))?;
+        let old = snapshot_of(&txn, &url).await?;
 
-        let active_model: ActiveModel = url.into();
-        active_model.delete(&self.db).await?;
+        let mut active_model: ActiveModel = url.into();
 
-        Ok(())
+        // Checks of the previous destination no longer apply
+        if *active_model.original_url.as_ref() != snapshot.original_url {
+            active_model.last_checked_at = Set(None);
+            active_model.last_status = Set(None);
+            active_model.last_error = Set(None);
+            active_model.check_failures = Set(0);
+        }
+        active_model.original_url_hash = Set(url_hash::hash(&snapshot.original_url));
+        active_model.original_url = Set(snapshot.original_url.clone());
+        active_model.description = Set(snapshot.description.clone());
+        active_model.status = Set(snapshot.status);
+        active_model.expires_at = Set(snapshot.expires_at);
+        active_model.active_from = Set(snapshot.active_from);
+        active_model.active_until = Set(snapshot.active_until);
+        active_model.max_clicks = Set(snapshot.max_clicks);
+        active_model.redirect_type = Set(snapshot.redirect_type);
+        active_model.targeting_rules = Set(snapshot.targeting_rules.clone());
+        active_model.variants = Set(snapshot.variants.clone());
+        active_model.sticky_variants = Set(snapshot.sticky_variants);
+        active_model.forward_query = Set(snapshot.forward_query);
+        active_model.forward_path = Set(snapshot.forward_path);
+        active_model.metadata = Set(snapshot.metadata.clone());
+        active_model.updated_at = Set(chrono::Utc::now());
+
+        let model = active_model.update(&txn).await?;
+        replace_url_tags(&txn, model.id, &snapshot.tags).await?;
+        let new = snapshot_of(&txn, &model).await?;
+        record(
+            &txn,
+            &model,
+            RevisionAction::Restore,
+            actor,
+            Some(old),
+            Some(new),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(model)
+    }
+
+    async fn delete(&self, domain: &str, code: &str, actor: &str) -> Result<(), DbErr> {
+        let txn = self.db.begin().await?;
+        let url = live_by_code(&txn, domain, code).await?;
+        let old = snapshot_of(&txn, &url).await?;
+
+        let mut active_model: ActiveModel = url.into();
+        active_model.deleted_at = Set(Some(chrono::Utc::now()));
+        let model = active_model.update(&txn).await?;
+        record(&txn, &model, RevisionAction::Delete, actor, Some(old), None).await?;
+
+        txn.commit().await
     }
 
-    async fn delete_batch(&self, ids: Vec<i64>) -> Result<u64, DbErr> {
-        let result = Entity::delete_many()
+    async fn delete_batch(&self, ids: Vec<i64>, actor: &str) -> Result<u64, DbErr> {
+        let txn = self.db.begin().await?;
+        let urls = Entity::find()
             .filter(Column::Id.is_in(ids))
-            .exec(&self.db)
+            .filter(Column::DeletedAt.is_null())
+            .all(&txn)
             .await?;
+        let ids: Vec<i64> = urls.iter().map(|url| url.id).collect();
+        let mut tags = url_tags(&txn, &ids).await?;
+
+        let result = Entity::update_many()
+            .col_expr(Column::DeletedAt, Expr::value(chrono::Utc::now()))
+            .filter(Column::Id.is_in(ids))
+            .filter(Column::DeletedAt.is_null())
+            .exec(&txn)
+            .await?;
+        for url in &urls {
+            let old = LinkSnapshot::of(url, tags.remove(&url.id).unwrap_or_default());
+            record(&txn, url, RevisionAction::Delete, actor, Some(old), None).await?;
+        }
+        txn.commit().await?;
 
         Ok(result.rows_affected)
     }
+
+    async fn undelete(&self, domain: &str, code: &str, actor: &str) -> Result<Model, DbErr> {
+        let txn = self.db.begin().await?;
+        let url = Entity::find()
+            .filter(Column::Domain.eq(domain))
+            .filter(Column::ShortCode.eq(code))
+            .filter(Column::DeletedAt.is_not_null())
+            .one(&txn)
+            .await?
+            .ok_or_else(|| {
+                DbErr::RecordNotFound(format!("Deleted URL with code '{}' not found", code))
+            })?;
+
+        let mut active_model: ActiveModel = url.into();
+        active_model.deleted_at = Set(None);
+        active_model.updated_at = Set(chrono::Utc::now());
+
+        let model = active_model.update(&txn).await?;
+        let new = snapshot_of(&txn, &model).await?;
+        record(
+            &txn,
+            &model,
+            RevisionAction::Restore,
+            actor,
+            None,
+            Some(new),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(model)
+    }
+
+    async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<u64, DbErr> {
+        let txn = self.db.begin().await?;
+
+        let ids: Vec<i64> = Entity::find()
+            .select_only()
+            .column(Column::Id)
+            .filter(Column::DeletedAt.lt(before))
+            .into_tuple()
+            .all(&txn)
+            .await?;
+
+        let mut purged = 0;
+        for chunk in ids.chunks(PURGE_CHUNK_SIZE) {
+            // Histories have no foreign key to urls, tag assignments cascade
+            history::Entity::delete_many()
+                .filter(history::Column::UrlId.is_in(chunk.iter().map(|&id| id as i32)))
+                .exec(&txn)
+                .await?;
+            purged += Entity::delete_many()
+                .filter(Column::Id.is_in(chunk.iter().copied()))
+                .exec(&txn)
+                .await?
+                .rows_affected;
+        }
+
+        txn.commit().await?;
+
+        Ok(purged)
+    }
+
+    async fn import(
+        &self,
+        urls: Vec<ImportUrlDto>,
+        on_conflict: ConflictPolicy,
+        commit: bool,
+        actor: &str,
+    ) -> Result<(Vec<ImportOutcome>, bool), DbErr> {
+        let txn = self.db.begin().await?;
+        let now = chrono::Utc::now();
+        let mut outcomes = Vec::with_capacity(urls.len());
+
+        for url in urls {
+            let existing = Entity::find()
+                .filter(Column::Domain.eq(&url.domain))
+                .filter(Column::ShortCode.eq(&url.short_code))
+                .one(&txn)
+                .await?;
+
+            let outcome = match (existing, on_conflict) {
+                (None, _) => {
+                    let created_at = url.created_at.unwrap_or(now);
+                    let model = ActiveModel {
+                        domain: Set(url.domain),
+                        short_code: Set(url.short_code),
+                        original_url_hash: Set(url_hash::hash(&url.original_url)),
+                        original_url: Set(url.original_url),
+                        description: Set(url.description),
+                        status: Set(url.status),
+                        check_failures: Set(0),
+                        created_at: Set(created_at),
+                        updated_at: Set(now),
+                        ..Default::default()
+                    }
+                    .insert(&txn)
+                    .await?;
+                    let new = LinkSnapshot::of(&model, Vec::new());
+                    record(&txn, &model, RevisionAction::Create, actor, None, Some(new)).await?;
+                    ImportOutcome::Created(Box::new(model))
+                }
+                (Some(_), ConflictPolicy::Skip) => ImportOutcome::Skipped,
+                (Some(_), ConflictPolicy::Fail) => ImportOutcome::Conflict,
+                (Some(old), ConflictPolicy::Overwrite) if old.is_deleted() => {
+                    ImportOutcome::Trashed
+                }
+                (Some(old), ConflictPolicy::Overwrite) => {
+                    let old_snapshot = snapshot_of(&txn, &old).await?;
+                    let mut active_model: ActiveModel = old.clone().into();
+
+                    // Checks of the previous destination no longer apply
+                    if old.original_url != url.original_url {
+                        active_model.last_checked_at = Set(None);
+                        active_model.last_status = Set(None);
+                        active_model.last_error = Set(None);
+                        active_model.check_failures = Set(0);
+                    }
+                    active_model.original_url_hash = Set(url_hash::hash(&url.original_url));
+                    active_model.original_url = Set(url.original_url);
+                    active_model.description = Set(url.description);
+                    active_model.status = Set(url.status);
+                    if let Some(created_at) = url.created_at {
+                        active_model.created_at = Set(created_at);
+                    }
+                    active_model.updated_at = Set(now);
+
+                    let new = active_model.update(&txn).await?;
+                    let new_snapshot = LinkSnapshot::of(&new, old_snapshot.tags.clone());
+                    record(
+                        &txn,
+                        &new,
+                        RevisionAction::Update,
+                        actor,
+                        Some(old_snapshot),
+                        Some(new_snapshot),
+                    )
+                    .await?;
+                    ImportOutcome::Updated {
+                        old: Box::new(old),
+                        new: Box::new(new),
+                    }
+                }
+            };
+            outcomes.push(outcome);
+        }
+
+        let committed = commit
+            && !outcomes
+                .iter()
+                .any(|o| matches!(o, ImportOutcome::Conflict | ImportOutcome::Trashed));
+        if committed {
+            txn.commit().await?;
+        } else {
+            txn.rollback().await?;
+        }
+
+        Ok((outcomes, committed))
+    }
+
+    async fn mark_expired(&self, now: DateTime<Utc>, actor: &str) -> Result<Vec<LinkKey>, DbErr> {
+        let due = Condition::all()
+            .add(Column::Status.eq(UrlStatus::Enabled as i32))
+            .add(Column::ExpiresAt.lte(now))
+            .add(Column::DeletedAt.is_null());
+
+        let txn = self.db.begin().await?;
+        let expired = Entity::find()
+            .filter(due.clone())
+            .lock_exclusive()
+            .all(&txn)
+            .await?;
+        if expired.is_empty() {
+            txn.rollback().await?;
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<i64> = expired.iter().map(|url| url.id).collect();
+        Entity::update_many()
+            .col_expr(Column::Status, Expr::value(UrlStatus::Expired as i32))
+            .col_expr(Column::UpdatedAt, Expr::value(now))
+            .filter(Column::Id.is_in(ids.clone()))
+            .filter(due)
+            .exec(&txn)
+            .await?;
+
+        let mut tags = url_tags(&txn, &ids).await?;
+        for old in &expired {
+            let tags = tags.remove(&old.id).unwrap_or_default();
+            let new = Model {
+                status: UrlStatus::Expired as i32,
+                updated_at: now,
+                ..old.clone()
+            };
+            record(
+                &txn,
+                &new,
+                RevisionAction::Update,
+                actor,
+                Some(LinkSnapshot::of(old, tags.clone())),
+                Some(LinkSnapshot::of(&new, tags)),
+            )
+            .await?;
+        }
+        txn.commit().await?;
+
+        Ok(expired.iter().map(Model::link_key).collect())
+    }
+
+    async fn consume_click(&self, id: i64) -> Result<bool, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::ClickCount, Expr::col(Column::ClickCount).add(1i64))
+            .filter(Column::Id.eq(id))
+            .filter(
+                Condition::any()
+                    .add(Column::MaxClicks.is_null())
+                    .add(Expr::col(Column::ClickCount).lt(Expr::col(Column::MaxClicks))),
+            )
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected == 1)
+    }
+
+    async fn next_sequence(&self, name: &str) -> Result<u64, DbErr> {
+        let txn = self.db.begin().await?;
+
+        // Single upsert so concurrent first uses cannot both insert the row
+        code_sequence::Entity::insert(code_sequence::ActiveModel {
+            name: Set(name.to_string()),
+            value: Set(1),
+        })
+        .on_conflict(
+            OnConflict::column(code_sequence::Column::Name)
+                .value(
+                    code_sequence::Column::Value,
+                    Expr::col((code_sequence::Entity, code_sequence::Column::Value)).add(1i64),
+                )
+                .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        let value = code_sequence::Entity::find_by_id(name.to_string())
+            .one(&txn)
+            .await?
+            .map(|sequence| sequence.value)
+            .ok_or_else(|| DbErr::RecordNotFound(format!("Sequence not found: {}", name)))?;
+        txn.commit().await?;
+
+        Ok(value as u64)
+    }
+
+    async fn record_health_check(&self, id: i64, check: &HealthCheck) -> Result<Model, DbErr> {
+        let failures = if check.is_ok() {
+            Expr::value(0)
+        } else {
+            Expr::col(Column::CheckFailures).add(1)
+        };
+
+        Entity::update_many()
+            .col_expr(Column::LastCheckedAt, Expr::value(check.checked_at))
+            .col_expr(Column::LastStatus, Expr::value(check.status))
+            .col_expr(Column::LastError, Expr::value(check.error.clone()))
+            .col_expr(Column::CheckFailures, failures)
+            .filter(Column::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+
+        self.find_by_id(id)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("URL with id {} not found", id)))
+    }
+}
+
+/// Live URL by code on `db`, usually the transaction of a change
+async fn live_by_code<C: ConnectionTrait>(
+    db: &C,
+    domain: &str,
+    code: &str,
+) -> Result<Model, DbErr> {
+    Entity::find()
+        .filter(Column::Domain.eq(domain))
+        .filter(Column::ShortCode.eq(code))
+        .filter(Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("URL with code '{}' not found", code)))
+}
+
+/// Snapshot of a URL with its tags, read on `db`
+async fn snapshot_of<C: ConnectionTrait>(db: &C, url: &Model) -> Result<LinkSnapshot, DbErr> {
+    let mut tags = url_tags(db, &[url.id]).await?;
+
+    Ok(LinkSnapshot::of(
+        url,
+        tags.remove(&url.id).unwrap_or_default(),
+    ))
+}
+
+/// Record a change to a URL on `db`; updates that changed nothing are skipped
+async fn record<C: ConnectionTrait>(
+    db: &C,
+    url: &Model,
+    action: RevisionAction,
+    actor: &str,
+    old_value: Option<LinkSnapshot>,
+    new_value: Option<LinkSnapshot>,
+) -> Result<(), DbErr> {
+    if action == RevisionAction::Update && old_value == new_value {
+        return Ok(());
+    }
+
+    insert_revision(
+        db,
+        CreateRevisionDto {
+            url_id: url.id,
+            domain: url.domain.clone(),
+            short_code: url.short_code.clone(),
+            action,
+            actor: actor.to_string(),
+            old_value,
+            new_value,
+        },
+    )
+    .await?;
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, DatabaseConfig, DatabaseType, SqliteConfig};
+    use crate::config::Config;
     use crate::db::DbFactory;
     use crate::models::url::UrlStatus;
 
     async fn setup_test_db() -> DatabaseConnection {
-        let config = Config {
-            server: crate::config::ServerConfig {
-                address: ":8080".to_string(),
-                trusted_platform: None,
-                site_url: "http://localhost:8080".to_string(),
-                api_key: "test-key".to_string(),
-            },
-            shortener: crate::config::ShortenerConfig {
-                code_length: 6,
-                code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-                    .to_string(),
-            },
-            admin: crate::config::AdminConfig {
-                username: "admin".to_string(),
-                password: "admin123".to_string(),
-            },
-            database: DatabaseConfig {
-                db_type: DatabaseType::Sqlite,
-                log_level: 0,
-                sqlite: Some(SqliteConfig {
-                    path: ":memory:".to_string(),
-                }),
-                postgres: None,
-                mysql: None,
-            },
-            cache: crate::config::CacheConfig {
-                enabled: false,
-                cache_type: crate::config::CacheType::Redis,
-                expire: 3600,
-                prefix: "shorten:".to_string(),
-                redis: None,
-                valkey: None,
-            },
-            geoip: crate::config::GeoIpConfig {
-                enabled: false,
-                geoip_type: crate::config::GeoIpType::Ip2region,
-                ip2region: None,
-            },
-            logging: crate::logging::LoggingConfig::default(),
-        };
+        let config = Config::for_tests();
 
         let db = DbFactory::create_connection(&config).await.unwrap();
         DbFactory::run_migrations(&db).await.unwrap();
@@ -292,9 +1063,10 @@ mod tests {
             original_url: "https://example.com".to_string(),
             description: Some("Test URL".to_string()),
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
 
-        let result = repo.create(create_dto).await;
+        let result = repo.create(create_dto, "admin").await;
         assert!(result.is_ok());
 
         let url = result.unwrap();
@@ -315,20 +1087,71 @@ mod tests {
             original_url: "https://example.com".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto).await.unwrap();
+        repo.create(create_dto, "admin").await.unwrap();
 
         // Find by code
-        let result = repo.find_by_code("find123").await;
+        let result = repo.find_by_code("", "find123").await;
         assert!(result.is_ok());
         assert!(result.unwrap().is_some());
 
         // Find non-existent code
-        let result = repo.find_by_code("nonexistent").await;
+        let result = repo.find_by_code("", "nonexistent").await;
         assert!(result.is_ok());
         assert!(result.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn test_short_code_unique_per_domain() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        let dto = |domain: &str, url: &str| CreateUrlDto {
+            domain: domain.to_string(),
+            short_code: "same".to_string(),
+            original_url: url.to_string(),
+            status: UrlStatus::Enabled as i32,
+            ..Default::default()
+        };
+        repo.create(dto("", "https://example.com/a"), "admin")
+            .await
+            .unwrap();
+        repo.create(dto("go.example.com", "https://example.com/b"), "admin")
+            .await
+            .unwrap();
+        assert!(
+            repo.create(dto("go.example.com", "https://example.com/c"), "admin")
+                .await
+                .is_err()
+        );
+
+        let found = repo
+            .find_by_code("go.example.com", "same")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.original_url, "https://example.com/b");
+        assert!(
+            repo.find_by_code("other.example.com", "same")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        repo.delete("go.example.com", "same", "admin")
+            .await
+            .unwrap();
+        assert!(repo.find_by_code("", "same").await.unwrap().is_some());
+
+        let params = ListParams {
+            domain: Some(String::new()),
+            ..Default::default()
+        };
+        let (_, total) = repo.list(params).await.unwrap();
+        assert_eq!(total, 1);
+    }
+
     #[tokio::test]
     async fn test_find_by_id() {
         let db = setup_test_db().await;
@@ -340,8 +1163,9 @@ mod tests {
             original_url: "https://example.com".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        let created = repo.create(create_dto).await.unwrap();
+        let created = repo.create(create_dto, "admin").await.unwrap();
 
         // Find by ID
         let result = repo.find_by_id(created.id).await;
@@ -370,8 +1194,9 @@ mod tests {
                 } else {
                     UrlStatus::Enabled as i32
                 },
+                ..Default::default()
             };
-            repo.create(create_dto).await.unwrap();
+            repo.create(create_dto, "admin").await.unwrap();
         }
 
         // List all URLs
@@ -417,16 +1242,18 @@ mod tests {
             original_url: "https://example.com".to_string(),
             description: Some("Original".to_string()),
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto).await.unwrap();
+        repo.create(create_dto, "admin").await.unwrap();
 
         // Update the URL
         let update_dto = UpdateUrlDto {
             original_url: Some("https://updated.com".to_string()),
             description: Some("Updated".to_string()),
             status: Some(UrlStatus::Disabled as i32),
+            ..Default::default()
         };
-        let result = repo.update("update123", update_dto).await;
+        let result = repo.update("", "update123", update_dto, "admin").await;
         assert!(result.is_ok());
 
         let updated = result.unwrap();
@@ -439,8 +1266,9 @@ mod tests {
             original_url: Some("https://test.com".to_string()),
             description: None,
             status: None,
+            ..Default::default()
         };
-        let result = repo.update("nonexistent", update_dto).await;
+        let result = repo.update("", "nonexistent", update_dto, "admin").await;
         assert!(result.is_err());
     }
 
@@ -455,19 +1283,20 @@ mod tests {
             original_url: "https://example.com".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto).await.unwrap();
+        repo.create(create_dto, "admin").await.unwrap();
 
         // Delete the URL
-        let result = repo.delete("delete123").await;
+        let result = repo.delete("", "delete123", "admin").await;
         assert!(result.is_ok());
 
         // Verify it's deleted
-        let found = repo.find_by_code("delete123").await.unwrap();
+        let found = repo.find_by_code("", "delete123").await.unwrap();
         assert!(found.is_none());
 
         // Try to delete non-existent URL
-        let result = repo.delete("nonexistent").await;
+        let result = repo.delete("", "nonexistent", "admin").await;
         assert!(result.is_err());
     }
 
@@ -484,14 +1313,15 @@ mod tests {
                 original_url: format!("https://example{}.com", i),
                 description: None,
                 status: UrlStatus::Enabled as i32,
+                ..Default::default()
             };
-            let created = repo.create(create_dto).await.unwrap();
+            let created = repo.create(create_dto, "admin").await.unwrap();
             ids.push(created.id);
         }
 
         // Delete first 3 URLs
         let delete_ids = ids[0..3].to_vec();
-        let result = repo.delete_batch(delete_ids).await;
+        let result = repo.delete_batch(delete_ids, "admin").await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 3);
 
@@ -500,6 +1330,353 @@ mod tests {
         let (urls, total) = repo.list(params).await.unwrap();
         assert_eq!(urls.len(), 2);
         assert_eq!(total, 2);
+
+        // Deleting them again changes nothing
+        assert_eq!(
+            repo.delete_batch(ids[0..3].to_vec(), "admin")
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_batch() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        for code in ["e1", "e2", "e3", "other", "e4"] {
+            repo.create(
+                CreateUrlDto {
+                    short_code: code.to_string(),
+                    original_url: format!("https://example.com/{}", code),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        }
+        repo.delete("", "e4", "admin").await.unwrap();
+
+        let params = ListParams {
+            original_url: Some("com/e".to_string()),
+            // Paging is ignored
+            page: 2,
+            page_size: 1,
+            ..Default::default()
+        };
+
+        let first = repo.export_batch(&params, 0, 2).await.unwrap();
+        let codes: Vec<&str> = first.iter().map(|url| url.short_code.as_str()).collect();
+        assert_eq!(codes, ["e1", "e2"]);
+
+        // Trashed links are left out like in the list
+        let second = repo.export_batch(&params, first[1].id, 2).await.unwrap();
+        let codes: Vec<&str> = second.iter().map(|url| url.short_code.as_str()).collect();
+        assert_eq!(codes, ["e3"]);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_filter() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        let metadata = |value: serde_json::Value| match value {
+            serde_json::Value::Object(map) => Metadata(map),
+            _ => unreachable!(),
+        };
+        for (code, value) in [
+            ("m1", serde_json::json!({"ticket": 42, "team": "growth"})),
+            ("m2", serde_json::json!({"ticket": "42"})),
+            ("m3", serde_json::json!({"ticket": "7"})),
+        ] {
+            repo.create(
+                CreateUrlDto {
+                    short_code: code.to_string(),
+                    original_url: "https://example.com".to_string(),
+                    metadata: Some(metadata(value)),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        }
+        repo.create(
+            CreateUrlDto {
+                short_code: "plain".to_string(),
+                original_url: "https://example.com".to_string(),
+                ..Default::default()
+            },
+            "admin",
+        )
+        .await
+        .unwrap();
+
+        let codes = |filter: &str| {
+            let params = ListParams {
+                metadata: Some(MetadataFilter::try_from(filter.to_string()).unwrap()),
+                sort_by: Some("id".to_string()),
+                order: Some("asc".to_string()),
+                ..Default::default()
+            };
+            let repo = &repo;
+            async move {
+                let (urls, _) = repo.list(params).await.unwrap();
+                urls.into_iter()
+                    .map(|url| url.short_code)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Numbers and strings compare as text
+        assert_eq!(codes("ticket:42").await, ["m1", "m2"]);
+        assert_eq!(codes("ticket").await, ["m1", "m2", "m3"]);
+        assert_eq!(codes("team:growth").await, ["m1"]);
+        assert!(codes("team:other").await.is_empty());
+
+        // An empty object removes the metadata
+        let url = repo
+            .update(
+                "",
+                "m1",
+                UpdateUrlDto {
+                    metadata: Some(Metadata::default()),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        assert_eq!(url.metadata, None);
+        assert_eq!(codes("ticket").await, ["m2", "m3"]);
+    }
+
+    #[tokio::test]
+    async fn test_trash_undelete_and_purge() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        for code in ["keep", "trash"] {
+            repo.create(
+                CreateUrlDto {
+                    short_code: code.to_string(),
+                    original_url: "https://example.com".to_string(),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        }
+        repo.delete("", "trash", "admin").await.unwrap();
+
+        let deleted = repo.find_deleted_by_code("", "trash").await.unwrap();
+        assert!(deleted.unwrap().is_deleted());
+        assert!(
+            repo.find_deleted_by_code("", "keep")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let trash = ListParams {
+            deleted: true,
+            ..Default::default()
+        };
+        let (urls, total) = repo.list(trash.clone()).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(urls[0].short_code, "trash");
+
+        // Restored links are live again
+        let url = repo.undelete("", "trash", "admin").await.unwrap();
+        assert!(!url.is_deleted());
+        assert!(repo.find_by_code("", "trash").await.unwrap().is_some());
+        assert!(repo.undelete("", "trash", "admin").await.is_err());
+
+        // Only links deleted before the cutoff are purged
+        repo.delete("", "trash", "admin").await.unwrap();
+        let purged = repo
+            .purge_deleted(Utc::now() - chrono::Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 0);
+        let purged = repo
+            .purge_deleted(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        assert_eq!(repo.list(trash).await.unwrap().1, 0);
+        assert!(repo.find_by_code("", "keep").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_purge_keeps_revisions() {
+        use crate::repositories::history_repository::{
+            CreateHistoryDto, HistoryRepository, HistoryRepositoryImpl,
+        };
+        use crate::repositories::revision_repository::{
+            RevisionRepository, RevisionRepositoryImpl,
+        };
+
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db.clone());
+        let history_repo = HistoryRepositoryImpl::new(db.clone());
+
+        let mut ids = Vec::new();
+        for code in ["keep", "trash"] {
+            let url = repo
+                .create(
+                    CreateUrlDto {
+                        short_code: code.to_string(),
+                        original_url: "https://example.com".to_string(),
+                        ..Default::default()
+                    },
+                    "admin",
+                )
+                .await
+                .unwrap();
+            history_repo
+                .create(CreateHistoryDto {
+                    url_id: url.id as i32,
+                    short_code: code.to_string(),
+                    ip_address: "127.0.0.1".to_string(),
+                    user_agent: "test".to_string(),
+                    referer: None,
+                    country: None,
+                    region: None,
+                    province: None,
+                    city: None,
+                    isp: None,
+                    device_type: None,
+                    os: None,
+                    browser: None,
+                    targeting_rule: None,
+                    variant: None,
+                    accessed_at: Utc::now(),
+                })
+                .await
+                .unwrap();
+            ids.push(url.id as i32);
+        }
+        repo.delete("", "trash", "admin").await.unwrap();
+
+        let purged = repo
+            .purge_deleted(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+
+        // The access history of the purged link is gone, the other link keeps its own
+        let remaining = history::Entity::find().all(&db).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].url_id, ids[0]);
+
+        // The revisions of the purged link are kept
+        let revisions = RevisionRepositoryImpl::new(db)
+            .list_by_code("", "trash")
+            .await
+            .unwrap();
+        assert_eq!(revisions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_policies() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        repo.create(
+            CreateUrlDto {
+                short_code: "taken".to_string(),
+                original_url: "https://example.com/old".to_string(),
+                ..Default::default()
+            },
+            "admin",
+        )
+        .await
+        .unwrap();
+        let import = |code: &str| ImportUrlDto {
+            short_code: code.to_string(),
+            original_url: "https://example.com/new".to_string(),
+            status: UrlStatus::Disabled as i32,
+            created_at: Some(Utc::now() - chrono::Duration::days(365)),
+            ..Default::default()
+        };
+        let urls = vec![import("fresh"), import("taken")];
+
+        // A conflict under `fail` rolls everything back
+        let (outcomes, committed) = repo
+            .import(urls.clone(), ConflictPolicy::Fail, true, "admin")
+            .await
+            .unwrap();
+        assert!(!committed);
+        assert!(matches!(outcomes[0], ImportOutcome::Created(_)));
+        assert_eq!(outcomes[1], ImportOutcome::Conflict);
+        assert!(repo.find_by_code("", "fresh").await.unwrap().is_none());
+
+        // So does a dry run
+        let (_, committed) = repo
+            .import(urls.clone(), ConflictPolicy::Skip, false, "admin")
+            .await
+            .unwrap();
+        assert!(!committed);
+        assert!(repo.find_by_code("", "fresh").await.unwrap().is_none());
+
+        let (outcomes, committed) = repo
+            .import(urls.clone(), ConflictPolicy::Skip, true, "admin")
+            .await
+            .unwrap();
+        assert!(committed);
+        assert_eq!(outcomes[1], ImportOutcome::Skipped);
+        let fresh = repo.find_by_code("", "fresh").await.unwrap().unwrap();
+        assert_eq!(fresh.status, UrlStatus::Disabled as i32);
+        assert!(fresh.created_at < Utc::now() - chrono::Duration::days(364));
+
+        let (outcomes, committed) = repo
+            .import(
+                vec![import("taken")],
+                ConflictPolicy::Overwrite,
+                true,
+                "admin",
+            )
+            .await
+            .unwrap();
+        assert!(committed);
+        let ImportOutcome::Updated { old, new } = &outcomes[0] else {
+            panic!("expected an update, got {:?}", outcomes[0]);
+        };
+        assert_eq!(old.original_url, "https://example.com/old");
+        assert_eq!(new.original_url, "https://example.com/new");
+
+        // Links in the trash are not overwritten and stay there
+        repo.delete("", "taken", "admin").await.unwrap();
+        let (outcomes, committed) = repo
+            .import(
+                vec![import("fresher"), import("taken")],
+                ConflictPolicy::Overwrite,
+                true,
+                "admin",
+            )
+            .await
+            .unwrap();
+        assert!(!committed);
+        assert_eq!(outcomes[1], ImportOutcome::Trashed);
+        assert!(repo.find_by_code("", "fresher").await.unwrap().is_none());
+        assert!(repo.find_by_code("", "taken").await.unwrap().is_none());
+        assert!(
+            repo.find_deleted_by_code("", "taken")
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let (outcomes, committed) = repo
+            .import(vec![import("taken")], ConflictPolicy::Skip, true, "admin")
+            .await
+            .unwrap();
+        assert!(committed);
+        assert_eq!(outcomes[0], ImportOutcome::Skipped);
     }
 
     #[tokio::test]
@@ -514,8 +1691,9 @@ mod tests {
                 original_url: format!("https://example{}.com", i),
                 description: Some(format!("Test URL {}", i)),
                 status: UrlStatus::Enabled as i32,
+                ..Default::default()
             };
-            repo.create(create_dto).await.unwrap();
+            repo.create(create_dto, "admin").await.unwrap();
         }
 
         // List with code filter
@@ -553,16 +1731,18 @@ mod tests {
             original_url: "https://example1.com".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto1).await.unwrap();
+        repo.create(create_dto1, "admin").await.unwrap();
 
         let create_dto2 = CreateUrlDto {
             short_code: "disabled1".to_string(),
             original_url: "https://example2.com".to_string(),
             description: None,
             status: UrlStatus::Disabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto2).await.unwrap();
+        repo.create(create_dto2, "admin").await.unwrap();
 
         // List with status filter
         let params = ListParams {
@@ -601,32 +1781,41 @@ mod tests {
             original_url: "https://github.com/user/repo1".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto1).await.unwrap();
+        repo.create(create_dto1, "admin").await.unwrap();
 
         let create_dto2 = CreateUrlDto {
             short_code: "gitlab1".to_string(),
             original_url: "https://gitlab.com/user/repo2".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto2).await.unwrap();
+        repo.create(create_dto2, "admin").await.unwrap();
 
         let create_dto3 = CreateUrlDto {
             short_code: "github2".to_string(),
             original_url: "https://github.com/another/project".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto3).await.unwrap();
+        repo.create(create_dto3, "admin").await.unwrap();
 
         // Test fuzzy search for "github"
         let params = ListParams {
             page: 1,
             page_size: 10,
+            domain: None,
             short_code: None,
             original_url: Some("github".to_string()),
             status: None,
+            schedule: None,
+            tag: None,
+            broken: None,
+            metadata: None,
+            deleted: false,
             sort_by: None,
             order: None,
         };
@@ -642,9 +1831,15 @@ mod tests {
         let params = ListParams {
             page: 1,
             page_size: 10,
+            domain: None,
             short_code: None,
             original_url: Some("gitlab".to_string()),
             status: None,
+            schedule: None,
+            tag: None,
+            broken: None,
+            metadata: None,
+            deleted: false,
             sort_by: None,
             order: None,
         };
@@ -657,9 +1852,15 @@ mod tests {
         let params = ListParams {
             page: 1,
             page_size: 10,
+            domain: None,
             short_code: None,
             original_url: Some("user".to_string()),
             status: None,
+            schedule: None,
+            tag: None,
+            broken: None,
+            metadata: None,
+            deleted: false,
             sort_by: None,
             order: None,
         };
@@ -671,9 +1872,15 @@ mod tests {
         let params = ListParams {
             page: 1,
             page_size: 10,
+            domain: None,
             short_code: None,
             original_url: Some("nonexistent".to_string()),
             status: None,
+            schedule: None,
+            tag: None,
+            broken: None,
+            metadata: None,
+            deleted: false,
             sort_by: None,
             order: None,
         };
@@ -693,24 +1900,32 @@ mod tests {
             original_url: "https://github.com/test/repo".to_string(),
             description: None,
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto1).await.unwrap();
+        repo.create(create_dto1, "admin").await.unwrap();
 
         let create_dto2 = CreateUrlDto {
             short_code: "test2".to_string(),
             original_url: "https://github.com/another/project".to_string(),
             description: None,
             status: UrlStatus::Disabled as i32,
+            ..Default::default()
         };
-        repo.create(create_dto2).await.unwrap();
+        repo.create(create_dto2, "admin").await.unwrap();
 
         // Test combining original_url and status filters
         let params = ListParams {
             page: 1,
             page_size: 10,
+            domain: None,
             short_code: None,
             original_url: Some("github".to_string()),
             status: Some(UrlStatus::Enabled as i32),
+            schedule: None,
+            tag: None,
+            broken: None,
+            metadata: None,
+            deleted: false,
             sort_by: None,
             order: None,
         };
@@ -723,9 +1938,15 @@ mod tests {
         let params = ListParams {
             page: 1,
             page_size: 10,
+            domain: None,
             short_code: Some("test2".to_string()),
             original_url: Some("github".to_string()),
             status: Some(UrlStatus::Disabled as i32),
+            schedule: None,
+            tag: None,
+            broken: None,
+            metadata: None,
+            deleted: false,
             sort_by: None,
             order: None,
         };
@@ -734,4 +1955,453 @@ mod tests {
         assert_eq!(total, 1);
         assert_eq!(urls[0].short_code, "test2");
     }
+
+    #[tokio::test]
+    async fn test_list_with_schedule_filter() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+        let now = chrono::Utc::now();
+
+        let windows = [
+            ("scheduled", Some(now + chrono::Duration::hours(1)), None),
+            (
+                "live",
+                Some(now - chrono::Duration::hours(1)),
+                Some(now + chrono::Duration::hours(1)),
+            ),
+            ("ended", None, Some(now - chrono::Duration::hours(1))),
+            ("always", None, None),
+        ];
+        for (code, active_from, active_until) in windows {
+            let dto = CreateUrlDto {
+                short_code: code.to_string(),
+                original_url: "https://example.com".to_string(),
+                status: UrlStatus::Enabled as i32,
+                active_from,
+                active_until,
+                ..Default::default()
+            };
+            repo.create(dto, "admin").await.unwrap();
+        }
+
+        let codes_for = |schedule| {
+            let repo = &repo;
+            async move {
+                let params = ListParams {
+                    schedule: Some(schedule),
+                    sort_by: Some("short_code".to_string()),
+                    order: Some("asc".to_string()),
+                    ..Default::default()
+                };
+                let (urls, _) = repo.list(params).await.unwrap();
+                urls.into_iter()
+                    .map(|url| url.short_code)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(codes_for(ScheduleState::Scheduled).await, vec!["scheduled"]);
+        assert_eq!(codes_for(ScheduleState::Live).await, vec!["always", "live"]);
+        assert_eq!(codes_for(ScheduleState::Ended).await, vec!["ended"]);
+    }
+
+    #[test]
+    fn test_list_params_schedule_deserialization() {
+        let params: ListParams = serde_json::from_str(r#"{"schedule": "scheduled"}"#).unwrap();
+        assert_eq!(params.schedule, Some(ScheduleState::Scheduled));
+
+        let params: ListParams = serde_json::from_str("{}").unwrap();
+        assert_eq!(params.schedule, None);
+
+        assert!(serde_json::from_str::<ListParams>(r#"{"schedule": "soon"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mark_expired() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+        let now = chrono::Utc::now();
+
+        let past = CreateUrlDto {
+            short_code: "past".to_string(),
+            original_url: "https://example.com".to_string(),
+            description: None,
+            status: UrlStatus::Enabled as i32,
+            expires_at: Some(now - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        repo.create(past, "admin").await.unwrap();
+
+        let future = CreateUrlDto {
+            short_code: "future".to_string(),
+            original_url: "https://example.com".to_string(),
+            description: None,
+            status: UrlStatus::Enabled as i32,
+            expires_at: Some(now + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        repo.create(future, "admin").await.unwrap();
+
+        let never = CreateUrlDto {
+            short_code: "never".to_string(),
+            original_url: "https://example.com".to_string(),
+            description: None,
+            status: UrlStatus::Enabled as i32,
+            expires_at: None,
+            ..Default::default()
+        };
+        repo.create(never, "admin").await.unwrap();
+
+        let codes = repo.mark_expired(now, "system").await.unwrap();
+        assert_eq!(codes, vec![LinkKey::from("past")]);
+
+        let past = repo.find_by_code("", "past").await.unwrap().unwrap();
+        assert_eq!(past.status, UrlStatus::Expired as i32);
+        let future = repo.find_by_code("", "future").await.unwrap().unwrap();
+        assert_eq!(future.status, UrlStatus::Enabled as i32);
+
+        // Already expired rows are not reported twice
+        let codes = repo.mark_expired(now, "system").await.unwrap();
+        assert!(codes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_consume_click() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        let limited = repo
+            .create(
+                CreateUrlDto {
+                    short_code: "limited".to_string(),
+                    original_url: "https://example.com".to_string(),
+                    status: UrlStatus::Enabled as i32,
+                    max_clicks: Some(2),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+
+        assert!(repo.consume_click(limited.id).await.unwrap());
+        assert!(repo.consume_click(limited.id).await.unwrap());
+        assert!(!repo.consume_click(limited.id).await.unwrap());
+
+        let limited = repo.find_by_code("", "limited").await.unwrap().unwrap();
+        assert_eq!(limited.click_count, 2);
+
+        // Links without a quota are always counted
+        let unlimited = repo
+            .create(
+                CreateUrlDto {
+                    short_code: "unlimited".to_string(),
+                    original_url: "https://example.com".to_string(),
+                    status: UrlStatus::Enabled as i32,
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        assert!(repo.consume_click(unlimited.id).await.unwrap());
+
+        // Unknown IDs are not counted
+        assert!(!repo.consume_click(9999).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_find_by_original_url() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        for (code, url, status) in [
+            ("off", "https://example.com/page", UrlStatus::Disabled),
+            ("first", "https://Example.com/page", UrlStatus::Enabled),
+            ("second", "https://example.com/page", UrlStatus::Enabled),
+        ] {
+            repo.create(
+                CreateUrlDto {
+                    short_code: code.to_string(),
+                    original_url: url.to_string(),
+                    status: status as i32,
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        }
+
+        let found = repo
+            .find_by_original_url("", "HTTPS://EXAMPLE.COM/page")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.short_code, "first");
+
+        assert!(
+            repo.find_by_original_url("go.example.com", "https://example.com/page")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            repo.find_by_original_url("", "https://example.com/Page")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Changing the destination moves the hash along
+        repo.update(
+            "",
+            "first",
+            UpdateUrlDto {
+                original_url: Some("https://example.com/moved".to_string()),
+                ..Default::default()
+            },
+            "admin",
+        )
+        .await
+        .unwrap();
+        let found = repo
+            .find_by_original_url("", "https://example.com/page")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.short_code, "second");
+
+        // Restricted links are not handed out
+        repo.update(
+            "",
+            "second",
+            UpdateUrlDto {
+                max_clicks: Some(Some(5)),
+                ..Default::default()
+            },
+            "admin",
+        )
+        .await
+        .unwrap();
+        assert!(
+            repo.find_by_original_url("", "https://example.com/page")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changes_record_revisions() {
+        use crate::repositories::RevisionRepository;
+
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db.clone());
+        let revisions = crate::repositories::RevisionRepositoryImpl::new(db.clone());
+
+        repo.create(
+            CreateUrlDto {
+                short_code: "rev".to_string(),
+                original_url: "https://example.com/old".to_string(),
+                status: UrlStatus::Enabled as i32,
+                tags: vec!["docs".to_string()],
+                ..Default::default()
+            },
+            "alice",
+        )
+        .await
+        .unwrap();
+        // An update that changes nothing is not recorded
+        repo.update("", "rev", UpdateUrlDto::default(), "bob")
+            .await
+            .unwrap();
+        repo.update(
+            "",
+            "rev",
+            UpdateUrlDto {
+                original_url: Some("https://example.com/new".to_string()),
+                tags: Some(Vec::new()),
+                ..Default::default()
+            },
+            "bob",
+        )
+        .await
+        .unwrap();
+
+        let list = revisions.list_by_code("", "rev").await.unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].action, RevisionAction::Update);
+        assert_eq!(list[0].actor, "bob");
+        assert_eq!(list[0].old_value.as_ref().unwrap().tags, ["docs"]);
+        assert!(list[0].new_value.as_ref().unwrap().tags.is_empty());
+        assert_eq!(list[1].action, RevisionAction::Create);
+        assert_eq!(list[1].actor, "alice");
+
+        // Without a place to record the revision the change is rolled back
+        db.execute_unprepared("DROP TABLE url_revisions")
+            .await
+            .unwrap();
+        let result = repo
+            .update(
+                "",
+                "rev",
+                UpdateUrlDto {
+                    original_url: Some("https://example.com/lost".to_string()),
+                    tags: Some(vec!["lost".to_string()]),
+                    ..Default::default()
+                },
+                "bob",
+            )
+            .await;
+        assert!(result.is_err());
+        let url = repo.find_by_code("", "rev").await.unwrap().unwrap();
+        assert_eq!(url.original_url, "https://example.com/new");
+        assert!(url_tags(&db, &[url.id]).await.unwrap().is_empty());
+
+        assert!(repo.delete("", "rev", "bob").await.is_err());
+        assert!(repo.find_by_code("", "rev").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_next_sequence() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        assert_eq!(repo.next_sequence("short_code").await.unwrap(), 1);
+        assert_eq!(repo.next_sequence("short_code").await.unwrap(), 2);
+        assert_eq!(repo.next_sequence("other").await.unwrap(), 1);
+        assert_eq!(repo.next_sequence("short_code").await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_next_sequence_concurrent_first_use() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        let (a, b, c) = tokio::join!(
+            repo.next_sequence("fresh"),
+            repo.next_sequence("fresh"),
+            repo.next_sequence("fresh")
+        );
+        let mut values = vec![a.unwrap(), b.unwrap(), c.unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_record_health_check() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        let url = repo
+            .create(
+                CreateUrlDto {
+                    short_code: "health".to_string(),
+                    original_url: "https://example.com/gone".to_string(),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        assert_eq!(url.check_failures, 0);
+
+        let failed = HealthCheck {
+            checked_at: Utc::now(),
+            status: Some(404),
+            error: Some("HTTP 404 Not Found".to_string()),
+        };
+        repo.record_health_check(url.id, &failed).await.unwrap();
+        let url = repo.record_health_check(url.id, &failed).await.unwrap();
+        assert_eq!(url.check_failures, 2);
+        assert_eq!(url.last_status, Some(404));
+        assert!(url.is_broken());
+
+        let broken = |broken| ListParams {
+            broken: Some(broken),
+            ..Default::default()
+        };
+        assert_eq!(repo.list(broken(true)).await.unwrap().1, 1);
+        assert_eq!(repo.list(broken(false)).await.unwrap().1, 0);
+
+        let url = repo
+            .record_health_check(
+                url.id,
+                &HealthCheck {
+                    checked_at: Utc::now(),
+                    status: Some(200),
+                    error: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(url.check_failures, 0);
+        assert!(!url.is_broken());
+        assert!(url.last_checked_at.is_some());
+
+        // A new destination clears the previous results
+        let url = repo.record_health_check(url.id, &failed).await.unwrap();
+        let url = repo
+            .update(
+                "",
+                &url.short_code,
+                UpdateUrlDto {
+                    original_url: Some("https://example.com/new".to_string()),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        assert!(url.last_checked_at.is_none());
+        assert!(!url.is_broken());
+        assert_eq!(url.check_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_restore_snapshot() {
+        let db = setup_test_db().await;
+        let repo = UrlRepositoryImpl::new(db);
+
+        let url = repo
+            .create(
+                CreateUrlDto {
+                    short_code: "restore".to_string(),
+                    original_url: "https://example.com/old".to_string(),
+                    description: Some("Old".to_string()),
+                    password_hash: Some("hash".to_string()),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
+        let snapshot = LinkSnapshot::of(&url, Vec::new());
+
+        repo.update(
+            "",
+            "restore",
+            UpdateUrlDto {
+                original_url: Some("https://example.com/new".to_string()),
+                expires_at: Some(Some(Utc::now() + chrono::Duration::days(1))),
+                max_clicks: Some(Some(5)),
+                ..Default::default()
+            },
+            "admin",
+        )
+        .await
+        .unwrap();
+
+        let restored = repo.restore(url.id, &snapshot, "admin").await.unwrap();
+        assert_eq!(restored.original_url, "https://example.com/old");
+        assert_eq!(restored.original_url_hash, url.original_url_hash);
+        assert_eq!(restored.description.as_deref(), Some("Old"));
+        // Fields unset in the snapshot are cleared, the password stays
+        assert!(restored.expires_at.is_none());
+        assert!(restored.max_clicks.is_none());
+        assert_eq!(restored.password_hash.as_deref(), Some("hash"));
+
+        assert!(repo.restore(999, &snapshot, "admin").await.is_err());
+    }
 }
@@ -0,0 +1,251 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::models::user::{ActiveModel, Column, Entity, Model, UserRole};
+
+/// DTO for creating a user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateUserDto {
+    pub username: String,
+    pub password_hash: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+    pub role: UserRole,
+}
+
+/// DTO for updating a user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateUserDto {
+    pub password_hash: Option<String>,
+    /// `Some(None)` clears the display name
+    pub display_name: Option<Option<String>>,
+    /// `Some(None)` clears the email
+    pub email: Option<Option<String>>,
+    pub role: Option<UserRole>,
+    pub disabled: Option<bool>,
+}
+
+/// User Repository trait
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    /// Create a new user
+    async fn create(&self, user: CreateUserDto) -> Result<Model, DbErr>;
+
+    /// Find user by username
+    async fn find_by_username(&self, username: &str) -> Result<Option<Model>, DbErr>;
+
+    /// List all users ordered by username
+    async fn list(&self) -> Result<Vec<Model>, DbErr>;
+
+    /// Number of users
+    async fn count(&self) -> Result<u64, DbErr>;
+
+    /// Number of administrators that are not disabled
+    async fn count_active_admins(&self) -> Result<u64, DbErr>;
+
+    /// Update a user by username
+    async fn update(&self, username: &str, data: UpdateUserDto) -> Result<Model, DbErr>;
+
+    /// Record a successful login
+    async fn record_login(&self, id: i64) -> Result<Model, DbErr>;
+
+    /// Delete a user by username
+    async fn delete(&self, username: &str) -> Result<(), DbErr>;
+}
+
+/// User Repository implementation
+pub struct UserRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl UserRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    async fn get(&self, username: &str) -> Result<Model, DbErr> {
+        self.find_by_username(username)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("User '{}' not found", username)))
+    }
+}
+
+#[async_trait]
+impl UserRepository for UserRepositoryImpl {
+    async fn create(&self, user: CreateUserDto) -> Result<Model, DbErr> {
+        let now = Utc::now();
+        let active_model = ActiveModel {
+            username: Set(user.username),
+            password_hash: Set(user.password_hash),
+            display_name: Set(user.display_name),
+            email: Set(user.email),
+            role: Set(user.role),
+            disabled: Set(false),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        };
+
+        active_model.insert(&self.db).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Username.eq(username))
+            .one(&self.db)
+            .await
+    }
+
+    async fn list(&self) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .order_by_asc(Column::Username)
+            .all(&self.db)
+            .await
+    }
+
+    async fn count(&self) -> Result<u64, DbErr> {
+        Entity::find().count(&self.db).await
+    }
+
+    async fn count_active_admins(&self) -> Result<u64, DbErr> {
+        Entity::find()
+            .filter(Column::Role.eq(UserRole::Admin))
+            .filter(Column::Disabled.eq(false))
+            .count(&self.db)
+            .await
+    }
+
+    async fn update(&self, username: &str, data: UpdateUserDto) -> Result<Model, DbErr> {
+        let mut active_model: ActiveModel = self.get(username).await?.into();
+
+        if let Some(password_hash) = data.password_hash {
+            active_model.password_hash = Set(password_hash);
+        }
+        if let Some(display_name) = data.display_name {
+            active_model.display_name = Set(display_name);
+        }
+        if let Some(email) = data.email {
+            active_model.email = Set(email);
+        }
+        if let Some(role) = data.role {
+            active_model.role = Set(role);
+        }
+        if let Some(disabled) = data.disabled {
+            active_model.disabled = Set(disabled);
+        }
+        active_model.updated_at = Set(Utc::now());
+
+        active_model.update(&self.db).await
+    }
+
+    async fn record_login(&self, id: i64) -> Result<Model, DbErr> {
+        ActiveModel {
+            id: Set(id),
+            last_login_at: Set(Some(Utc::now())),
+            ..Default::default()
+        }
+        .update(&self.db)
+        .await
+    }
+
+    async fn delete(&self, username: &str) -> Result<(), DbErr> {
+        let user = self.get(username).await?;
+        Entity::delete_by_id(user.id).exec(&self.db).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::DbFactory;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let config = Config::for_tests();
+
+        let db = DbFactory::create_connection(&config).await.unwrap();
+        DbFactory::run_migrations(&db).await.unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_user_crud() {
+        let db = setup_test_db().await;
+        let repo = UserRepositoryImpl::new(db);
+
+        let alice = repo
+            .create(CreateUserDto {
+                username: "alice".to_string(),
+                password_hash: "hash".to_string(),
+                role: UserRole::Admin,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(!alice.disabled);
+        assert_eq!(alice.last_login_at, None);
+        repo.create(CreateUserDto {
+            username: "bob".to_string(),
+            password_hash: "hash".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        // Usernames are unique
+        let duplicate = repo
+            .create(CreateUserDto {
+                username: "alice".to_string(),
+                password_hash: "hash".to_string(),
+                ..Default::default()
+            })
+            .await;
+        assert!(duplicate.is_err());
+
+        let names: Vec<String> = repo
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|user| user.username)
+            .collect();
+        assert_eq!(names, ["alice", "bob"]);
+        assert_eq!(repo.count().await.unwrap(), 2);
+        assert_eq!(repo.count_active_admins().await.unwrap(), 1);
+
+        let bob = repo
+            .update(
+                "bob",
+                UpdateUserDto {
+                    display_name: Some(Some("Bob".to_string())),
+                    role: Some(UserRole::Admin),
+                    disabled: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(bob.display_name.as_deref(), Some("Bob"));
+        assert_eq!(bob.password_hash, "hash");
+        // Disabled admins do not count
+        assert_eq!(repo.count_active_admins().await.unwrap(), 1);
+
+        let alice = repo.record_login(alice.id).await.unwrap();
+        assert!(alice.last_login_at.is_some());
+        assert_eq!(alice.username, "alice");
+
+        repo.delete("bob").await.unwrap();
+        assert!(repo.find_by_username("bob").await.unwrap().is_none());
+        assert!(matches!(
+            repo.delete("bob").await,
+            Err(DbErr::RecordNotFound(_))
+        ));
+    }
+}
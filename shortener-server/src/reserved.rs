@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Codes that clash with routes of the server, or may do so in the future
+///
+/// Covers every top-level path of the router (`/api/...`, `/ping`) plus the
+/// usual operational endpoints kept free for proxies and future pages.
+pub const BUILTIN_RESERVED_CODES: &[&str] = &["api", "ping", "admin", "health", "metrics"];
+
+/// Codes that cannot be used as short codes
+///
+/// Reserved codes match as a whole, blocked words anywhere in the code; both
+/// ignore case.
+#[derive(Debug, Clone, Default)]
+pub struct ReservedCodes {
+    reserved: HashSet<String>,
+    blocked: Vec<String>,
+}
+
+impl ReservedCodes {
+    /// Build from the configured reserved codes and blocked words
+    pub fn new(reserved: &[String], blocked: &[String]) -> Self {
+        let reserved = BUILTIN_RESERVED_CODES
+            .iter()
+            .copied()
+            .chain(reserved.iter().map(String::as_str))
+            .map(|code| code.trim().to_lowercase())
+            .filter(|code| !code.is_empty())
+            .collect();
+
+        let mut blocked: Vec<String> = blocked
+            .iter()
+            .map(|word| word.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        blocked.sort();
+        blocked.dedup();
+
+        Self { reserved, blocked }
+    }
+
+    /// Build from the configured reserved codes and a blocklist file
+    ///
+    /// The file holds one word per line; blank lines and lines starting with
+    /// `#` are skipped.
+    pub fn load(reserved: &[String], blocklist_file: Option<&Path>) -> std::io::Result<Self> {
+        let blocked = match blocklist_file {
+            Some(path) => std::fs::read_to_string(path)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Self::new(reserved, &blocked))
+    }
+
+    /// Why a code cannot be used, `None` when it is allowed
+    pub fn check(&self, code: &str) -> Option<String> {
+        let code = code.to_lowercase();
+
+        if self.reserved.contains(&code) {
+            return Some(format!("Code '{}' is reserved", code));
+        }
+        if self.blocked.iter().any(|word| code.contains(word.as_str())) {
+            return Some(format!("Code '{}' contains a blocked word", code));
+        }
+
+        None
+    }
+
+    /// Whether a code cannot be used
+    pub fn is_reserved(&self, code: &str) -> bool {
+        self.check(code).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_builtin_and_configured_codes() {
+        let codes = ReservedCodes::new(&["Promo".to_string()], &[]);
+
+        assert!(codes.is_reserved("api"));
+        assert!(codes.is_reserved("API"));
+        assert!(codes.is_reserved("ping"));
+        assert!(codes.is_reserved("promo"));
+        // Reserved codes only match as a whole
+        assert!(!codes.is_reserved("apis"));
+        assert!(!codes.is_reserved("abc123"));
+    }
+
+    #[test]
+    fn test_blocked_words() {
+        let codes = ReservedCodes::new(&[], &["Badword".to_string(), " ".to_string()]);
+
+        assert!(codes.is_reserved("badword"));
+        assert!(codes.is_reserved("xBADWORDx"));
+        assert!(!codes.is_reserved("good"));
+        assert!(codes.check("mybadword").unwrap().contains("blocked word"));
+        assert!(codes.check("admin").unwrap().contains("reserved"));
+    }
+
+    #[test]
+    fn test_load_blocklist_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "# offensive words\nfoo\n\n  bar  ").unwrap();
+
+        let codes = ReservedCodes::load(&[], Some(file.path())).unwrap();
+        assert!(codes.is_reserved("xfoox"));
+        assert!(codes.is_reserved("bar1"));
+        assert!(!codes.is_reserved("offensive"));
+
+        assert!(ReservedCodes::load(&[], Some(Path::new("/nonexistent/blocklist.txt"))).is_err());
+    }
+}
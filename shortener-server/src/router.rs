@@ -1,12 +1,19 @@
 use crate::config::Config;
 use crate::handlers::{
-    create_shorten, current_user, delete_batch, delete_histories, delete_shorten, get_shorten,
-    list_histories, list_shortens, login, logout, redirect_to_url, update_shorten,
+    MAX_IMPORT_SIZE, add_targeting_rule, change_password, create_batch, create_shorten, create_tag,
+    create_user, current_user, delete_batch, delete_histories, delete_shorten, delete_tag,
+    delete_targeting_rule, delete_user, export_histories, export_shortens, get_shorten,
+    get_shorten_qr, get_user, import_shortens, list_histories, list_policy_matches, list_revisions,
+    list_shortens, list_tags, list_targeting_rules, list_trash, list_users, login, logout,
+    redirect_to_url, redirect_with_path, reload_policy, rename_tag, restore_deleted,
+    restore_revision, unlock_url, update_batch, update_shorten, update_targeting_rule, update_user,
 };
 use crate::middleware::{HybridAuth, error_handler_middleware, logging_middleware};
-use crate::services::{HistoryService, ShortenService};
+use crate::services::{HistoryService, ShortenService, UserService};
 use axum::{
-    Router, middleware,
+    Router,
+    extract::DefaultBodyLimit,
+    middleware,
     routing::{delete, get, post, put},
 };
 use std::sync::Arc;
@@ -17,6 +24,7 @@ use tower_http::cors::CorsLayer;
 pub struct AppState {
     pub shorten_service: Arc<ShortenService>,
     pub history_service: Arc<HistoryService>,
+    pub user_service: Arc<UserService>,
     pub config: Arc<Config>,
 }
 
@@ -28,22 +36,57 @@ pub fn create_router(state: AppState) -> Router {
     let shortener_api = Router::new()
         .route("/api/shortens", post(create_shorten))
         .route("/api/shortens", get(list_shortens))
+        .route("/api/shortens/batch", post(create_batch))
         .route("/api/shortens/batch-delete", post(delete_batch))
+        .route("/api/shortens/batch-update", post(update_batch))
+        .route("/api/shortens/export", get(export_shortens))
+        .route(
+            "/api/shortens/import",
+            post(import_shortens).layer(DefaultBodyLimit::max(MAX_IMPORT_SIZE)),
+        )
         .route("/api/shortens/{short_code}", get(get_shorten))
         .route("/api/shortens/{short_code}", put(update_shorten))
         .route("/api/shortens/{short_code}", delete(delete_shorten))
+        .route("/api/shortens/{short_code}/qr", get(get_shorten_qr))
+        .route(
+            "/api/shortens/{short_code}/rules",
+            get(list_targeting_rules).post(add_targeting_rule),
+        )
+        .route(
+            "/api/shortens/{short_code}/rules/{index}",
+            put(update_targeting_rule).delete(delete_targeting_rule),
+        )
+        .route("/api/shortens/{short_code}/revisions", get(list_revisions))
+        .route(
+            "/api/shortens/{short_code}/revisions/{id}/restore",
+            post(restore_revision),
+        )
+        .route("/api/trash", get(list_trash))
+        .route("/api/trash/{short_code}/restore", post(restore_deleted))
+        .route("/api/tags", get(list_tags).post(create_tag))
+        .route("/api/tags/{name}", put(rename_tag).delete(delete_tag))
+        .route("/api/policy/matches", get(list_policy_matches))
+        .route("/api/policy/reload", post(reload_policy))
         .with_state(state.shorten_service.clone());
 
     // Create history API routes (protected)
     let history_api = Router::new()
         .route("/api/histories", get(list_histories))
+        .route("/api/histories/export", get(export_histories))
         .route("/api/histories/batch-delete", post(delete_histories))
         .with_state(state.history_service.clone());
 
     // Create account API routes (protected)
     let account_api = Router::new()
         .route("/api/account/logout", post(logout))
-        .route("/api/users/current", get(current_user));
+        .route("/api/users", get(list_users).post(create_user))
+        .route("/api/users/current", get(current_user))
+        .route("/api/users/current/password", put(change_password))
+        .route(
+            "/api/users/{username}",
+            get(get_user).put(update_user).delete(delete_user),
+        )
+        .with_state(state.user_service.clone());
 
     // Combine protected API routes
     let protected_api = Router::new()
@@ -59,11 +102,12 @@ pub fn create_router(state: AppState) -> Router {
     // Create public API routes (no authentication required)
     let public_api = Router::new()
         .route("/api/account/login", post(login))
-        .with_state(Arc::new(state.config.admin.clone()));
+        .with_state(state.user_service.clone());
 
     // Create redirect routes (public, for short URL redirection)
     let redirect_routes = Router::new()
-        .route("/{short_code}", get(redirect_to_url))
+        .route("/{short_code}", get(redirect_to_url).post(unlock_url))
+        .route("/{short_code}/{*path}", get(redirect_with_path))
         .with_state(state.clone());
 
     // Create health check route
@@ -109,69 +153,42 @@ async fn root() -> axum::Json<serde_json::Value> {
 mod tests {
     use super::*;
     use crate::cache::NullCache;
-    use crate::config::{
-        AdminConfig, CacheConfig, CacheType, DatabaseConfig, DatabaseType, GeoIpConfig, GeoIpType,
-        ServerConfig, ShortenerConfig, SqliteConfig,
-    };
+    use crate::config::ShortenerConfig;
     use crate::db::DbFactory;
     use crate::geoip::NullGeoIp;
-    use crate::repositories::{HistoryRepositoryImpl, UrlRepositoryImpl};
+    use crate::repositories::{
+        HistoryRepositoryImpl, RevisionRepositoryImpl, TagRepositoryImpl, UrlRepositoryImpl,
+        UserRepositoryImpl,
+    };
     use axum::body::Body;
-    use axum::http::{Request, StatusCode};
+    use axum::http::{Request, StatusCode, header};
     use tower::ServiceExt;
 
     async fn setup_test_state() -> AppState {
-        let config = Config {
-            server: ServerConfig {
-                address: ":8080".to_string(),
-                trusted_platform: None,
-                site_url: "http://localhost:8080".to_string(),
-                api_key: "test-api-key".to_string(),
-            },
-            shortener: ShortenerConfig {
-                code_length: 6,
-                code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-                    .to_string(),
-            },
-            admin: AdminConfig {
-                username: "admin".to_string(),
-                password: "admin123".to_string(),
-            },
-            database: DatabaseConfig {
-                db_type: DatabaseType::Sqlite,
-                log_level: 0,
-                sqlite: Some(SqliteConfig {
-                    path: ":memory:".to_string(),
-                }),
-                postgres: None,
-                mysql: None,
-            },
-            cache: CacheConfig {
-                enabled: false,
-                cache_type: CacheType::Redis,
-                expire: 3600,
-                prefix: "shorten:".to_string(),
-                redis: None,
-                valkey: None,
-            },
-            geoip: GeoIpConfig {
-                enabled: false,
-                geoip_type: GeoIpType::Ip2region,
-                ip2region: None,
-            },
-            logging: crate::logging::LoggingConfig::default(),
-        };
+        setup_test_state_with(ShortenerConfig::default()).await.0
+    }
+
+    async fn setup_test_state_with(
+        shortener: ShortenerConfig,
+    ) -> (AppState, sea_orm::DatabaseConnection) {
+        let mut config = Config::for_tests();
+        config.server.api_key = "test-api-key".to_string();
+        config.shortener = shortener;
 
         let db = DbFactory::create_connection(&config).await.unwrap();
         DbFactory::run_migrations(&db).await.unwrap();
 
         let url_repo = Arc::new(UrlRepositoryImpl::new(db.clone()));
-        let history_repo = Arc::new(HistoryRepositoryImpl::new(db));
+        let history_repo = Arc::new(HistoryRepositoryImpl::new(db.clone()));
+        let tag_repo = Arc::new(TagRepositoryImpl::new(db.clone()));
+        let revision_repo = Arc::new(RevisionRepositoryImpl::new(db.clone()));
         let cache = Arc::new(NullCache::new());
         let geoip = Some(Arc::new(NullGeoIp::new()) as Arc<dyn crate::geoip::GeoIp>);
 
         let shorten_service = Arc::new(ShortenService::new(
             url_repo,
+            tag_repo,
+            revision_repo,
             cache,
             config.shortener.clone(),
             config.server.site_url.clone(),
@@ -179,11 +196,39 @@ mod tests {
 
         let history_service = Arc::new(HistoryService::new(history_repo, geoip));
 
-        AppState {
+        let user_service = Arc::new(UserService::new(Arc::new(UserRepositoryImpl::new(
+            db.clone(),
+        ))));
+        user_service.ensure_admin(&config.admin).await.unwrap();
+
+        let state = AppState {
             shorten_service,
             history_service,
+            user_service,
             config: Arc::new(config),
-        }
+        };
+
+        (state, db)
+    }
+
+    async fn create_expired_url(db: &sea_orm::DatabaseConnection, code: &str) {
+        use crate::repositories::UrlRepository;
+        use crate::repositories::url_repository::CreateUrlDto;
+
+        UrlRepositoryImpl::new(db.clone())
+            .create(
+                CreateUrlDto {
+                    short_code: code.to_string(),
+                    original_url: "https://example.com".to_string(),
+                    description: None,
+                    status: 0,
+                    expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+                    ..Default::default()
+                },
+                "admin",
+            )
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
@@ -299,4 +344,1583 @@ mod tests {
         let location = redirect_response.headers().get("location").unwrap();
         assert_eq!(location.to_str().unwrap(), "https://example.com");
     }
+
+    #[tokio::test]
+    async fn test_redirect_route_expired_returns_gone() {
+        let (state, db) = setup_test_state_with(ShortenerConfig::default()).await;
+        create_expired_url(&db, "expired1").await;
+        let app = create_router(state);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/expired1")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GONE);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["errcode"], crate::errors::error_codes::URL_EXPIRED);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_expired_with_fallback() {
+        let (state, db) = setup_test_state_with(ShortenerConfig {
+            expired_redirect_url: Some("https://example.com/expired".to_string()),
+            ..Default::default()
+        })
+        .await;
+        create_expired_url(&db, "expired2").await;
+        let app = create_router(state);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/expired2")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+
+        let location = response.headers().get("location").unwrap();
+        assert_eq!(location.to_str().unwrap(), "https://example.com/expired");
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_password_protected() {
+        let state = setup_test_state().await;
+        let app = create_router(state);
+
+        let create_request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"original_url":"https://example.com/docs","short_code":"locked1","password":"s3cret"}"#,
+            ))
+            .unwrap();
+        let create_response = app.clone().oneshot(create_request).await.unwrap();
+        assert_eq!(create_response.status(), StatusCode::CREATED);
+
+        // GET serves the password form instead of redirecting
+        let request = Request::builder()
+            .method("GET")
+            .uri("/locked1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("location").is_none());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains(r#"<form method="post" action="/locked1">"#));
+
+        // Wrong password re-renders the form
+        let request = Request::builder()
+            .method("POST")
+            .uri("/locked1")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from("password=wrong"))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Correct password redirects and sets the unlock cookie
+        let request = Request::builder()
+            .method("POST")
+            .uri("/locked1")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from("password=s3cret"))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get("location").unwrap(),
+            "https://example.com/docs"
+        );
+        let cookie = response
+            .headers()
+            .get("set-cookie")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(cookie.starts_with("shortener_unlock_locked1="));
+        assert!(cookie.contains("HttpOnly"));
+
+        // The cookie keeps the link unlocked
+        let pair = cookie.split(';').next().unwrap();
+        let request = Request::builder()
+            .method("GET")
+            .uri("/locked1")
+            .header("cookie", pair)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_unlock_attempt_limit() {
+        let (state, _db) = setup_test_state_with(ShortenerConfig {
+            unlock_max_attempts: 2,
+            unlock_lockout: 300,
+            ..Default::default()
+        })
+        .await;
+        let app = create_router(state);
+
+        let create_request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"original_url":"https://example.com/docs","short_code":"locked2","password":"s3cret"}"#,
+            ))
+            .unwrap();
+        let create_response = app.clone().oneshot(create_request).await.unwrap();
+        assert_eq!(create_response.status(), StatusCode::CREATED);
+
+        let unlock = |password: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/locked2")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(format!("password={}", password)))
+                .unwrap()
+        };
+
+        for _ in 0..2 {
+            let response = app.clone().oneshot(unlock("wrong")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        // Further attempts are refused, the right password included
+        let response = app.oneshot(unlock("s3cret")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().get("set-cookie").is_none());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("Too many attempts"));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_redirect_type() {
+        let (state, _db) = setup_test_state_with(ShortenerConfig {
+            redirect_type: 301,
+            ..Default::default()
+        })
+        .await;
+        let app = create_router(state);
+
+        for body in [
+            r#"{"original_url":"https://example.com/a","short_code":"default1"}"#,
+            r#"{"original_url":"https://example.com/b","short_code":"found1","redirect_type":302}"#,
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/shortens")
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        // Links without an override use the configured default
+        let request = Request::builder()
+            .method("GET")
+            .uri("/default1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers().get("location").unwrap(),
+            "https://example.com/a"
+        );
+
+        // Per-link override wins
+        let request = Request::builder()
+            .method("GET")
+            .uri("/found1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(
+            response.headers().get("location").unwrap(),
+            "https://example.com/b"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_not_yet_active() {
+        use std::io::Write;
+
+        let mut page = tempfile::NamedTempFile::new().unwrap();
+        write!(page, "<p>{{{{code}}}} launches at {{{{active_from}}}}</p>").unwrap();
+
+        let (state, _db) = setup_test_state_with(ShortenerConfig {
+            not_active_page: Some(page.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+        .await;
+        let app = create_router(state);
+
+        let active_from = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        let body = format!(
+            r#"{{"original_url":"https://example.com","short_code":"launch","active_from":"{}"}}"#,
+            active_from
+        );
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/launch")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("<p>launch launches at "));
+        assert!(!body.contains("{{active_from}}"));
+
+        // The list endpoint can filter on the activation window
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/shortens?schedule=scheduled")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["meta"]["total"], 1);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/shortens?schedule=live")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["meta"]["total"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_targeting_rules() {
+        let state = setup_test_state().await;
+        let app = create_router(state);
+
+        let body = r#"{"original_url":"https://example.com","short_code":"campaign"}"#;
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        for rule in [
+            r#"{"os":"ios","url":"https://apps.apple.com/app/id1"}"#,
+            r#"{"os":"android","url":"https://play.google.com/store/apps"}"#,
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/shortens/campaign/rules")
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/json")
+                .body(Body::from(rule))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let cases = [
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148 Safari/604.1",
+                "https://apps.apple.com/app/id1",
+            ),
+            (
+                "Mozilla/5.0 (Linux; Android 14; Pixel 8) Chrome/120.0 Mobile Safari/537.36",
+                "https://play.google.com/store/apps",
+            ),
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Chrome/120.0 Safari/537.36",
+                "https://example.com",
+            ),
+        ];
+        for (user_agent, location) in cases {
+            let request = Request::builder()
+                .method("GET")
+                .uri("/campaign")
+                .header("user-agent", user_agent)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+            assert_eq!(response.headers().get("location").unwrap(), location);
+        }
+
+        // Removing the iOS rule sends iPhones to the original URL
+        let request = Request::builder()
+            .method("DELETE")
+            .uri("/api/shortens/campaign/rules/0")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/campaign")
+            .header("user-agent", cases[0].0)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(
+            response.headers().get("location").unwrap(),
+            "https://example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_passthrough() {
+        let mut state = setup_test_state().await;
+        let mut config = (*state.config).clone();
+        config.shortener.forward_query = true;
+        state.config = Arc::new(config);
+        let app = create_router(state);
+
+        for body in [
+            r#"{"original_url":"https://example.com/docs?lang=en","short_code":"docs","forward_path":true}"#,
+            r#"{"original_url":"https://example.com/plain","short_code":"plain","forward_query":false}"#,
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/shortens")
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let cases = [
+            // Query forwarding comes from the server default
+            (
+                "/docs?utm_source=mail&lang=de",
+                "https://example.com/docs?utm_source=mail&lang=de",
+            ),
+            (
+                "/docs/guide/intro?utm_source=mail",
+                "https://example.com/docs/guide/intro?lang=en&utm_source=mail",
+            ),
+            // The link turns query forwarding off
+            ("/plain?utm_source=mail", "https://example.com/plain"),
+        ];
+        for (uri, location) in cases {
+            let request = Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+            assert_eq!(response.headers().get("location").unwrap(), location);
+        }
+
+        // Path suffixes are only accepted by links that forward them
+        let request = Request::builder()
+            .method("GET")
+            .uri("/plain/extra")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_tag_routes() {
+        let state = setup_test_state().await;
+        let app = create_router(state);
+
+        let send = |method: &str, uri: &str, body: Option<&str>| {
+            let mut builder = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("X-API-KEY", "test-api-key");
+            if body.is_some() {
+                builder = builder.header("content-type", "application/json");
+            }
+            let request = builder
+                .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let response = send("POST", "/api/tags", Some(r#"{"name":"docs"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = send("POST", "/api/tags", Some(r#"{"name":"docs"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = r#"{"original_url":"https://example.com","short_code":"guide","tags":["docs"]}"#;
+        let response = send("POST", "/api/shortens", Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = send("GET", "/api/shortens?tag=docs", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["meta"]["total"], 1);
+        assert_eq!(json["data"][0]["tags"][0], "docs");
+
+        let response = send("PUT", "/api/tags/docs", Some(r#"{"name":"manuals"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send("GET", "/api/tags", None).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json[0]["name"], "manuals");
+        assert_eq!(json[0]["url_count"], 1);
+
+        let response = send("DELETE", "/api/tags/manuals", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = send("DELETE", "/api/tags/manuals", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_qr_routes() {
+        let state = setup_test_state().await;
+        let app = create_router(state);
+
+        let body = r#"{"original_url":"https://example.com","short_code":"flyer"}"#;
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/shortens/flyer/qr?size=128&ec=h&fg=%23336699")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "image/png");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.starts_with(b"\x89PNG"));
+
+        // The QR code API is protected like the rest of the API
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/shortens/flyer/qr")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Public shortcut, rendered as SVG
+        let request = Request::builder()
+            .method("GET")
+            .uri("/flyer.qr?format=svg")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "image/svg+xml"
+        );
+
+        // The plain short code still redirects
+        let request = Request::builder()
+            .method("GET")
+            .uri("/flyer")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/missing.qr")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/flyer.qr?size=5")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_sticky_variants() {
+        let state = setup_test_state().await;
+        let app = create_router(state);
+
+        let body = r#"{"original_url":"https://example.com","short_code":"landing","sticky_variants":true,"variants":[{"name":"a","url":"https://example.com/a"},{"name":"b","url":"https://example.com/b","weight":3}]}"#;
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // First visit draws a variant and pins it with a cookie
+        let request = Request::builder()
+            .method("GET")
+            .uri("/landing")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+        let location = response
+            .headers()
+            .get("location")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let cookie = response
+            .headers()
+            .get("set-cookie")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let variant = location.trim_start_matches("https://example.com/");
+        assert!(cookie.starts_with(&format!("shortener_variant_landing={};", variant)));
+        assert!(cookie.contains("Path=/landing"));
+
+        // Returning visitors keep their variant
+        for _ in 0..10 {
+            let request = Request::builder()
+                .method("GET")
+                .uri("/landing")
+                .header("cookie", "shortener_variant_landing=a")
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(
+                response.headers().get("location").unwrap(),
+                "https://example.com/a"
+            );
+        }
+
+        // Invalid variants are rejected
+        let body = r#"{"variants":[{"name":"a","url":"https://example.com/a"},{"name":"a","url":"https://example.com/b"}]}"#;
+        let request = Request::builder()
+            .method("PUT")
+            .uri("/api/shortens/landing")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_route_geo_targeting() {
+        use crate::geoip::{GeoIp, GeoIpError, GeoIpInfo};
+        use crate::repositories::history_repository::HistoryListParams;
+
+        /// Places every visitor in Guangdong
+        struct GuangdongGeoIp;
+
+        #[async_trait::async_trait]
+        impl GeoIp for GuangdongGeoIp {
+            async fn lookup(&self, _ip: &str) -> Result<GeoIpInfo, GeoIpError> {
+                Ok(GeoIpInfo::new(
+                    "中国".to_string(),
+                    "广东省".to_string(),
+                    "深圳市".to_string(),
+                    "电信".to_string(),
+                ))
+            }
+        }
+
+        let (mut state, db) = setup_test_state_with(ShortenerConfig::default()).await;
+        state.history_service = Arc::new(HistoryService::new(
+            Arc::new(HistoryRepositoryImpl::new(db)),
+            Some(Arc::new(GuangdongGeoIp)),
+        ));
+        let app = create_router(state.clone());
+
+        let body = r#"{"original_url":"https://example.com","short_code":"mirror","targeting_rules":[{"os":"ios","url":"https://apps.apple.com/app/id1"},{"country":"中国","url":"https://mirror.example.cn"}]}"#;
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/mirror")
+            .header("x-forwarded-for", "113.88.0.1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers().get("location").unwrap(),
+            "https://mirror.example.cn"
+        );
+
+        // The history row is written in the background and records the chosen rule
+        let mut histories = Vec::new();
+        for _ in 0..50 {
+            histories = state
+                .history_service
+                .list_histories(HistoryListParams::default())
+                .await
+                .unwrap()
+                .data;
+            if !histories.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(histories.len(), 1);
+        assert_eq!(histories[0].targeting_rule, Some(1));
+        assert_eq!(histories[0].province.as_deref(), Some("广东省"));
+    }
+
+    #[tokio::test]
+    async fn test_multi_domain_routes() {
+        let (state, _db) = setup_test_state_with(ShortenerConfig {
+            domains: vec!["https://go.example.com".to_string()],
+            ..Default::default()
+        })
+        .await;
+        let app = create_router(state);
+
+        let send = |method: &str, uri: &str, body: Option<&str>| {
+            let mut builder = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("X-API-KEY", "test-api-key");
+            if body.is_some() {
+                builder = builder.header("content-type", "application/json");
+            }
+            let request = builder
+                .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let json_of = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        // The same code can be used once per domain
+        let body = r#"{"original_url":"https://example.com/default","short_code":"promo"}"#;
+        let response = send("POST", "/api/shortens", Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let json = json_of(response).await;
+        assert_eq!(json["short_url"], "http://localhost:8080/promo");
+        assert_eq!(json["domain"], "localhost:8080");
+
+        let body = r#"{"original_url":"https://example.com/brand","short_code":"promo","domain":"go.example.com"}"#;
+        let response = send("POST", "/api/shortens", Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let json = json_of(response).await;
+        assert_eq!(json["short_url"], "https://go.example.com/promo");
+        assert_eq!(json["domain"], "go.example.com");
+
+        let response = send("POST", "/api/shortens", Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = r#"{"original_url":"https://example.com","domain":"evil.example.net"}"#;
+        let response = send("POST", "/api/shortens", Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Admin routes pick the domain from the query string
+        let response = send("GET", "/api/shortens/promo?domain=go.example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            json_of(response).await["original_url"],
+            "https://example.com/brand"
+        );
+
+        let response = send("GET", "/api/shortens?domain=go.example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(json_of(response).await["meta"]["total"], 1);
+
+        // Visits resolve the domain from the Host header
+        for (host, destination) in [
+            ("go.example.com", "https://example.com/brand"),
+            ("localhost:8080", "https://example.com/default"),
+        ] {
+            let request = Request::builder()
+                .method("GET")
+                .uri("/promo")
+                .header("host", host)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+            assert_eq!(response.headers().get("location").unwrap(), destination);
+        }
+
+        let response = send("DELETE", "/api/shortens/promo?domain=go.example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = send("GET", "/api/shortens/promo", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_create_reuse_existing() {
+        let app = create_router(setup_test_state().await);
+
+        let mut codes = Vec::new();
+        for expected in [StatusCode::CREATED, StatusCode::OK] {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/shortens")
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"original_url":"https://example.com/reuse","reuse_existing":true}"#,
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), expected);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            codes.push(json["short_code"].as_str().unwrap().to_string());
+        }
+        assert_eq!(codes[0], codes[1]);
+    }
+
+    #[tokio::test]
+    async fn test_create_with_reserved_code() {
+        let (state, _db) = setup_test_state_with(ShortenerConfig {
+            reserved_codes: vec!["promo".to_string()],
+            ..Default::default()
+        })
+        .await;
+        let app = create_router(state);
+
+        for code in ["api", "admin", "promo"] {
+            let body = format!(
+                r#"{{"original_url":"https://example.com","short_code":"{}"}}"#,
+                code
+            );
+            let request = Request::builder()
+                .method("POST")
+                .uri("/api/shortens")
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["errcode"], crate::errors::error_codes::CODE_RESERVED);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_destination_policy() {
+        let blocklist = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(blocklist.path(), "evil.example\n").unwrap();
+        let (state, _db) = setup_test_state_with(ShortenerConfig {
+            destination_blocklist_file: Some(blocklist.path().display().to_string()),
+            ..Default::default()
+        })
+        .await;
+        let app = create_router(state);
+
+        let create = |url: &str, code: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/shortens")
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{"original_url":"{}","short_code":"{}"}}"#,
+                    url, code
+                )))
+                .unwrap()
+        };
+        let api = |method: &str, uri: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("X-API-KEY", "test-api-key")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // Blocked destinations cannot be shortened
+        let response = app
+            .clone()
+            .oneshot(create("https://login.evil.example/", "phish"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["errcode"], crate::errors::error_codes::URL_BLOCKED);
+
+        let response = app
+            .clone()
+            .oneshot(create("https://later.example/", "later"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Blocklisting the destination afterwards blocks the existing link
+        std::fs::write(blocklist.path(), "evil.example\nlater.example\n").unwrap();
+        let response = app
+            .clone()
+            .oneshot(api("POST", "/api/policy/reload"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["blocked_domains"], 2);
+
+        let request = Request::builder()
+            .uri("/later")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .clone()
+            .oneshot(api("GET", "/api/policy/matches"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let matches = json.as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["short_code"], "later");
+        assert!(
+            matches[0]["reason"]
+                .as_str()
+                .unwrap()
+                .contains("later.example")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_broken_links() {
+        let state = setup_test_state().await;
+        let service = state.shorten_service.clone();
+        let app = create_router(state);
+        let addr = crate::health::stub::spawn().await;
+
+        let body = format!(
+            r#"{{"original_url":"http://{}/missing","short_code":"dead"}}"#,
+            addr
+        );
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let checker =
+            crate::health::HealthChecker::new(std::time::Duration::from_secs(5), 1, 0, true)
+                .unwrap();
+        service.check_health(&checker).await.unwrap();
+
+        for (broken, total) in [("true", 1), ("false", 0)] {
+            let request = Request::builder()
+                .method("GET")
+                .uri(format!("/api/shortens?broken={}", broken))
+                .header("X-API-KEY", "test-api-key")
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["meta"]["total"], total);
+            if total == 1 {
+                assert_eq!(json["data"][0]["last_status"], 404);
+                assert_eq!(json["data"][0]["last_error"], "HTTP 404 Not Found");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revisions_record_actor() {
+        let app = create_router(setup_test_state().await);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/account/login")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"username":"admin","password":"admin123"}"#))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let token = json["token"].as_str().unwrap().to_string();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("Authorization", format!("Bearer {}", token))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"original_url":"https://example.com/old","short_code":"rev"}"#,
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = Request::builder()
+            .method("PUT")
+            .uri("/api/shortens/rev")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"original_url":"https://example.com/new"}"#))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/shortens/rev/revisions")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json[0]["action"], "update");
+        assert_eq!(json[0]["actor"], "api-key");
+        assert_eq!(json[1]["action"], "create");
+        assert_eq!(json[1]["actor"], "admin");
+        assert_eq!(
+            json[1]["new_value"]["original_url"],
+            "https://example.com/old"
+        );
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/shortens/rev/revisions/{}/restore",
+                json[1]["id"]
+            ))
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["original_url"], "https://example.com/old");
+    }
+
+    #[tokio::test]
+    async fn test_user_routes() {
+        let app = create_router(setup_test_state().await);
+
+        async fn send(
+            app: &Router,
+            method: &str,
+            uri: &str,
+            auth: (&str, &str),
+            body: &str,
+        ) -> (StatusCode, serde_json::Value) {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(auth.0, auth.1)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let json = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+            (status, json)
+        }
+        let api_key = ("X-API-KEY", "test-api-key");
+
+        let (status, json) = send(
+            &app,
+            "POST",
+            "/api/users",
+            api_key,
+            r#"{"username":"carol","password":"carol-secret","display_name":"Carol"}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(json["role"], "user");
+        assert!(json.get("password_hash").is_none());
+
+        let (status, json) = send(
+            &app,
+            "POST",
+            "/api/account/login",
+            api_key,
+            r#"{"username":"carol","password":"carol-secret"}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let bearer = format!("Bearer {}", json["token"].as_str().unwrap());
+        let carol = ("Authorization", bearer.as_str());
+
+        let (status, json) = send(&app, "GET", "/api/users/current", carol, "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["name"], "Carol");
+        assert_eq!(json["username"], "carol");
+        assert!(json["last_login_at"].is_string());
+
+        // Regular users cannot manage accounts
+        let (status, _) = send(&app, "GET", "/api/users", carol, "").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = send(
+            &app,
+            "PUT",
+            "/api/users/current/password",
+            carol,
+            r#"{"current_password":"wrong","new_password":"carol-secret-2"}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, json) = send(
+            &app,
+            "PUT",
+            "/api/users/current/password",
+            carol,
+            r#"{"current_password":"carol-secret","new_password":"carol-secret-2"}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The old token is revoked, the returned one keeps the session
+        let (status, _) = send(&app, "GET", "/api/users/current", carol, "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let bearer = format!("Bearer {}", json["token"].as_str().unwrap());
+        let carol = ("Authorization", bearer.as_str());
+        let (status, _) = send(&app, "GET", "/api/users/current", carol, "").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, json) = send(&app, "GET", "/api/users", api_key, "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json.as_array().unwrap().len(), 2);
+
+        // Disabling the user ends the session
+        let (status, json) = send(
+            &app,
+            "PUT",
+            "/api/users/carol",
+            api_key,
+            r#"{"disabled":true}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["disabled"], true);
+        let (status, _) = send(&app, "GET", "/api/users/current", carol, "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&app, "DELETE", "/api/users/carol", api_key, "").await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send(&app, "GET", "/api/users/carol", api_key, "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // The last administrator stays
+        let (status, _) = send(&app, "DELETE", "/api/users/admin", api_key, "").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_trash_restore() {
+        let app = create_router(setup_test_state().await);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"original_url":"https://example.com","short_code":"bin"}"#,
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = Request::builder()
+            .method("DELETE")
+            .uri("/api/shortens/bin")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/bin")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/trash")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["meta"]["total"], 1);
+        assert_eq!(json["data"][0]["short_code"], "bin");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/trash/bin/restore")
+            .header("X-API-KEY", "test-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/bin")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert!(response.status().is_redirection());
+    }
+
+    #[tokio::test]
+    async fn test_import_shortens() {
+        let app = create_router(setup_test_state().await);
+        let body = "{\"code\":\"imp1\",\"url\":\"https://example.com/1\"}\n\
+                    {\"code\":\"imp2\",\"url\":\"https://example.com/2\",\"status\":\"disabled\"}\n";
+
+        let import = |query: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/shortens/import{}", query))
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/x-ndjson")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(import("")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["committed"], true);
+        assert_eq!(json["created"], 2);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/imp1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert!(response.status().is_redirection());
+
+        // Importing the same codes again conflicts under the default policy
+        let response = app.clone().oneshot(import("")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["conflicts"], 2);
+        assert_eq!(json["rows"][0]["result"], "conflict");
+
+        let response = app
+            .clone()
+            .oneshot(import("?on_conflict=skip"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    #[tokio::test]
+    async fn test_batch_routes() {
+        let app = create_router(setup_test_state().await);
+
+        let post = |uri: &str, body: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("X-API-KEY", "test-api-key")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(post(
+                "/api/shortens/batch",
+                r#"{"items":[
+                    {"original_url":"https://example.com/a","short_code":"cms1","tags":["post-7"]},
+                    {"original_url":"https://example.com/b","short_code":"admin"},
+                    {"original_url":"https://example.com/c","short_code":"cms2","tags":["post-7"]}
+                ]}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["succeeded"], 2);
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["items"][0]["data"]["short_code"], "cms1");
+        assert_eq!(json["items"][1]["success"], false);
+        assert_eq!(
+            json["items"][1]["error"]["errcode"],
+            crate::errors::error_codes::CODE_RESERVED
+        );
+
+        let response = app
+            .clone()
+            .oneshot(post(
+                "/api/shortens/batch-update",
+                r#"{"filter":{"tag":"post-7"},"status":1}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["succeeded"], 2);
+        assert_eq!(json["items"][1]["data"]["status"], 1);
+
+        // Disabled links no longer redirect
+        let request = Request::builder()
+            .method("GET")
+            .uri("/cms1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert!(!response.status().is_redirection());
+
+        let response = app
+            .clone()
+            .oneshot(post(
+                "/api/shortens/batch-update",
+                r#"{"ids":[12345],"description":"gone"}"#,
+            ))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["items"][0]["id"], 12345);
+        assert_eq!(
+            json["items"][0]["error"]["errcode"],
+            crate::errors::error_codes::NOT_FOUND
+        );
+
+        for body in [
+            r#"{"ids":[1],"filter":{"tag":"post-7"},"status":1}"#,
+            r#"{"status":1}"#,
+            r#"{"ids":[1]}"#,
+            r#"{"ids":[1],"status":9}"#,
+            r#"{"filter":{},"status":1}"#,
+        ] {
+            let response = app
+                .clone()
+                .oneshot(post("/api/shortens/batch-update", body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", body);
+        }
+
+        let response = app
+            .oneshot(post("/api/shortens/batch", r#"{"items":[]}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_routes() {
+        let app = create_router(setup_test_state().await);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"original_url":"https://example.com","short_code":"meta","metadata":{"ticket":42,"owner":"ops"}}"#,
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let get = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .header("X-API-KEY", "test-api-key")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get("/api/shortens/meta"))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["metadata"]["owner"], "ops");
+
+        for (filter, total) in [("ticket:42", 1), ("owner", 1), ("owner:dev", 0)] {
+            let response = app
+                .clone()
+                .oneshot(get(&format!("/api/shortens?metadata={}", filter)))
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["meta"]["total"], total, "{}", filter);
+        }
+
+        let response = app
+            .oneshot(get("/api/shortens?metadata=bad%20key:1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_export_routes() {
+        let app = create_router(setup_test_state().await);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/shortens/import")
+            .header("X-API-KEY", "test-api-key")
+            .header("content-type", "text/csv")
+            .body(Body::from(
+                "code,url,description,status\n\
+                 exp1,https://example.com/1,\"Multi\nline, quoted\",enabled\n\
+                 exp2,https://example.com/2,,disabled\n",
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let export = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .header("X-API-KEY", "test-api-key")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(export("/api/shortens/export?status=1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"shortens.csv\""
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("id,domain,short_code,short_url,original_url"));
+        assert!(lines[1].contains(",exp2,") && lines[1].contains(",disabled,"));
+
+        let response = app
+            .clone()
+            .oneshot(export("/api/shortens/export?format=parquet_csv"))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        // One line per link, line breaks inside fields flattened
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.contains("\"Multi line, quoted\""));
+
+        let response = app
+            .clone()
+            .oneshot(export(
+                "/api/shortens/export?format=ndjson&original_url=example.com/1",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/x-ndjson"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let records: Vec<serde_json::Value> = String::from_utf8(body.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["short_code"], "exp1");
+
+        let response = app
+            .clone()
+            .oneshot(export("/api/shortens/export?format=xml"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(export("/api/histories/export?short_code=exp1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.starts_with(b"id,url_id,short_code,ip_address"));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/histories/export")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
 }
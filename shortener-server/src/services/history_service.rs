@@ -1,10 +1,11 @@
 use crate::errors::ServiceError;
-use crate::geoip::GeoIp;
+use crate::export::EXPORT_BATCH_SIZE;
+use crate::geoip::{GeoIp, GeoIpInfo, NullGeoIp};
 use crate::models::history::Model as HistoryModel;
 use crate::repositories::history_repository::{
     CreateHistoryDto, HistoryListParams, HistoryRepository,
 };
-use crate::services::shorten_service::{PageMeta, PagedResponse};
+use crate::services::shorten_service::PageMeta;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{debug, info};
@@ -26,6 +27,8 @@ pub struct HistoryResponse {
     pub device_type: Option<String>,
     pub os: Option<String>,
     pub browser: Option<String>,
+    pub targeting_rule: Option<i32>,
+    pub variant: Option<String>,
     pub accessed_at: String,
     pub created_at: String,
 }
@@ -48,12 +51,33 @@ impl HistoryResponse {
             device_type: model.device_type,
             os: model.os,
             browser: model.browser,
+            targeting_rule: model.targeting_rule,
+            variant: model.variant,
             accessed_at: model.accessed_at.to_rfc3339(),
             created_at: model.created_at.to_rfc3339(),
         }
     }
 }
 
+/// Clicks served by one A/B variant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantClicks {
+    pub variant: String,
+    pub clicks: u64,
+}
+
+/// Paginated history listing
+///
+/// `variants` holds the per-variant click breakdown of the filtered records
+/// when the listing is narrowed to one link (`short_code` or `url_id`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryListResponse {
+    pub data: Vec<HistoryResponse>,
+    pub meta: PageMeta,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<VariantClicks>,
+}
+
 /// GeoIP information structure
 #[derive(Debug, Clone)]
 struct GeoInfo {
@@ -63,6 +87,17 @@ struct GeoInfo {
     isp: Option<String>,
 }
 
+/// Facts about a visit already resolved in the redirect path
+#[derive(Debug, Clone, Default)]
+pub struct AccessDetails {
+    /// GeoIP result looked up for targeting (saves a second lookup)
+    pub geo: Option<GeoIpInfo>,
+    /// Index of the targeting rule that chose the destination
+    pub targeting_rule: Option<i32>,
+    /// Name of the A/B variant that was served
+    pub variant: Option<String>,
+}
+
 /// User-Agent parsed information
 #[derive(Debug, Clone)]
 pub struct UserAgentInfo {
@@ -74,18 +109,26 @@ pub struct UserAgentInfo {
 /// History Service - handles business logic for access history
 pub struct HistoryService {
     history_repo: Arc<dyn HistoryRepository>,
-    geoip: Option<Arc<dyn GeoIp>>,
+    geoip: Arc<dyn GeoIp>,
 }
 
 impl HistoryService {
     /// Create a new HistoryService instance
+    ///
+    /// Without a GeoIP backend, lookups fall back to `NullGeoIp` and return empty locations.
     pub fn new(history_repo: Arc<dyn HistoryRepository>, geoip: Option<Arc<dyn GeoIp>>) -> Self {
         Self {
             history_repo,
-            geoip,
+            geoip: geoip.unwrap_or_else(|| Arc::new(NullGeoIp::new())),
         }
     }
 
+    /// Look up the location of a visitor (empty when GeoIP is disabled or fails)
+    pub async fn locate(&self, ip: &str) -> GeoIpInfo {
+        debug!("Looking up GeoIP for IP: {}", ip);
+        self.geoip.lookup_or_empty(ip).await
+    }
+
     /// Record an access to a short URL
     ///
     /// # Arguments
@@ -108,32 +151,40 @@ impl HistoryService {
         user_agent: Option<&str>,
         referer: Option<&str>,
     ) -> Result<(), ServiceError> {
-        // 初始化地理位置信息
-        let mut geo_info = GeoInfo {
-            country: None,
-            province: None,
-            city: None,
-            isp: None,
-        };
+        self.record_access_with(
+            url_id,
+            code,
+            ip,
+            user_agent,
+            referer,
+            AccessDetails::default(),
+        )
+        .await
+    }
 
+    /// Record an access along with details resolved while redirecting
+    ///
+    /// Reuses the GeoIP result in `details` instead of looking the IP up again.
+    pub async fn record_access_with(
+        &self,
+        url_id: i64,
+        code: &str,
+        ip: &str,
+        user_agent: Option<&str>,
+        referer: Option<&str>,
+        details: AccessDetails,
+    ) -> Result<(), ServiceError> {
         // 查询 GeoIP 信息
-        if let Some(ref geoip) = self.geoip {
-            debug!("Looking up GeoIP for IP: {}", ip);
-            if let Ok(geoip_info) = geoip.lookup(ip).await {
-                if !geoip_info.country.is_empty() && geoip_info.country != "0" {
-                    geo_info.country = Some(geoip_info.country);
-                }
-                if !geoip_info.province.is_empty() && geoip_info.province != "0" {
-                    geo_info.province = Some(geoip_info.province);
-                }
-                if !geoip_info.city.is_empty() && geoip_info.city != "0" {
-                    geo_info.city = Some(geoip_info.city);
-                }
-                if !geoip_info.isp.is_empty() && geoip_info.isp != "0" {
-                    geo_info.isp = Some(geoip_info.isp);
-                }
-            }
-        }
+        let geoip_info = match details.geo {
+            Some(info) => info,
+            None => self.locate(ip).await,
+        };
+        let geo_info = GeoInfo {
+            country: GeoIpInfo::known(&geoip_info.country),
+            province: GeoIpInfo::known(&geoip_info.province),
+            city: GeoIpInfo::known(&geoip_info.city),
+            isp: GeoIpInfo::known(&geoip_info.isp),
+        };
 
         // Parse User-Agent
         let ua_info = user_agent.map(|ua| self.parse_user_agent(ua));
@@ -153,6 +204,8 @@ impl HistoryService {
             device_type: ua_info.as_ref().and_then(|ua| ua.device_type.clone()),
             os: ua_info.as_ref().and_then(|ua| ua.os.clone()),
             browser: ua_info.as_ref().and_then(|ua| ua.browser.clone()),
+            targeting_rule: details.targeting_rule,
+            variant: details.variant,
             accessed_at: chrono::Utc::now(),
         };
 
@@ -171,14 +224,26 @@ impl HistoryService {
     ///
     /// # Returns
     ///
-    /// * `Ok(PagedResponse<HistoryResponse>)` - Paginated list of history records
+    /// * `Ok(HistoryListResponse)` - Paginated list of history records
     /// * `Err(ServiceError)` - Query failed
     pub async fn list_histories(
         &self,
         params: HistoryListParams,
-    ) -> Result<PagedResponse<HistoryResponse>, ServiceError> {
+    ) -> Result<HistoryListResponse, ServiceError> {
         let (histories, total) = self.history_repo.list(params.clone()).await?;
 
+        // Variant names are only comparable within one link
+        let variants = if params.short_code.is_some() || params.url_id.is_some() {
+            self.history_repo
+                .count_by_variant(params.clone())
+                .await?
+                .into_iter()
+                .map(|(variant, clicks)| VariantClicks { variant, clicks })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         let data: Vec<HistoryResponse> = histories
             .into_iter()
             .map(HistoryResponse::from_model)
@@ -194,7 +259,31 @@ impl HistoryService {
             total_pages,
         };
 
-        Ok(PagedResponse { data, meta })
+        Ok(HistoryListResponse {
+            data,
+            meta,
+            variants,
+        })
+    }
+
+    /// Read the batch of an export of history records following `after_id`
+    ///
+    /// Takes the filters of [`list_histories`](Self::list_histories); records
+    /// come in ID order, [`EXPORT_BATCH_SIZE`] at a time.
+    pub async fn export_histories(
+        &self,
+        params: &HistoryListParams,
+        after_id: i64,
+    ) -> Result<Vec<HistoryResponse>, ServiceError> {
+        let histories = self
+            .history_repo
+            .export_batch(params, after_id, EXPORT_BATCH_SIZE)
+            .await?;
+
+        Ok(histories
+            .into_iter()
+            .map(HistoryResponse::from_model)
+            .collect())
     }
 
     /// Delete multiple history records by IDs
@@ -227,7 +316,7 @@ impl HistoryService {
     /// # Returns
     ///
     /// * `UserAgentInfo` - Parsed information
-    fn parse_user_agent(&self, user_agent: &str) -> UserAgentInfo {
+    pub fn parse_user_agent(&self, user_agent: &str) -> UserAgentInfo {
         let ua_lower = user_agent.to_lowercase();
 
         // Detect OS first (more specific checks first)
@@ -285,7 +374,7 @@ impl HistoryService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, DatabaseConfig, DatabaseType, SqliteConfig};
+    use crate::config::Config;
     use crate::db::DbFactory;
     use crate::geoip::NullGeoIp;
     use crate::models::url::UrlStatus;
@@ -293,46 +382,7 @@ mod tests {
     use crate::repositories::url_repository::{CreateUrlDto, UrlRepository, UrlRepositoryImpl};
 
     async fn setup_test_service() -> (HistoryService, Arc<dyn UrlRepository>) {
-        let config = Config {
-            server: crate::config::ServerConfig {
-                address: ":8080".to_string(),
-                trusted_platform: None,
-                site_url: "http://localhost:8080".to_string(),
-                api_key: "test-key".to_string(),
-            },
-            shortener: crate::config::ShortenerConfig {
-                code_length: 6,
-                code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
-                    .to_string(),
-            },
-            admin: crate::config::AdminConfig {
-                username: "admin".to_string(),
-                password: "admin123".to_string(),
-            },
-            database: DatabaseConfig {
-                db_type: DatabaseType::Sqlite,
-                log_level: 0,
-                sqlite: Some(SqliteConfig {
-                    path: ":memory:".to_string(),
-                }),
-                postgres: None,
-                mysql: None,
-            },
-            cache: crate::config::CacheConfig {
-                enabled: false,
-                cache_type: crate::config::CacheType::Redis,
-                expire: 3600,
-                prefix: "shorten:".to_string(),
-                redis: None,
-                valkey: None,
-            },
-            geoip: crate::config::GeoIpConfig {
-                enabled: false,
-                geoip_type: crate::config::GeoIpType::Ip2region,
-                ip2region: None,
-            },
-            logging: crate::logging::LoggingConfig::default(),
-        };
+        let config = Config::for_tests();
 
         let db = DbFactory::create_connection(&config).await.unwrap();
         DbFactory::run_migrations(&db).await.unwrap();
@@ -352,8 +402,9 @@ mod tests {
             original_url: "https://example.com".to_string(),
             description: Some("Test URL".to_string()),
             status: UrlStatus::Enabled as i32,
+            ..Default::default()
         };
-        let url = url_repo.create(create_dto).await.unwrap();
+        let url = url_repo.create(create_dto, "admin").await.unwrap();
         url.id
     }
 
@@ -375,6 +426,105 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_record_access_with_details() {
+        let (service, url_repo) = setup_test_service().await;
+        let url_id = create_test_url(&url_repo).await;
+
+        let details = AccessDetails {
+            geo: Some(GeoIpInfo::new(
+                "中国".to_string(),
+                "广东省".to_string(),
+                "深圳市".to_string(),
+                "0".to_string(),
+            )),
+            targeting_rule: Some(1),
+            ..Default::default()
+        };
+        service
+            .record_access_with(url_id, "test123", "1.2.3.4", None, None, details)
+            .await
+            .unwrap();
+
+        let list_result = service
+            .list_histories(HistoryListParams::default())
+            .await
+            .unwrap();
+        let record = &list_result.data[0];
+        assert_eq!(record.country.as_deref(), Some("中国"));
+        assert_eq!(record.province.as_deref(), Some("广东省"));
+        assert_eq!(record.isp, None);
+        assert_eq!(record.targeting_rule, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_list_histories_variant_breakdown() {
+        let (service, url_repo) = setup_test_service().await;
+        let url_id = create_test_url(&url_repo).await;
+
+        for variant in [Some("a"), Some("b"), Some("a"), None, Some("a")] {
+            let details = AccessDetails {
+                variant: variant.map(str::to_string),
+                ..Default::default()
+            };
+            service
+                .record_access_with(url_id, "test123", "1.2.3.4", None, None, details)
+                .await
+                .unwrap();
+        }
+
+        let params = HistoryListParams {
+            short_code: Some("test123".to_string()),
+            ..Default::default()
+        };
+        let response = service.list_histories(params).await.unwrap();
+        assert_eq!(response.meta.total, 5);
+        assert_eq!(
+            response.variants,
+            vec![
+                VariantClicks {
+                    variant: "a".to_string(),
+                    clicks: 3
+                },
+                VariantClicks {
+                    variant: "b".to_string(),
+                    clicks: 1
+                },
+            ]
+        );
+
+        // Narrowing to one variant lists only its visits
+        let params = HistoryListParams {
+            url_id: Some(url_id as i32),
+            variant: Some("a".to_string()),
+            ..Default::default()
+        };
+        let response = service.list_histories(params).await.unwrap();
+        assert_eq!(response.meta.total, 3);
+        assert!(
+            response
+                .data
+                .iter()
+                .all(|h| h.variant.as_deref() == Some("a"))
+        );
+        assert_eq!(response.variants.len(), 1);
+
+        // Variant names are per link, so no breakdown across links
+        let response = service
+            .list_histories(HistoryListParams::default())
+            .await
+            .unwrap();
+        assert!(response.variants.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_locate_without_geoip() {
+        let (service, _) = setup_test_service().await;
+        let service = HistoryService::new(service.history_repo.clone(), None);
+
+        assert!(service.locate("8.8.8.8").await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_record_access_without_user_agent() {
         let (service, url_repo) = setup_test_service().await;
@@ -1,8 +1,17 @@
 mod history_service;
 mod shorten_service;
+mod user_service;
 
-pub use history_service::{HistoryResponse, HistoryService, UserAgentInfo};
+pub use history_service::{
+    AccessDetails, HistoryListResponse, HistoryResponse, HistoryService, UserAgentInfo,
+    VariantClicks,
+};
 pub use shorten_service::{
-    CreateShortenRequest, PageMeta, PagedResponse, ShortenResponse, ShortenService,
-    UpdateShortenRequest,
+    BatchTarget, BlockedLinkResponse, CreateShortenRequest, HealthSummary, MAX_BATCH_ITEMS,
+    PageMeta, PagedResponse, RevisionResponse, SYSTEM_ACTOR, ShortenResponse, ShortenService,
+    TagRequest, TagResponse, UpdateShortenRequest,
+};
+pub use user_service::{
+    ChangePasswordRequest, CreateUserRequest, MIN_PASSWORD_LEN, UpdateUserRequest, UserResponse,
+    UserService,
 };
@@ -0,0 +1,549 @@
+use crate::config::AdminConfig;
+use crate::errors::ServiceError;
+use crate::models::user::{Model as UserModel, UserRole};
+use crate::password::{hash_password, verify_password};
+use crate::repositories::user_repository::{CreateUserDto, UpdateUserDto, UserRepository};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// Longest allowed username
+pub const MAX_USERNAME_LEN: usize = 64;
+
+/// Shortest allowed password
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// Names that would clash with routes or with the actors recorded in revisions
+const RESERVED_USERNAMES: &[&str] = &["current", "api-key", "system"];
+
+/// Argon2 hash checked against when logging in as an unknown user
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$db2gPKOUHP5TnotPQMRJYg$IdtmtBAtBuCLfEoOZq4EKr26CtB4Wn+3U+Vkg042EwU";
+
+/// Request DTO for creating a user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub password: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+    /// `user` when unset
+    pub role: Option<UserRole>,
+}
+
+/// Request DTO for updating a user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateUserRequest {
+    /// New password, set without knowing the current one
+    pub password: Option<String>,
+    /// New display name; an empty string removes it
+    pub display_name: Option<String>,
+    /// New email; an empty string removes it
+    pub email: Option<String>,
+    pub role: Option<UserRole>,
+    /// Disabled users cannot log in
+    pub disabled: Option<bool>,
+}
+
+/// Request DTO for changing the password of the logged-in user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Response DTO for a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserResponse {
+    pub id: i64,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+    pub role: UserRole,
+    pub disabled: bool,
+    pub last_login_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl UserResponse {
+    /// Convert user model to response DTO
+    pub fn from_model(model: UserModel) -> Self {
+        Self {
+            id: model.id,
+            username: model.username,
+            display_name: model.display_name,
+            email: model.email,
+            role: model.role,
+            disabled: model.disabled,
+            last_login_at: model.last_login_at.map(|at| at.to_rfc3339()),
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// User Service - handles accounts of the admin API
+pub struct UserService {
+    user_repo: Arc<dyn UserRepository>,
+}
+
+impl UserService {
+    /// Create a new UserService instance
+    pub fn new(user_repo: Arc<dyn UserRepository>) -> Self {
+        Self { user_repo }
+    }
+
+    /// Create the `[admin]` account of the config when there are no users yet
+    ///
+    /// Returns whether the account was created. Once a user exists, the
+    /// `[admin]` section is no longer used.
+    pub async fn ensure_admin(&self, admin: &AdminConfig) -> Result<bool, ServiceError> {
+        if self.user_repo.count().await? > 0 {
+            return Ok(false);
+        }
+
+        self.user_repo
+            .create(CreateUserDto {
+                username: admin.username.clone(),
+                password_hash: hash_password(&admin.password)?,
+                role: UserRole::Admin,
+                ..Default::default()
+            })
+            .await?;
+
+        info!("Created administrator from config: {}", admin.username);
+
+        Ok(true)
+    }
+
+    /// Check the credentials of a user and record the login
+    ///
+    /// # Returns
+    ///
+    /// * `Err(ServiceError::Unauthorized)` - Unknown user, wrong password or disabled account
+    pub async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<UserResponse, ServiceError> {
+        let invalid = || ServiceError::Unauthorized("Invalid credentials".to_string());
+
+        let Some(user) = self.user_repo.find_by_username(username).await? else {
+            // Take as long as a wrong password, so usernames cannot be probed
+            verify_password(password, DUMMY_PASSWORD_HASH)?;
+            return Err(invalid());
+        };
+        if !verify_password(password, &user.password_hash)? {
+            return Err(invalid());
+        }
+        if user.disabled {
+            return Err(ServiceError::Unauthorized(
+                "Account is disabled".to_string(),
+            ));
+        }
+
+        let user = self.user_repo.record_login(user.id).await?;
+
+        Ok(UserResponse::from_model(user))
+    }
+
+    /// Get a user by username
+    pub async fn get_user(&self, username: &str) -> Result<UserResponse, ServiceError> {
+        Ok(UserResponse::from_model(self.find_user(username).await?))
+    }
+
+    /// Whether the user exists, is enabled and is an administrator
+    pub async fn is_admin(&self, username: &str) -> Result<bool, ServiceError> {
+        Ok(self
+            .user_repo
+            .find_by_username(username)
+            .await?
+            .is_some_and(|user| user.role == UserRole::Admin && !user.disabled))
+    }
+
+    /// List all users ordered by username
+    pub async fn list_users(&self) -> Result<Vec<UserResponse>, ServiceError> {
+        let users = self.user_repo.list().await?;
+
+        Ok(users.into_iter().map(UserResponse::from_model).collect())
+    }
+
+    /// Create a user
+    ///
+    /// # Returns
+    ///
+    /// * `Err(ServiceError::AlreadyExists)` - The username is taken
+    pub async fn create_user(&self, req: CreateUserRequest) -> Result<UserResponse, ServiceError> {
+        check_username(&req.username)?;
+        check_password(&req.password)?;
+        let display_name = optional_text(req.display_name);
+        let email = optional_text(req.email);
+        check_email(email.as_deref())?;
+
+        if self
+            .user_repo
+            .find_by_username(&req.username)
+            .await?
+            .is_some()
+        {
+            return Err(ServiceError::AlreadyExists(format!(
+                "User '{}' already exists",
+                req.username
+            )));
+        }
+
+        let user = self
+            .user_repo
+            .create(CreateUserDto {
+                username: req.username,
+                password_hash: hash_password(&req.password)?,
+                display_name,
+                email,
+                role: req.role.unwrap_or_default(),
+            })
+            .await?;
+
+        info!("Created user: {} ({:?})", user.username, user.role);
+
+        Ok(UserResponse::from_model(user))
+    }
+
+    /// Update a user
+    ///
+    /// The last enabled administrator can be neither demoted nor disabled,
+    /// and users cannot disable themselves.
+    pub async fn update_user(
+        &self,
+        username: &str,
+        req: UpdateUserRequest,
+        actor: &str,
+    ) -> Result<UserResponse, ServiceError> {
+        let user = self.find_user(username).await?;
+
+        let password_hash = match req.password.as_deref() {
+            Some(password) => {
+                check_password(password)?;
+                Some(hash_password(password)?)
+            }
+            None => None,
+        };
+        let email = req.email.map(|email| optional_text(Some(email)));
+        check_email(email.clone().flatten().as_deref())?;
+
+        if req.disabled == Some(true) && username == actor {
+            return Err(ServiceError::InvalidInput(
+                "You cannot disable your own account".to_string(),
+            ));
+        }
+        let demoted = req.role.is_some_and(|role| role != UserRole::Admin);
+        if (demoted || req.disabled == Some(true)) && self.is_last_admin(&user).await? {
+            return Err(ServiceError::InvalidInput(
+                "The last administrator cannot be demoted or disabled".to_string(),
+            ));
+        }
+
+        let user = self
+            .user_repo
+            .update(
+                username,
+                UpdateUserDto {
+                    password_hash,
+                    display_name: req
+                        .display_name
+                        .map(|display_name| optional_text(Some(display_name))),
+                    email,
+                    role: req.role,
+                    disabled: req.disabled,
+                },
+            )
+            .await?;
+
+        info!("Updated user: {} by {}", username, actor);
+
+        Ok(UserResponse::from_model(user))
+    }
+
+    /// Delete a user
+    ///
+    /// Users cannot delete themselves, and the last enabled administrator stays.
+    pub async fn delete_user(&self, username: &str, actor: &str) -> Result<(), ServiceError> {
+        let user = self.find_user(username).await?;
+
+        if username == actor {
+            return Err(ServiceError::InvalidInput(
+                "You cannot delete your own account".to_string(),
+            ));
+        }
+        if self.is_last_admin(&user).await? {
+            return Err(ServiceError::InvalidInput(
+                "The last administrator cannot be deleted".to_string(),
+            ));
+        }
+
+        self.user_repo.delete(username).await?;
+
+        info!("Deleted user: {} by {}", username, actor);
+
+        Ok(())
+    }
+
+    /// Change the password of a user who knows the current one
+    ///
+    /// # Returns
+    ///
+    /// * `Err(ServiceError::Forbidden)` - The current password is wrong
+    pub async fn change_password(
+        &self,
+        username: &str,
+        req: ChangePasswordRequest,
+    ) -> Result<(), ServiceError> {
+        let user = self.find_user(username).await?;
+
+        if !verify_password(&req.current_password, &user.password_hash)? {
+            return Err(ServiceError::Forbidden(
+                "Current password is incorrect".to_string(),
+            ));
+        }
+
+        self.set_password(username, &req.new_password).await
+    }
+
+    /// Set the password of a user
+    pub async fn set_password(&self, username: &str, password: &str) -> Result<(), ServiceError> {
+        check_password(password)?;
+        self.find_user(username).await?;
+
+        self.user_repo
+            .update(
+                username,
+                UpdateUserDto {
+                    password_hash: Some(hash_password(password)?),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        info!("Changed password of user: {}", username);
+
+        Ok(())
+    }
+
+    async fn find_user(&self, username: &str) -> Result<UserModel, ServiceError> {
+        self.user_repo
+            .find_by_username(username)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("User '{}' not found", username)))
+    }
+
+    /// Whether the user is the only enabled administrator
+    async fn is_last_admin(&self, user: &UserModel) -> Result<bool, ServiceError> {
+        Ok(user.role == UserRole::Admin
+            && !user.disabled
+            && self.user_repo.count_active_admins().await? <= 1)
+    }
+}
+
+/// Check a username: letters, digits, `.`, `-` and `_`
+fn check_username(username: &str) -> Result<(), ServiceError> {
+    if username.is_empty()
+        || username.len() > MAX_USERNAME_LEN
+        || !username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return Err(ServiceError::InvalidInput(format!(
+            "Invalid username: {} (use up to {} letters, digits, '.', '-' or '_')",
+            username, MAX_USERNAME_LEN
+        )));
+    }
+    if RESERVED_USERNAMES.contains(&username) {
+        return Err(ServiceError::InvalidInput(format!(
+            "Username '{}' is reserved",
+            username
+        )));
+    }
+
+    Ok(())
+}
+
+fn check_password(password: &str) -> Result<(), ServiceError> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(ServiceError::InvalidInput(format!(
+            "password must be at least {} characters",
+            MIN_PASSWORD_LEN
+        )));
+    }
+
+    Ok(())
+}
+
+fn check_email(email: Option<&str>) -> Result<(), ServiceError> {
+    match email {
+        Some(email) if !email.contains('@') || email.chars().any(char::is_whitespace) => Err(
+            ServiceError::InvalidInput(format!("Invalid email: {}", email)),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Trimmed text, `None` when it is empty
+fn optional_text(text: Option<String>) -> Option<String> {
+    text.map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::DbFactory;
+    use crate::repositories::UserRepositoryImpl;
+
+    async fn setup_test_service() -> UserService {
+        let config = Config::for_tests();
+
+        let db = DbFactory::create_connection(&config).await.unwrap();
+        DbFactory::run_migrations(&db).await.unwrap();
+
+        UserService::new(Arc::new(UserRepositoryImpl::new(db)))
+    }
+
+    fn admin_config() -> AdminConfig {
+        AdminConfig {
+            username: "admin".to_string(),
+            password: "admin123".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ensure_admin_and_authenticate() {
+        let service = setup_test_service().await;
+
+        assert!(service.ensure_admin(&admin_config()).await.unwrap());
+        // Only the first start creates the account
+        let changed = AdminConfig {
+            password: "changed-password".to_string(),
+            ..admin_config()
+        };
+        assert!(!service.ensure_admin(&changed).await.unwrap());
+
+        let user = service.authenticate("admin", "admin123").await.unwrap();
+        assert_eq!(user.role, UserRole::Admin);
+        assert!(user.last_login_at.is_some());
+        assert!(service.is_admin("admin").await.unwrap());
+
+        // Unknown users fail like wrong passwords, after checking a password too
+        for (username, password) in [("admin", "changed-password"), ("nobody", "admin123")] {
+            let result = service.authenticate(username, password).await;
+            assert!(
+                matches!(result, Err(ServiceError::Unauthorized(ref e)) if e == "Invalid credentials")
+            );
+        }
+        assert!(!verify_password("admin123", DUMMY_PASSWORD_HASH).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_user_management() {
+        let service = setup_test_service().await;
+        service.ensure_admin(&admin_config()).await.unwrap();
+
+        let req = CreateUserRequest {
+            username: "alice".to_string(),
+            password: "alice-secret".to_string(),
+            display_name: Some(" Alice ".to_string()),
+            email: Some("alice@example.com".to_string()),
+            role: None,
+        };
+        let alice = service.create_user(req.clone()).await.unwrap();
+        assert_eq!(alice.role, UserRole::User);
+        assert_eq!(alice.display_name.as_deref(), Some("Alice"));
+        assert!(!service.is_admin("alice").await.unwrap());
+        assert!(matches!(
+            service.create_user(req.clone()).await,
+            Err(ServiceError::AlreadyExists(_))
+        ));
+
+        for bad in [
+            CreateUserRequest {
+                username: "current".to_string(),
+                ..req.clone()
+            },
+            CreateUserRequest {
+                username: "bad name".to_string(),
+                ..req.clone()
+            },
+            CreateUserRequest {
+                username: "bob".to_string(),
+                password: "short".to_string(),
+                ..req.clone()
+            },
+            CreateUserRequest {
+                username: "bob".to_string(),
+                email: Some("not-an-email".to_string()),
+                ..req.clone()
+            },
+        ] {
+            let result = service.create_user(bad).await;
+            assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+        }
+
+        // An empty string clears the display name
+        let req = UpdateUserRequest {
+            display_name: Some(String::new()),
+            disabled: Some(true),
+            ..Default::default()
+        };
+        let alice = service.update_user("alice", req, "admin").await.unwrap();
+        assert_eq!(alice.display_name, None);
+        assert_eq!(alice.email.as_deref(), Some("alice@example.com"));
+        let result = service.authenticate("alice", "alice-secret").await;
+        assert!(matches!(result, Err(ServiceError::Unauthorized(_))));
+
+        // The last administrator stays
+        let req = UpdateUserRequest {
+            role: Some(UserRole::User),
+            ..Default::default()
+        };
+        let result = service.update_user("admin", req, "api-key").await;
+        assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+        let result = service.delete_user("admin", "api-key").await;
+        assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+        let result = service.delete_user("alice", "alice").await;
+        assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+
+        service.delete_user("alice", "admin").await.unwrap();
+        assert_eq!(service.list_users().await.unwrap().len(), 1);
+        assert!(matches!(
+            service.get_user("alice").await,
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_change_password() {
+        let service = setup_test_service().await;
+        service.ensure_admin(&admin_config()).await.unwrap();
+
+        let req = ChangePasswordRequest {
+            current_password: "wrong-password".to_string(),
+            new_password: "new-password".to_string(),
+        };
+        let result = service.change_password("admin", req).await;
+        assert!(matches!(result, Err(ServiceError::Forbidden(_))));
+
+        let req = ChangePasswordRequest {
+            current_password: "admin123".to_string(),
+            new_password: "new".to_string(),
+        };
+        let result = service.change_password("admin", req).await;
+        assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+
+        let req = ChangePasswordRequest {
+            current_password: "admin123".to_string(),
+            new_password: "new-password".to_string(),
+        };
+        service.change_password("admin", req).await.unwrap();
+        assert!(service.authenticate("admin", "admin123").await.is_err());
+        assert!(service.authenticate("admin", "new-password").await.is_ok());
+    }
+}
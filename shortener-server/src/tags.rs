@@ -0,0 +1,72 @@
+/// Longest allowed tag name
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Trim a tag name and check it is usable
+///
+/// Tags appear in URL paths and comma separated CLI output, so `/` and `,`
+/// are rejected along with control characters.
+pub fn normalize_tag(name: &str) -> Result<String, String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err("tag name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_TAG_LEN {
+        return Err(format!(
+            "Invalid tag name: {} (at most {} characters)",
+            name, MAX_TAG_LEN
+        ));
+    }
+    if name.chars().any(|c| c.is_control() || c == '/' || c == ',') {
+        return Err(format!(
+            "Invalid tag name: {} (must not contain '/', ',' or control characters)",
+            name
+        ));
+    }
+
+    Ok(name.to_string())
+}
+
+/// Normalize every tag, dropping duplicates and sorting by name
+pub fn normalize_tags(names: Vec<String>) -> Result<Vec<String>, String> {
+    let mut tags = names
+        .iter()
+        .map(|name| normalize_tag(name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    tags.sort();
+    tags.dedup();
+
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("  marketing ").unwrap(), "marketing");
+        assert_eq!(normalize_tag("双十一").unwrap(), "双十一");
+
+        assert!(normalize_tag("").is_err());
+        assert!(normalize_tag("   ").is_err());
+        assert!(normalize_tag("a/b").is_err());
+        assert!(normalize_tag("a,b").is_err());
+        assert!(normalize_tag(&"x".repeat(MAX_TAG_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = normalize_tags(vec![
+            "social".to_string(),
+            " ads ".to_string(),
+            "social".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(tags, vec!["ads", "social"]);
+
+        assert!(normalize_tags(vec!["ok".to_string(), "".to_string()]).is_err());
+        assert!(normalize_tags(Vec::new()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::destination;
+use crate::geoip::GeoIpInfo;
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// Device types a targeting rule can match (as reported by `parse_user_agent`)
+pub const DEVICE_TYPES: &[&str] = &["mobile", "tablet", "desktop"];
+
+/// Operating systems a targeting rule can match (as reported by `parse_user_agent`)
+pub const OPERATING_SYSTEMS: &[&str] = &["ios", "android", "windows", "macos", "linux"];
+
+/// A destination override chosen when all of its conditions match the visitor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetingRule {
+    /// Device type to match: mobile, tablet or desktop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+    /// Operating system to match: ios, android, windows, macos or linux
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    /// Country to match, as reported by GeoIP (e.g. `中国`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Province to match, as reported by GeoIP (e.g. `广东省`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub province: Option<String>,
+    /// Where matching visitors are redirected
+    pub url: String,
+}
+
+impl TargetingRule {
+    /// Lowercase the conditions and check them against the known values
+    pub fn normalize(mut self) -> Result<Self, String> {
+        self.device_type = normalize_condition("device_type", self.device_type, DEVICE_TYPES)?;
+        self.os = normalize_condition("os", self.os, OPERATING_SYSTEMS)?;
+        self.country = self
+            .country
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty());
+        self.province = self
+            .province
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty());
+
+        if self.device_type.is_none()
+            && self.os.is_none()
+            && self.country.is_none()
+            && self.province.is_none()
+        {
+            return Err("targeting rule needs at least one condition".to_string());
+        }
+        self.url = destination::normalize(&self.url)
+            .map_err(|e| format!("Invalid targeting URL {}: {}", self.url, e))?;
+
+        Ok(self)
+    }
+
+    /// Whether the rule needs the visitor's GeoIP location
+    pub fn needs_location(&self) -> bool {
+        self.country.is_some() || self.province.is_some()
+    }
+
+    /// Whether every condition of the rule matches the visitor
+    pub fn matches(&self, context: &TargetingContext) -> bool {
+        condition_matches(self.device_type.as_deref(), context.device_type.as_deref())
+            && condition_matches(self.os.as_deref(), context.os.as_deref())
+            && condition_matches(self.country.as_deref(), context.country.as_deref())
+            && condition_matches(self.province.as_deref(), context.province.as_deref())
+    }
+}
+
+/// Ordered targeting rules of a URL, stored as a JSON column
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct TargetingRules(pub Vec<TargetingRule>);
+
+/// First rule matching the visitor and its index, if any
+pub fn select_rule<'a>(
+    rules: &'a [TargetingRule],
+    context: &TargetingContext,
+) -> Option<(usize, &'a TargetingRule)> {
+    rules
+        .iter()
+        .enumerate()
+        .find(|(_, rule)| rule.matches(context))
+}
+
+/// What is known about the visitor when picking a destination
+#[derive(Debug, Clone, Default)]
+pub struct TargetingContext {
+    pub device_type: Option<String>,
+    pub os: Option<String>,
+    pub country: Option<String>,
+    pub province: Option<String>,
+}
+
+impl TargetingContext {
+    /// Fill in the visitor's location from a GeoIP result
+    pub fn set_location(&mut self, geo: &GeoIpInfo) {
+        self.country = GeoIpInfo::known(&geo.country);
+        self.province = GeoIpInfo::known(&geo.province);
+    }
+}
+
+fn normalize_condition(
+    name: &str,
+    value: Option<String>,
+    allowed: &[&str],
+) -> Result<Option<String>, String> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    let value = value.trim().to_lowercase();
+    if !allowed.contains(&value.as_str()) {
+        return Err(format!(
+            "Invalid {}: {} (expected one of {})",
+            name,
+            value,
+            allowed.join(", ")
+        ));
+    }
+
+    Ok(Some(value))
+}
+
+fn condition_matches(expected: Option<&str>, actual: Option<&str>) -> bool {
+    match expected {
+        Some(expected) => {
+            actual.is_some_and(|actual| actual.to_lowercase() == expected.to_lowercase())
+        }
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(device_type: Option<&str>, os: Option<&str>, url: &str) -> TargetingRule {
+        TargetingRule {
+            device_type: device_type.map(str::to_string),
+            os: os.map(str::to_string),
+            country: None,
+            province: None,
+            url: url.to_string(),
+        }
+    }
+
+    fn geo_rule(country: Option<&str>, province: Option<&str>, url: &str) -> TargetingRule {
+        TargetingRule {
+            country: country.map(str::to_string),
+            province: province.map(str::to_string),
+            ..rule(None, None, url)
+        }
+    }
+
+    fn context(device_type: &str, os: &str) -> TargetingContext {
+        TargetingContext {
+            device_type: Some(device_type.to_string()),
+            os: Some(os.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_rule() {
+        let normalized = rule(Some("Mobile"), Some(" iOS "), "https://apps.apple.com")
+            .normalize()
+            .unwrap();
+        assert_eq!(normalized.device_type.as_deref(), Some("mobile"));
+        assert_eq!(normalized.os.as_deref(), Some("ios"));
+
+        assert!(rule(None, None, "https://example.com").normalize().is_err());
+        assert!(
+            rule(Some("watch"), None, "https://example.com")
+                .normalize()
+                .is_err()
+        );
+        assert!(
+            rule(None, Some("beos"), "https://example.com")
+                .normalize()
+                .is_err()
+        );
+        assert!(
+            rule(None, Some("ios"), "ftp://example.com")
+                .normalize()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_select_first_matching_rule() {
+        let rules = vec![
+            rule(None, Some("ios"), "https://apps.apple.com"),
+            rule(None, Some("android"), "https://play.google.com"),
+            rule(Some("mobile"), None, "https://m.example.com"),
+        ];
+
+        let (index, selected) = select_rule(&rules, &context("Mobile", "iOS")).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(selected.url, "https://apps.apple.com");
+
+        let (index, selected) = select_rule(&rules, &context("Tablet", "Android")).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(selected.url, "https://play.google.com");
+
+        let (index, selected) = select_rule(&rules, &context("Mobile", "Windows")).unwrap();
+        assert_eq!(index, 2);
+        assert_eq!(selected.url, "https://m.example.com");
+
+        assert!(select_rule(&rules, &context("Desktop", "macOS")).is_none());
+        assert!(select_rule(&rules, &TargetingContext::default()).is_none());
+    }
+
+    #[test]
+    fn test_select_rule_by_location() {
+        let rules = vec![
+            geo_rule(None, Some("广东省"), "https://gd.example.cn"),
+            geo_rule(Some("中国"), None, "https://mirror.example.cn"),
+        ];
+        assert!(rules.iter().all(TargetingRule::needs_location));
+
+        let mut context = TargetingContext::default();
+        context.set_location(&GeoIpInfo::new(
+            "中国".to_string(),
+            "广东省".to_string(),
+            "深圳市".to_string(),
+            "电信".to_string(),
+        ));
+        let (index, _) = select_rule(&rules, &context).unwrap();
+        assert_eq!(index, 0);
+
+        context.set_location(&GeoIpInfo::new(
+            "中国".to_string(),
+            "浙江省".to_string(),
+            "杭州市".to_string(),
+            "0".to_string(),
+        ));
+        let (index, _) = select_rule(&rules, &context).unwrap();
+        assert_eq!(index, 1);
+
+        // Without GeoIP data location rules never match
+        context.set_location(&GeoIpInfo::empty());
+        assert!(select_rule(&rules, &context).is_none());
+
+        let normalized = geo_rule(Some(" 中国 "), Some(""), "https://mirror.example.cn")
+            .normalize()
+            .unwrap();
+        assert_eq!(normalized.country.as_deref(), Some("中国"));
+        assert_eq!(normalized.province, None);
+    }
+}
@@ -0,0 +1,49 @@
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::destination;
+
+/// SHA-256 (hex) of a destination URL, stored in `urls.original_url_hash`
+///
+/// The URL is hashed in the form produced by [`destination::normalize`],
+/// with "/" for an empty path. Stored URLs that fail its checks (links
+/// created before they existed) are hashed as given, trimmed.
+pub fn hash(url: &str) -> String {
+    let normalized = destination::normalize(url)
+        .ok()
+        .and_then(|url| Url::parse(&url).ok())
+        .map_or_else(|| url.trim().to_string(), |url| url.to_string());
+
+    Sha256::digest(normalized.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        let hash = hash("https://example.com/");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, super::hash(" HTTPS://EXAMPLE.com:443/ "));
+        assert_eq!(hash, super::hash("https://example.com"));
+        assert_eq!(
+            super::hash("https://example.com?q=1"),
+            super::hash("https://example.com/?q=1")
+        );
+        assert_ne!(hash, super::hash("https://example.com/other"));
+        assert_ne!(
+            super::hash("https://example.com/Path"),
+            super::hash("https://example.com/path")
+        );
+    }
+
+    #[test]
+    fn test_hash_invalid_url() {
+        assert_eq!(hash(" not a url "), hash("not a url"));
+        assert_ne!(hash("ftp://example.com/"), hash("https://example.com/"));
+    }
+}
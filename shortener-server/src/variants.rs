@@ -0,0 +1,172 @@
+use crate::destination;
+use rand::Rng;
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// Longest allowed variant name (it is stored in history and in a cookie)
+pub const MAX_VARIANT_NAME_LEN: usize = 32;
+
+/// One weighted destination of an A/B experiment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Variant {
+    /// Label recorded in the access history (letters, digits, `-` and `_`)
+    pub name: String,
+    /// Where visitors assigned to this variant are redirected
+    pub url: String,
+    /// Relative share of the traffic
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl Variant {
+    /// Trim the name and check the variant is usable
+    pub fn normalize(mut self) -> Result<Self, String> {
+        self.name = self.name.trim().to_string();
+
+        if self.name.is_empty() {
+            return Err("variant name cannot be empty".to_string());
+        }
+        if self.name.len() > MAX_VARIANT_NAME_LEN
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid variant name: {} (use up to {} letters, digits, '-' or '_')",
+                self.name, MAX_VARIANT_NAME_LEN
+            ));
+        }
+        if self.weight == 0 {
+            return Err(format!(
+                "variant {} needs a weight of at least 1",
+                self.name
+            ));
+        }
+        self.url = destination::normalize(&self.url)
+            .map_err(|e| format!("Invalid variant URL {}: {}", self.url, e))?;
+
+        Ok(self)
+    }
+}
+
+/// Weighted destinations of a URL, stored as a JSON column
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct Variants(pub Vec<Variant>);
+
+/// Normalize every variant and reject duplicate names
+pub fn normalize_variants(variants: Vec<Variant>) -> Result<Vec<Variant>, String> {
+    let variants = variants
+        .into_iter()
+        .map(Variant::normalize)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, variant) in variants.iter().enumerate() {
+        if variants[..i].iter().any(|other| other.name == variant.name) {
+            return Err(format!("Duplicate variant name: {}", variant.name));
+        }
+    }
+
+    Ok(variants)
+}
+
+/// Pick the variant owning `roll`, a number in `0..total weight`
+pub fn pick_variant(variants: &[Variant], mut roll: u64) -> Option<&Variant> {
+    for variant in variants {
+        let weight = u64::from(variant.weight);
+        if roll < weight {
+            return Some(variant);
+        }
+        roll -= weight;
+    }
+    None
+}
+
+/// Assign a visitor to a variant
+///
+/// A `sticky` name of an existing variant keeps the visitor on it; otherwise
+/// the variant is drawn at random according to the weights.
+pub fn choose_variant<'a>(variants: &'a [Variant], sticky: Option<&str>) -> Option<&'a Variant> {
+    if let Some(name) = sticky
+        && let Some(variant) = variants.iter().find(|variant| variant.name == name)
+    {
+        return Some(variant);
+    }
+
+    let total: u64 = variants
+        .iter()
+        .map(|variant| u64::from(variant.weight))
+        .sum();
+    if total == 0 {
+        return None;
+    }
+
+    pick_variant(variants, rand::rng().random_range(0..total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variant(name: &str, weight: u32) -> Variant {
+        Variant {
+            name: name.to_string(),
+            url: format!("https://example.com/{}", name.trim()),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_normalize_variants() {
+        let normalized = normalize_variants(vec![variant(" a ", 1), variant("b", 3)]).unwrap();
+        assert_eq!(normalized[0].name, "a");
+        assert_eq!(normalized[1].weight, 3);
+
+        assert!(normalize_variants(vec![variant("a", 1), variant("a", 2)]).is_err());
+        assert!(normalize_variants(vec![variant("", 1)]).is_err());
+        assert!(normalize_variants(vec![variant("a;b", 1)]).is_err());
+        assert!(normalize_variants(vec![variant("a", 0)]).is_err());
+
+        let mut invalid = variant("a", 1);
+        invalid.url = "ftp://example.com".to_string();
+        assert!(invalid.normalize().is_err());
+
+        let parsed: Variant =
+            serde_json::from_str(r#"{"name":"a","url":"https://example.com"}"#).unwrap();
+        assert_eq!(parsed.weight, 1);
+    }
+
+    #[test]
+    fn test_pick_variant_by_weight() {
+        let variants = vec![variant("a", 1), variant("b", 3)];
+
+        assert_eq!(pick_variant(&variants, 0).unwrap().name, "a");
+        assert_eq!(pick_variant(&variants, 1).unwrap().name, "b");
+        assert_eq!(pick_variant(&variants, 3).unwrap().name, "b");
+        assert!(pick_variant(&variants, 4).is_none());
+    }
+
+    #[test]
+    fn test_choose_variant() {
+        let variants = vec![variant("a", 1), variant("b", 1)];
+
+        // A known sticky name always wins
+        for _ in 0..20 {
+            assert_eq!(choose_variant(&variants, Some("b")).unwrap().name, "b");
+        }
+
+        // Unknown names fall back to a random draw
+        let chosen = choose_variant(&variants, Some("gone")).unwrap();
+        assert!(chosen.name == "a" || chosen.name == "b");
+
+        // A single variant takes all traffic
+        let only = vec![variant("solo", 5)];
+        assert_eq!(choose_variant(&only, None).unwrap().name, "solo");
+
+        assert!(choose_variant(&[], None).is_none());
+    }
+}
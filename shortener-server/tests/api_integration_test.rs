@@ -21,9 +21,12 @@ use shortener_server::{
     },
     db::DbFactory,
     geoip::NullGeoIp,
-    repositories::{HistoryRepositoryImpl, UrlRepositoryImpl},
+    repositories::{
+        HistoryRepositoryImpl, RevisionRepositoryImpl, TagRepositoryImpl, UrlRepositoryImpl,
+        UserRepositoryImpl,
+    },
     router::{AppState, create_router},
-    services::{HistoryService, ShortenService},
+    services::{HistoryService, ShortenService, UserService},
 };
 use std::sync::Arc;
 use tower::ServiceExt;
@@ -41,6 +44,7 @@ fn create_test_config() -> Config {
             code_length: 6,
             code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
                 .to_string(),
+            ..Default::default()
         },
         admin: AdminConfig {
             username: "admin".to_string(),
@@ -79,12 +83,16 @@ async fn setup_test_app() -> Router {
     DbFactory::run_migrations(&db).await.unwrap();
 
     let url_repo = Arc::new(UrlRepositoryImpl::new(db.clone()));
-    let history_repo = Arc::new(HistoryRepositoryImpl::new(db));
+    let tag_repo = Arc::new(TagRepositoryImpl::new(db.clone()));
+    let revision_repo = Arc::new(RevisionRepositoryImpl::new(db.clone()));
+    let history_repo = Arc::new(HistoryRepositoryImpl::new(db.clone()));
     let cache: Arc<dyn Cache> = Arc::new(NullCache::new());
     let geoip = Some(Arc::new(NullGeoIp::new()) as Arc<dyn shortener_server::geoip::GeoIp>);
 
     let shorten_service = Arc::new(ShortenService::new(
         url_repo,
+        tag_repo,
+        revision_repo,
         cache,
         config.shortener.clone(),
         config.server.site_url.clone(),
@@ -92,9 +100,13 @@ async fn setup_test_app() -> Router {
 
     let history_service = Arc::new(HistoryService::new(history_repo, geoip));
 
+    let user_service = Arc::new(UserService::new(Arc::new(UserRepositoryImpl::new(db))));
+    user_service.ensure_admin(&config.admin).await.unwrap();
+
     let state = AppState {
         shorten_service,
         history_service,
+        user_service,
         config: Arc::new(config),
     };
 
@@ -112,7 +124,9 @@ async fn setup_test_app_with_redis() -> Router {
     DbFactory::run_migrations(&db).await.unwrap();
 
     let url_repo = Arc::new(UrlRepositoryImpl::new(db.clone()));
-    let history_repo = Arc::new(HistoryRepositoryImpl::new(db));
+    let tag_repo = Arc::new(TagRepositoryImpl::new(db.clone()));
+    let revision_repo = Arc::new(RevisionRepositoryImpl::new(db.clone()));
+    let history_repo = Arc::new(HistoryRepositoryImpl::new(db.clone()));
 
     // Try to connect to Redis, fallback to NullCache if unavailable
     let cache: Arc<dyn Cache> = match RedisCache::new(
@@ -130,6 +144,8 @@ async fn setup_test_app_with_redis() -> Router {
 
     let shorten_service = Arc::new(ShortenService::new(
         url_repo,
+        tag_repo,
+        revision_repo,
         cache,
         config.shortener.clone(),
         config.server.site_url.clone(),
@@ -137,9 +153,13 @@ async fn setup_test_app_with_redis() -> Router {
 
     let history_service = Arc::new(HistoryService::new(history_repo, geoip));
 
+    let user_service = Arc::new(UserService::new(Arc::new(UserRepositoryImpl::new(db))));
+    user_service.ensure_admin(&config.admin).await.unwrap();
+
     let state = AppState {
         shorten_service,
         history_service,
+        user_service,
         config: Arc::new(config),
     };
 
@@ -18,6 +18,7 @@ fn create_test_config() -> Config {
             code_length: 6,
             code_charset: "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
                 .to_string(),
+            ..Default::default()
         },
         admin: shortener_server::config::AdminConfig {
             username: "admin".to_string(),